@@ -0,0 +1,210 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::str::FromStr;
+
+use bzxc_shared::Token;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+const MAX_WIDTH: usize = 80;
+const INDENT: &str = "  ";
+
+/*
+ * Output format of the compiler dumps
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DumpFormat {
+    Pretty,
+    Json,
+}
+
+impl FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(DumpFormat::Pretty),
+            "json" => Ok(DumpFormat::Json),
+            _ => Err(format!("Unknown dump format {}", s)),
+        }
+    }
+}
+
+/*
+ * Which stages of the compiler should be dumped to stdout
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct Dump {
+    pub tokens: bool,
+    pub ast: bool,
+    pub typed_ast: bool,
+    pub format: DumpFormat,
+}
+
+impl Dump {
+    /*
+     * Dumps nothing, compiles as usual
+     */
+    pub fn none() -> Self {
+        Dump {
+            tokens: false,
+            ast: false,
+            typed_ast: false,
+            format: DumpFormat::Pretty,
+        }
+    }
+
+    /*
+     * Whether any stage is being dumped, the compiler stops after the last one
+     */
+    pub fn any(&self) -> bool {
+        self.tokens || self.ast || self.typed_ast
+    }
+
+    /*
+     * Prints the lexed tokens, one per line in pretty format
+     */
+    pub fn print_tokens(&self, tokens: &[Token]) {
+        match self.format {
+            DumpFormat::Json => self.print(&tokens),
+            DumpFormat::Pretty => {
+                for token in tokens {
+                    let value = serde_json::to_value(&token.value).unwrap();
+                    println!(
+                        "{}..{} {}",
+                        token.pos_start.index,
+                        token.pos_end.index,
+                        pretty(&value, 0)
+                    );
+                }
+            }
+        }
+    }
+
+    /*
+     * Prints any serializable tree in the chosen format
+     */
+    pub fn print<T: Serialize>(&self, tree: &T) {
+        match self.format {
+            DumpFormat::Json => println!("{}", serde_json::to_string_pretty(tree).unwrap()),
+            DumpFormat::Pretty => println!("{}", pretty(&serde_json::to_value(tree).unwrap(), 0)),
+        }
+    }
+}
+
+/*
+ * Enum variants are serialized as a map with a single capitalized key
+ */
+fn variant(map: &Map<String, Value>) -> Option<(&String, &Value)> {
+    if map.len() != 1 {
+        return None;
+    }
+    let (name, value) = map.iter().next().unwrap();
+    if name.starts_with(char::is_uppercase) {
+        Some((name, value))
+    } else {
+        None
+    }
+}
+
+/*
+ * Types of the typed tree are serialized the way they are written in BlazeX source,
+ * so they're shown unquoted, the untyped tree has nodes under the same keys
+ */
+fn type_field<'a>(key: &str, value: &'a Value) -> Option<&'a str> {
+    match (key, value) {
+        ("ty" | "prev" | "base" | "class", Value::String(ty)) => Some(ty),
+        _ => None,
+    }
+}
+
+/*
+ * Renders a value on a single line
+ */
+fn inline(value: &Value) -> String {
+    match value {
+        Value::Array(items) => format!(
+            "[{}]",
+            items.iter().map(inline).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(map) => match variant(map) {
+            Some((name, Value::Array(items))) => format!(
+                "{}({})",
+                name,
+                items.iter().map(inline).collect::<Vec<_>>().join(", ")
+            ),
+            Some((name, Value::Object(fields))) if variant(fields).is_none() => {
+                format!("{} {}", name, inline(&Value::Object(fields.clone())))
+            }
+            Some((name, value)) => format!("{}({})", name, inline(value)),
+            None if map.is_empty() => "{}".to_string(),
+            None => format!(
+                "{{ {} }}",
+                map.iter()
+                    .map(|(key, value)| match type_field(key, value) {
+                        Some(ty) => format!("{}: {}", key, ty),
+                        None => format!("{}: {}", key, inline(value)),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        },
+        _ => value.to_string(),
+    }
+}
+
+/*
+ * Renders a value indented by `depth`, breaking it over lines when it does not fit
+ */
+fn pretty(value: &Value, depth: usize) -> String {
+    let line = inline(value);
+    if line.len() + depth * INDENT.len() <= MAX_WIDTH {
+        return line;
+    }
+
+    let indent = INDENT.repeat(depth);
+    let inner = INDENT.repeat(depth + 1);
+    let items = |items: &Vec<Value>| {
+        items
+            .iter()
+            .map(|item| format!("{}{},\n", inner, pretty(item, depth + 1)))
+            .collect::<String>()
+    };
+    let fields = |map: &Map<String, Value>| {
+        map.iter()
+            .map(|(key, value)| match type_field(key, value) {
+                Some(ty) => format!("{}{}: {},\n", inner, key, ty),
+                None => format!("{}{}: {},\n", inner, key, pretty(value, depth + 1)),
+            })
+            .collect::<String>()
+    };
+
+    match value {
+        Value::Array(list) => format!("[\n{}{}]", items(list), indent),
+        Value::Object(map) => match variant(map) {
+            Some((name, Value::Array(list))) => format!("{}(\n{}{})", name, items(list), indent),
+            Some((name, Value::Object(map))) if variant(map).is_none() => {
+                format!("{} {{\n{}{}}}", name, fields(map), indent)
+            }
+            Some((name, value)) => format!(
+                "{}(\n{}{},\n{})",
+                name,
+                inner,
+                pretty(value, depth + 1),
+                indent
+            ),
+            None => format!("{{\n{}{}}}", fields(map), indent),
+        },
+        _ => line,
+    }
+}
@@ -28,32 +28,76 @@ use llvm_sys::transforms::scalar::{
 };
 use llvm_sys::transforms::util::LLVMAddPromoteMemoryToRegisterPass;
 
+pub mod dump;
 pub mod test;
 
 use bzxc_lexer::Lexer;
+use bzxc_lints::Lints;
 use bzxc_llvm::Compiler;
 use bzxc_parser::parser::Parser;
-use bzxc_shared::to_c_str;
+use bzxc_shared::{to_c_str, Session, Severity};
 use bzxc_type_system::TypeSystem;
+use dump::Dump;
+
+/*
+ * How a file is compiled, `CompileOptions::new` builds it to an executable with the defaults
+ */
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    pub is_quiet: bool,
+    pub watch: bool,
+    pub no_std: bool,
+    pub out_file: String,
+    pub llvm: bool,
+    pub dump: Dump,
+    pub lints: Lints,
+    pub freeze_vals: bool,
+    pub checked_arith: bool,
+}
+
+impl CompileOptions {
+    pub fn new(out_file: String) -> Self {
+        CompileOptions {
+            is_quiet: false,
+            watch: false,
+            no_std: false,
+            out_file,
+            llvm: false,
+            dump: Dump::none(),
+            lints: Lints::default(),
+            freeze_vals: false,
+            checked_arith: true,
+        }
+    }
+}
+
+/*
+ * Compiles a file in a session of its own, nothing of it is left once it's done
+ */
+pub unsafe fn compile(file_name: String, cnt: String, options: &CompileOptions) -> i32 {
+    Session::enter(|| compile_file(file_name, cnt, options))
+}
+
+unsafe fn compile_file(file_name: String, cnt: String, options: &CompileOptions) -> i32 {
+    let CompileOptions {
+        is_quiet,
+        watch,
+        no_std,
+        out_file,
+        llvm,
+        dump,
+        lints,
+        freeze_vals,
+        checked_arith,
+    } = options.clone();
 
-pub unsafe fn compile(
-    file_name: String,
-    cnt: String,
-    is_quiet: bool,
-    watch: bool,
-    no_std: bool,
-    out_file: String,
-    llvm: bool,
-) -> i32 {
     if !is_quiet {
         println!("----BlazeX compiler----");
         println!("Version: {}", env!("CARGO_PKG_VERSION"));
         println!("File: {}", file_name);
     }
 
-    let name = Box::leak(file_name.to_owned().into_boxed_str());
-    let content = Box::leak(cnt.to_owned().into_boxed_str());
-    let lexed = Lexer::new(name, content).lex();
+    let lexed = Lexer::new(file_name.clone(), cnt).lex();
     let mut tokens = vec![];
     match lexed {
         Ok(lexed) => {
@@ -67,6 +111,13 @@ pub unsafe fn compile(
         }
     }
 
+    if dump.tokens {
+        dump.print_tokens(&tokens);
+        if !dump.ast && !dump.typed_ast {
+            return 0;
+        }
+    }
+
     let parsed = Parser::new(tokens).parse();
     if parsed.error.is_some() || parsed.node.is_none() {
         parsed.error.unwrap().prettify();
@@ -75,10 +126,58 @@ pub unsafe fn compile(
         }
     }
 
+    if dump.ast {
+        dump.print(parsed.node.as_ref().unwrap());
+        if !dump.typed_ast {
+            return 0;
+        }
+    }
+
+    if !dump.typed_ast {
+        if let Some(node) = &parsed.node {
+            // warnings don't stop the compilation unless their lint is denied
+            let diagnostics = lints.check(node);
+            let denied = diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.severity == Severity::Error);
+            for diagnostic in diagnostics {
+                diagnostic.prettify();
+            }
+            if denied {
+                return 1;
+            }
+        }
+    }
+
     let context = LLVMContextCreate();
-    let llvm_node = TypeSystem::new(parsed.node.unwrap(), context).llvm_node();
+    let mut type_system = TypeSystem::new(parsed.node.unwrap(), context);
+    type_system.freeze_vals = freeze_vals;
+    type_system.checked_arith = checked_arith;
+    if dump.typed_ast {
+        let typed = type_system.typed_node();
+        LLVMContextDispose(context);
+        return match typed {
+            Ok(typed) => {
+                dump.print(&typed);
+                0
+            }
+            Err(error) => {
+                error.prettify();
+                1
+            }
+        };
+    }
+
+    let llvm_node = match type_system.llvm_node() {
+        Ok(llvm_node) => llvm_node,
+        Err(error) => {
+            error.prettify();
+            LLVMContextDispose(context);
+            return 1;
+        }
+    };
 
-    let module = LLVMModuleCreateWithNameInContext(to_c_str(name).as_ptr(), context);
+    let module = LLVMModuleCreateWithNameInContext(to_c_str(&file_name).as_ptr(), context);
     let builder = LLVMCreateBuilderInContext(context);
 
     let fpm = LLVMCreateFunctionPassManager(LLVMCreateModuleProviderForExistingModule(module));
@@ -92,7 +191,9 @@ pub unsafe fn compile(
 
     LLVMEnablePrettyStackTrace();
 
-    Compiler::init(context, builder, module, fpm, llvm_node).compile_main();
+    let mut compiler = Compiler::init(context, builder, module, fpm, llvm_node);
+    compiler.checked_arith = checked_arith;
+    compiler.compile_main();
     if llvm {
         LLVMDumpModule(module);
     }
@@ -13,7 +13,9 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
-use blazex::compile;
+use blazex::dump::{Dump, DumpFormat};
+use blazex::{compile, CompileOptions};
+use bzxc_lints::{Level, Lints};
 use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
 use std::path::PathBuf;
 use std::process::exit;
@@ -61,19 +63,92 @@ struct CmdParams {
      */
     #[structopt(long, short = "no-std")]
     pub no_std: bool,
+
+    /*
+     * Print the lexed tokens and stop
+     */
+    #[structopt(long)]
+    pub dump_tokens: bool,
+
+    /*
+     * Print the parsed AST and stop
+     */
+    #[structopt(long)]
+    pub dump_ast: bool,
+
+    /*
+     * Print the AST with inferred types and stop
+     */
+    #[structopt(long)]
+    pub dump_typed_ast: bool,
+
+    /*
+     * Format of the dumps, pretty or json (Default: pretty)
+     */
+    #[structopt(long, default_value = "pretty", possible_values = &["pretty", "json"])]
+    pub format: DumpFormat,
+
+    /*
+     * Lints to warn about, `warnings` stands for all of them
+     */
+    #[structopt(short = "W", number_of_values = 1)]
+    pub warn: Vec<String>,
+
+    /*
+     * Lints to silence
+     */
+    #[structopt(short = "A", number_of_values = 1)]
+    pub allow: Vec<String>,
+
+    /*
+     * Lints which stop the compilation
+     */
+    #[structopt(short = "D", number_of_values = 1)]
+    pub deny: Vec<String>,
+
+    /*
+     * Turn every warning into an error, for CI
+     */
+    #[structopt(long)]
+    pub deny_warnings: bool,
+
+    /*
+     * Forbid changing the properties of objects bound with `val`
+     */
+    #[structopt(long)]
+    pub freeze_vals: bool,
+
+    /*
+     * Panic when integer arithmetic overflows or divides by zero, the default in debug builds
+     */
+    #[structopt(long)]
+    pub checked_arith: bool,
+
+    /*
+     * Let integer arithmetic wrap around silently, even in debug builds
+     */
+    #[structopt(long, conflicts_with = "checked-arith")]
+    pub unchecked_arith: bool,
 }
 
 /*
 * Entry Point of the Compiler
 */
 fn main() {
-    let cmd_params = CmdParams::from_args();
+    let matches = CmdParams::clap().get_matches();
+    let cmd_params = CmdParams::from_clap(&matches);
     let file_name = cmd_params.path.as_os_str().to_str().unwrap().to_string();
     if !file_name.ends_with(".bzx") {
         eprintln!("Unexpected file {}", file_name);
         exit(1);
     }
-    let is_quiet = cmd_params.quiet;
+    let dump = Dump {
+        tokens: cmd_params.dump_tokens,
+        ast: cmd_params.dump_ast,
+        typed_ast: cmd_params.dump_typed_ast,
+        format: cmd_params.format,
+    };
+    let is_quiet = cmd_params.quiet || dump.any();
     let out_file = if let Some(out) = cmd_params.out {
         if out.ends_with(".o") {
             out.as_os_str().to_str().unwrap().to_string()
@@ -83,26 +158,55 @@ fn main() {
     } else {
         file_name.clone().replace(".bzx", ".o")
     };
+    // lint levels are set in the order they're given, so later ones override earlier ones
+    let mut levels = vec![];
+    for (arg, names, level) in [
+        ("allow", &cmd_params.allow, Level::Allow),
+        ("warn", &cmd_params.warn, Level::Warn),
+        ("deny", &cmd_params.deny, Level::Deny),
+    ]
+    .iter()
+    {
+        if let Some(indices) = matches.indices_of(arg) {
+            levels.extend(
+                indices
+                    .zip(names.iter())
+                    .map(|(index, name)| (index, name, *level)),
+            );
+        }
+    }
+    levels.sort_by_key(|(index, _, _)| *index);
+
+    let mut lints = Lints::default();
+    for (_, name, level) in levels {
+        if !lints.set(name, level) {
+            eprintln!("Unknown lint {}", name);
+            exit(1);
+        }
+    }
+    if cmd_params.deny_warnings {
+        lints.deny_warnings();
+    }
     let watch = cmd_params.watch;
-    let spit_ll = cmd_params.llvm;
-    let no_std = cmd_params.no_std;
+    let options = CompileOptions {
+        is_quiet,
+        watch,
+        no_std: cmd_params.no_std,
+        out_file,
+        llvm: cmd_params.llvm,
+        dump,
+        lints,
+        freeze_vals: cmd_params.freeze_vals,
+        checked_arith: cmd_params.checked_arith
+            || (cfg!(debug_assertions) && !cmd_params.unchecked_arith),
+    };
 
     /*
      * Compiling to Object File
      */
     let compile_with_config = || {
         let cnt = std::fs::read_to_string(file_name.clone()).expect("could not read file");
-        unsafe {
-            compile(
-                file_name.clone(),
-                cnt,
-                is_quiet,
-                watch,
-                no_std,
-                out_file.clone(),
-                spit_ll,
-            )
-        }
+        unsafe { compile(file_name.clone(), cnt, &options) }
     };
 
     let init = compile_with_config();
@@ -2,6 +2,8 @@
 mod tests {
     use std::process::Command;
 
+    use crate::CompileOptions;
+
     #[test]
     fn compile() {
         let mut test_dir = std::env::current_dir().unwrap();
@@ -14,7 +16,14 @@ mod tests {
                 continue;
             }
 
-            let child_folder = std::fs::read_dir(folder.unwrap().path());
+            // the programs which have to fail are checked by `tests/errors.rs`,
+            // the dumps by `tests/dumps.rs`
+            let folder = folder.unwrap().path();
+            if folder.ends_with("errors") || folder.ends_with("dumps") {
+                continue;
+            }
+
+            let child_folder = std::fs::read_dir(folder);
             if child_folder.is_err() {
                 continue;
             }
@@ -34,8 +43,8 @@ mod tests {
                 let out_file = file_path.replace(".bzx", ".o");
                 let cnt = std::fs::read_to_string(file_path).unwrap();
                 unsafe {
-                    let res =
-                        super::super::compile(file_name, cnt, false, false, false, out_file, false);
+                    let options = CompileOptions::new(out_file);
+                    let res = super::super::compile(file_name, cnt, &options);
                     assert_eq!(res, 0);
                 }
             }
@@ -54,7 +63,14 @@ mod tests {
                 continue;
             }
 
-            let child_folder = std::fs::read_dir(folder.unwrap().path());
+            // the programs which have to fail are checked by `tests/errors.rs`,
+            // the dumps by `tests/dumps.rs`
+            let folder = folder.unwrap().path();
+            if folder.ends_with("errors") || folder.ends_with("dumps") {
+                continue;
+            }
+
+            let child_folder = std::fs::read_dir(folder);
             if child_folder.is_err() {
                 continue;
             }
@@ -68,7 +84,7 @@ mod tests {
                 let file_name = file.file_name().into_string().unwrap();
                 let file_path = file.path().into_os_string().into_string().unwrap();
 
-                if file_name.ends_with(".bzx") {
+                if !file_name.ends_with(".out") {
                     continue;
                 }
 
@@ -78,6 +94,17 @@ mod tests {
                     .output()
                     .expect(&format!("failed to execute {}", file_name));
                 assert!(cmd.status.success());
+
+                // programs with an `.expected` file next to them have to print exactly that
+                let expected = file_path.replace(".out", ".expected");
+                if let Ok(expected) = std::fs::read_to_string(expected) {
+                    assert_eq!(
+                        String::from_utf8_lossy(&cmd.stdout),
+                        expected,
+                        "{}",
+                        file_name
+                    );
+                }
             }
         }
     }
@@ -0,0 +1,62 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/*
+ * Every program in `tests/dumps` is dumped with the flags of the `@ args: ...` comment
+ * at its top, it prints exactly what its `.stdout` file holds
+ */
+#[test]
+fn dumps() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/dumps");
+    let mut programs = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "bzx"))
+        .collect::<Vec<_>>();
+    programs.sort();
+    assert!(!programs.is_empty());
+
+    for program in programs {
+        check(&dir, &program);
+    }
+}
+
+fn check(dir: &Path, program: &Path) {
+    let name = program.file_name().unwrap().to_str().unwrap();
+    let source = fs::read_to_string(program).unwrap();
+    let args = source
+        .lines()
+        .take_while(|line| line.starts_with('@'))
+        .find_map(|line| line.strip_prefix("@ args:"))
+        .unwrap_or_default();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_blazex"))
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .args(args.split_whitespace())
+        .arg(name)
+        .output()
+        .unwrap();
+
+    let expected = fs::read_to_string(program.with_extension("stdout")).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        expected,
+        "{}",
+        name
+    );
+    assert!(output.status.success(), "{}", name);
+}
@@ -0,0 +1,72 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/*
+ * Every program in `tests/errors` has to fail, either when it's compiled or when it's run.
+ * It prints exactly what its `.stderr` file holds and exits with 1, or the status of an
+ * `@ status: N` comment at its top. An `@ args: ...` comment passes flags to the compiler
+ */
+#[test]
+fn errors() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests/errors");
+    let mut programs = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "bzx"))
+        .collect::<Vec<_>>();
+    programs.sort();
+    assert!(!programs.is_empty());
+
+    for program in programs {
+        check(&dir, &program);
+    }
+}
+
+fn check(dir: &Path, program: &Path) {
+    let name = program.file_name().unwrap().to_str().unwrap();
+    let source = fs::read_to_string(program).unwrap();
+    let directive = |key: &str| {
+        source
+            .lines()
+            .take_while(|line| line.starts_with('@'))
+            .find_map(|line| line.strip_prefix(format!("@ {}:", key).as_str()))
+            .map(|value| value.trim().to_string())
+    };
+    let args = directive("args").unwrap_or_default();
+    let status = directive("status").map_or(1, |status| status.parse().unwrap());
+
+    let compiled = Command::new(env!("CARGO_BIN_EXE_blazex"))
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .arg("-q")
+        .args(args.split_whitespace())
+        .arg(name)
+        .output()
+        .unwrap();
+    // programs which compile fail when they're run
+    let output = if compiled.status.success() {
+        let executable = dir.join(name.replace(".bzx", ".out"));
+        let output = Command::new(&executable).output().unwrap();
+        fs::remove_file(executable).unwrap();
+        output
+    } else {
+        compiled
+    };
+
+    let expected = fs::read_to_string(program.with_extension("stderr")).unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stderr), expected, "{}", name);
+    assert_eq!(output.status.code(), Some(status), "{}", name);
+}
@@ -0,0 +1,74 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_lexer::Lexer;
+use bzxc_parser::parser::Parser;
+use bzxc_shared::{Error, Session, TypedNode};
+use bzxc_type_system::TypeSystem;
+use llvm_sys::core::{LLVMContextCreate, LLVMContextDispose};
+
+const PROGRAM: &str = "
+fun pick(a, b) { a }
+var point = { x: 1, y: pick(2.5, \"two\") }
+var total = point.x + 2
+";
+
+const MISTAKE: &str = "
+var point = { x: 1 }
+var total = point.x + \"two\"
+";
+
+/*
+ * Types the program in a session of its own
+ */
+fn typed(program: &str) -> Result<TypedNode, Error> {
+    Session::enter(|| unsafe {
+        let tokens = Lexer::new("session.bzx".to_string(), program.to_string())
+            .lex()
+            .unwrap();
+        let node = Parser::new(tokens).parse().node.unwrap();
+        let context = LLVMContextCreate();
+        let typed = TypeSystem::new(node, context).typed_node();
+        LLVMContextDispose(context);
+        typed
+    })
+}
+
+/*
+ * Nothing of a compilation is left for the next one in the same process,
+ * type variables, objects and bindings are numbered the same way again
+ */
+#[test]
+fn sessions() {
+    let first = format!("{:?}", typed(PROGRAM).unwrap());
+    let second = format!("{:?}", typed(PROGRAM).unwrap());
+    assert_eq!(first, second);
+    assert!(first.contains("\"total\""));
+
+    let first = typed(MISTAKE).unwrap_err();
+    let second = typed(MISTAKE).unwrap_err();
+    assert_eq!(format!("{:?}", first), format!("{:?}", second));
+    assert_eq!(first.description, second.description);
+}
+
+/*
+ * Symbols outlive their session, they're still shown once it's over
+ */
+#[test]
+fn symbols() {
+    let tokens = Session::enter(|| {
+        Lexer::new("session.bzx".to_string(), PROGRAM.to_string())
+            .lex()
+            .unwrap()
+    });
+    assert!(format!("{:?}", tokens).contains("Identifier(\"#0\")"));
+}
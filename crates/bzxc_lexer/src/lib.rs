@@ -13,40 +13,62 @@
 
 mod literals;
 mod logical;
-use bzxc_shared::{to_static_str, Error, Position, Token, Tokens};
+use bzxc_shared::{Error, Position, Session, Symbol, Token, Tokens};
 
 /*
 * Returns all the keywords in the language
 */
-pub fn get_keywords() -> Vec<String> {
+pub fn get_keywords() -> Vec<&'static str> {
     vec![
-        string("val"),
-        string("var"),
-        string("and"),
-        string("or"),
-        string("not"),
-        string("if"),
-        string("else"),
-        string("for"),
-        string("to"),
-        string("step"),
-        string("while"),
-        string("fun"),
-        string("return"),
-        string("class"),
-        string("new"),
-        string("extern"),
-        string("soul"),
-        string("static"),
-        string("void"),
-        string("int"),
-        string("float"),
-        string("bool"),
-        string("string"),
-        string("char"),
-        string("CObject"),
-        string("CArray"),
-        string("CToBzxObject"),
+        "val",
+        "var",
+        "const",
+        "and",
+        "or",
+        "not",
+        "if",
+        "else",
+        "for",
+        "to",
+        "step",
+        "as",
+        "while",
+        "fun",
+        "return",
+        "class",
+        "new",
+        "extern",
+        "soul",
+        "super",
+        "null",
+        "extends",
+        "interface",
+        "implements",
+        "enum",
+        "match",
+        "static",
+        "void",
+        "int",
+        "float",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "i128",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "u128",
+        "f32",
+        "f64",
+        "bool",
+        "string",
+        "char",
+        "any",
+        "CObject",
+        "CArray",
+        "CToBzxObject",
     ]
 }
 
@@ -57,13 +79,6 @@ pub(crate) fn get_number() -> Vec<u32> {
     vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
 }
 
-/*
-* Converts str to String
-*/
-fn string(str: &str) -> String {
-    return String::from(str);
-}
-
 /*
 * Return all ascii charecters
 */
@@ -78,7 +93,7 @@ pub(crate) fn get_ascii_letters() -> Vec<&'static str> {
 */
 pub(crate) fn get_ascii_letters_and_digits() -> Vec<&'static str> {
     let mut vec = get_ascii_letters();
-    vec.extend(get_number().iter().map(|x| to_static_str(x.to_string())));
+    vec.extend("0123456789".split(""));
     vec
 }
 
@@ -86,7 +101,6 @@ pub(crate) fn get_ascii_letters_and_digits() -> Vec<&'static str> {
 * Goes through the file and lexes into a Array of token
 */
 pub struct Lexer {
-    pub file_name: String,
     pub text: String,
     pub current_char: Option<char>,
     pub position: Position,
@@ -94,14 +108,13 @@ pub struct Lexer {
 
 impl Lexer {
     /*
-     * Creates a new Lexer Instance
+     * Creates a new Lexer Instance, the file is added to the running session
      */
-    pub fn new(file_name: &'static str, text: &'static str) -> Lexer {
+    pub fn new(file_name: String, text: String) -> Lexer {
         let lexer = Lexer {
-            file_name: String::from(file_name),
-            text: String::from(text),
             current_char: Some(text.chars().collect::<Vec<char>>()[0]),
-            position: Position::new(0, file_name, text),
+            position: Position::new(0, Session::add_file(file_name, text.clone())),
+            text,
         };
         lexer
     }
@@ -179,13 +192,19 @@ impl Lexer {
                     '\'' => tokens.push(self.make_char()?),
                     '|' => tokens.push(self.make_or()?),
                     '&' => tokens.push(self.make_and()?),
+                    '?' => tokens.push(self.make_question_mark()),
                     _ => {
                         let no = self.current_char.unwrap().to_digit(36);
                         if no.is_some() {
                             if get_number().contains(&no.unwrap())
                                 || self.current_char.unwrap() == '.'
                             {
-                                tokens.push(self.make_number());
+                                // in `pair.0.1` a number after a dot is an index
+                                let index = matches!(
+                                    tokens.last(),
+                                    Some(token) if token.value == Tokens::Dot
+                                );
+                                tokens.push(self.make_number(index));
                             } else if get_ascii_letters()
                                 .contains(&self.current_char.unwrap().to_string().as_str())
                             {
@@ -193,6 +212,8 @@ impl Lexer {
                             } else {
                                 token_is_unknown = true;
                             }
+                        } else if self.current_char.unwrap() == '_' {
+                            tokens.push(self.make_identifiers());
                         } else {
                             token_is_unknown = true;
                         }
@@ -211,11 +232,7 @@ impl Lexer {
                     "Illegal Character",
                     start_1,
                     self.position.clone(),
-                    Box::leak(
-                        format!("Unexpected Character '{}'", char)
-                            .to_owned()
-                            .into_boxed_str(),
-                    ),
+                    format!("Unexpected Character '{}'", char),
                 ));
             }
         }
@@ -260,12 +277,15 @@ impl Lexer {
             self.advance();
         }
 
-        let identifier_type = if get_keywords().contains(&identifier) {
-            Tokens::Keyword(to_static_str(identifier))
+        let keyword = get_keywords()
+            .into_iter()
+            .find(|keyword| *keyword == identifier);
+        let identifier_type = if let Some(keyword) = keyword {
+            Tokens::Keyword(keyword)
         } else if identifier == "true".to_string() || identifier == "false".to_string() {
             Tokens::Boolean(identifier.parse().ok().unwrap())
         } else {
-            Tokens::Identifier(to_static_str(identifier))
+            Tokens::Identifier(Symbol::intern(&identifier))
         };
         Token::new(identifier_type, start, self.position.clone())
     }
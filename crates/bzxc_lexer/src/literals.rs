@@ -1,12 +1,12 @@
-use bzxc_shared::{to_static_str, Error, Token, Tokens};
+use bzxc_shared::{Error, Symbol, Token, Tokens};
 
 use crate::Lexer;
 
 impl Lexer {
     /*
-     * Makes a number token
+     * Makes a number token, an `index` is an integer so a dot after it isn't a decimal point
      */
-    pub(crate) fn make_number(&mut self) -> Token {
+    pub(crate) fn make_number(&mut self, index: bool) -> Token {
         let mut str_num = String::new();
         let mut dot_count = 0;
         let start = self.position.clone();
@@ -18,6 +18,9 @@ impl Lexer {
                 break;
             }
             if self.current_char.unwrap() == '.' {
+                if index {
+                    break;
+                }
                 dot_count += 1;
             }
             str_num.push(self.current_char.unwrap());
@@ -77,7 +80,7 @@ impl Lexer {
         self.advance();
 
         Token::new(
-            Tokens::String(to_static_str(str_raw)),
+            Tokens::String(Symbol::intern(&str_raw)),
             start,
             self.position.clone(),
         )
@@ -15,6 +15,11 @@ impl Lexer {
             return Token::new(Tokens::DoubleEquals, start, self.position.clone());
         }
 
+        if self.current_char.unwrap_or(' ') == '>' {
+            self.advance();
+            return Token::new(Tokens::Arrow, start, self.position.clone());
+        }
+
         Token::new(Tokens::Equals, start, self.position.clone())
     }
 
@@ -110,4 +115,21 @@ impl Lexer {
             "Expected one more '&'",
         ))
     }
+
+    /*
+     * Makes a QUESTION_MARK, SAFE_DOT or ELVIS Token
+     */
+    pub(crate) fn make_question_mark(&mut self) -> Token {
+        let start = self.position.clone();
+        self.advance();
+
+        let token = match self.current_char.unwrap_or(' ') {
+            '.' => Tokens::SafeDot,
+            ':' => Tokens::Elvis,
+            _ => return Token::new(Tokens::QuestionMark, start, self.position.clone()),
+        };
+
+        self.advance();
+        Token::new(token, start, self.position.clone())
+    }
 }
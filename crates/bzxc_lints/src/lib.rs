@@ -0,0 +1,142 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+mod linter;
+
+use bzxc_shared::{Error, Node, Position, Severity};
+use linter::Linter;
+
+/*
+ * Checks which warn about code that compiles but is likely a mistake
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lint {
+    UnusedVariables,
+    UnusedExterns,
+    UnreachableCode,
+    ShadowedBindings,
+    AlwaysTrueComparisons,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariables,
+        Lint::UnusedExterns,
+        Lint::UnreachableCode,
+        Lint::ShadowedBindings,
+        Lint::AlwaysTrueComparisons,
+    ];
+
+    /*
+     * Name the lint is given on the command line
+     */
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused-variables",
+            Lint::UnusedExterns => "unused-externs",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::ShadowedBindings => "shadowed-bindings",
+            Lint::AlwaysTrueComparisons => "always-true-comparisons",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.iter().copied().find(|lint| lint.name() == name)
+    }
+}
+
+/*
+ * What's done when a lint is hit
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/*
+ * Level of every lint, all of them warn by default
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lints([Level; Lint::ALL.len()]);
+
+impl Default for Lints {
+    fn default() -> Self {
+        Lints([Level::Warn; Lint::ALL.len()])
+    }
+}
+
+impl Lints {
+    pub fn level(&self, lint: Lint) -> Level {
+        self.0[lint as usize]
+    }
+
+    /*
+     * Sets the level of the lint `name`, `warnings` sets the one of all of them.
+     * Returns false if there's no such lint
+     */
+    pub fn set(&mut self, name: &str, level: Level) -> bool {
+        if name == "warnings" {
+            self.0 = [level; Lint::ALL.len()];
+            return true;
+        }
+
+        match Lint::from_name(name) {
+            Some(lint) => {
+                self.0[lint as usize] = level;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /*
+     * Turns every lint which warns into one which denies
+     */
+    pub fn deny_warnings(&mut self) {
+        for level in self.0.iter_mut() {
+            if *level == Level::Warn {
+                *level = Level::Deny;
+            }
+        }
+    }
+
+    /*
+     * Lints the program, the diagnostics of denied lints are errors
+     */
+    pub fn check(&self, node: &Node) -> Vec<Error> {
+        let mut linter = Linter::new();
+        linter.lint(node);
+
+        let mut diagnostics: Vec<Error> = linter
+            .warnings
+            .into_iter()
+            .filter_map(|(lint, warning)| {
+                let warning = warning.with_code(lint.name());
+                match self.level(lint) {
+                    Level::Allow => None,
+                    Level::Warn => Some(warning),
+                    Level::Deny => Some(Error {
+                        severity: Severity::Error,
+                        ..warning
+                    }),
+                }
+            })
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.pos_start.index);
+        diagnostics
+    }
+}
+
+pub(crate) type Span = (Position, Position);
@@ -0,0 +1,390 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{walk, Error, Node, Token, Tokens, Visitor};
+
+use crate::{Lint, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Variable,
+    Parameter,
+    Extern,
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    name: String,
+    pos: Span,
+    kind: Kind,
+    used: bool,
+}
+
+/*
+ * Walks the AST keeping the bindings in scope the way the type system does,
+ * names starting with `_` are never warned about
+ */
+pub(crate) struct Linter {
+    scopes: Vec<Vec<Binding>>,
+    pub warnings: Vec<(Lint, Error)>,
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Linter {
+            scopes: vec![vec![]],
+            warnings: vec![],
+        }
+    }
+
+    pub fn lint(&mut self, node: &Node) {
+        // externs are declared before the rest of the program, like the type system does
+        if let Node::Statements { statements } = node {
+            for statement in statements {
+                if let Node::ExternNode { name, .. } = statement {
+                    self.declare(name, Kind::Extern);
+                }
+            }
+        }
+        self.visit(node);
+        self.pop_scope();
+    }
+
+    fn warn(&mut self, lint: Lint, name: &'static str, pos: Span, description: String) {
+        self.warnings
+            .push((lint, Error::warning(name, pos.0, pos.1, description)));
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(vec![]);
+    }
+
+    /*
+     * Bindings going out of scope without being used are reported
+     */
+    fn pop_scope(&mut self) {
+        for binding in self.scopes.pop().unwrap() {
+            if binding.used || binding.name.starts_with('_') {
+                continue;
+            }
+
+            let (lint, name, description) = match binding.kind {
+                Kind::Variable => (
+                    Lint::UnusedVariables,
+                    "Unused Variable",
+                    format!("`{}` is never used", binding.name),
+                ),
+                Kind::Parameter => (
+                    Lint::UnusedVariables,
+                    "Unused Parameter",
+                    format!("`{}` is never used", binding.name),
+                ),
+                Kind::Extern => (
+                    Lint::UnusedExterns,
+                    "Unused Extern",
+                    format!("`{}` is never called", binding.name),
+                ),
+            };
+            self.warn(lint, name, binding.pos, description);
+        }
+    }
+
+    fn declare(&mut self, token: &Token, kind: Kind) {
+        let name = token.value.into_string();
+        let pos = (token.pos_start, token.pos_end);
+        // names made up by the parser aren't written by anyone
+        if name == "_" || name.starts_with('%') {
+            return;
+        }
+        // hoisted bindings are met again where they're written
+        if self
+            .scopes
+            .iter()
+            .flatten()
+            .any(|binding| binding.pos == pos)
+        {
+            return;
+        }
+
+        let previous = self
+            .scopes
+            .iter()
+            .flatten()
+            .rev()
+            .find(|binding| binding.name == name)
+            .map(|binding| binding.pos);
+        if let (Some(previous), false) = (previous, name.starts_with('_')) {
+            let warning = Error::warning(
+                "Shadowed Binding",
+                pos.0,
+                pos.1,
+                format!("`{}` shadows an earlier binding", name),
+            )
+            .with_label(previous.0, previous.1, "first bound here");
+            self.warnings.push((Lint::ShadowedBindings, warning));
+        }
+
+        self.scopes.last_mut().unwrap().push(Binding {
+            name,
+            pos,
+            kind,
+            used: false,
+        });
+    }
+
+    fn use_name(&mut self, name: &str) {
+        let binding = self
+            .scopes
+            .iter_mut()
+            .flatten()
+            .rev()
+            .find(|binding| binding.name == name);
+        if let Some(binding) = binding {
+            binding.used = true;
+        }
+    }
+
+    /*
+     * Type annotation, the constants naming the lengths of its arrays are used
+     */
+    fn use_type(&mut self, ty: &Option<Node>) {
+        if let Some(ty) = ty {
+            self.visit(ty);
+        }
+    }
+
+    /*
+     * Statements after a `return` in the same block
+     */
+    fn unreachable(&mut self, statements: &[Node]) {
+        let index = statements
+            .iter()
+            .position(|statement| matches!(statement, Node::ReturnNode { .. }));
+        if let Some(index) = index {
+            let rest = &statements[index + 1..];
+            if let (Some(first), Some(last)) = (rest.first(), rest.last()) {
+                let ret = statements[index].get_pos();
+                let warning = Error::warning(
+                    "Unreachable Code",
+                    first.get_pos().0,
+                    last.get_pos().1,
+                    "this code is never run",
+                )
+                .with_label(ret.0, ret.1, "the function returns here");
+                self.warnings.push((Lint::UnreachableCode, warning));
+            }
+        }
+    }
+
+    /*
+     * Comparisons of a variable with itself, or of two literals which hold
+     */
+    fn always_true(&mut self, left: &Node, right: &Node, op: &Token, pos: Span) {
+        let always = match (left, right) {
+            (Node::VarAccessNode { token: a }, Node::VarAccessNode { token: b })
+                if a.value == b.value =>
+            {
+                matches!(
+                    op.value,
+                    Tokens::DoubleEquals | Tokens::LessThanEquals | Tokens::GreaterThanEquals
+                )
+            }
+            _ => match (literal(left), literal(right)) {
+                (Some(a), Some(b)) => match op.value {
+                    Tokens::DoubleEquals => a == b,
+                    Tokens::NotEquals => a != b,
+                    Tokens::LessThan => a < b,
+                    Tokens::LessThanEquals => a <= b,
+                    Tokens::GreaterThan => a > b,
+                    Tokens::GreaterThanEquals => a >= b,
+                    _ => false,
+                },
+                _ => false,
+            },
+        };
+
+        if always {
+            self.warn(
+                Lint::AlwaysTrueComparisons,
+                "Always True Comparison",
+                pos,
+                "this comparison is always true".to_string(),
+            );
+        }
+    }
+}
+
+/*
+ * Value of a number, char or boolean literal
+ */
+fn literal(node: &Node) -> Option<f64> {
+    match node {
+        Node::NumberNode { token } | Node::CharNode { token } | Node::BooleanNode { token } => {
+            match token.value {
+                Tokens::Int(i) => Some(i as f64),
+                Tokens::Float(f) => Some(f),
+                Tokens::Char(c) => Some(c as u32 as f64),
+                Tokens::Boolean(b) => Some(b as u8 as f64),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl Visitor<Node> for Linter {
+    fn visit(&mut self, node: &Node) {
+        match node {
+            Node::VarAccessNode { token } => self.use_name(&token.value.into_string()),
+            Node::VarAssignNode {
+                name, ty, value, ..
+            }
+            | Node::ConstNode { name, ty, value } => {
+                self.use_type(ty);
+                self.visit(value);
+                self.declare(name, Kind::Variable);
+            }
+            Node::VarReassignNode { name, typee, value } => {
+                self.visit(value);
+                // `x += 1` reads `x`, `x = 1` doesn't
+                if typee.value != Tokens::Equals {
+                    self.use_name(&name.value.into_string());
+                }
+            }
+            Node::TupleAssignNode { names, value, .. } => {
+                self.visit(value);
+                for name in names {
+                    self.declare(name, Kind::Variable);
+                }
+            }
+            Node::ExternNode { name, .. } => {
+                walk(self, node);
+                self.declare(name, Kind::Extern);
+            }
+            Node::FunDef {
+                args,
+                return_type,
+                body_node,
+                ..
+            } => {
+                self.use_type(return_type);
+                self.push_scope();
+                for (arg, ty) in args {
+                    self.use_type(ty);
+                    self.declare(arg, Kind::Parameter);
+                }
+                self.visit(body_node);
+                self.pop_scope();
+            }
+            Node::ClassDefNode {
+                methods,
+                properties,
+                constructor,
+                static_members,
+                base,
+                interfaces,
+                ..
+            } => {
+                self.use_type(base);
+                for interface in interfaces {
+                    self.visit(interface);
+                }
+                // properties are members of the objects, not variables
+                for (_, member) in static_members.iter().chain(properties) {
+                    match member {
+                        Node::VarAssignNode { ty, value, .. } => {
+                            self.use_type(ty);
+                            self.visit(value);
+                        }
+                        _ => self.visit(member),
+                    }
+                }
+                for (_, method) in methods {
+                    self.visit(method);
+                }
+                if let Some(constructor) = &**constructor {
+                    self.visit(constructor);
+                }
+            }
+            Node::IfNode { cases, else_case } => {
+                for (cond, body) in cases {
+                    self.push_scope();
+                    self.visit(cond);
+                    self.visit(body);
+                    self.pop_scope();
+                }
+                if let Some(else_case) = &**else_case {
+                    self.push_scope();
+                    self.visit(else_case);
+                    self.pop_scope();
+                }
+            }
+            Node::WhileNode { .. } => {
+                self.push_scope();
+                walk(self, node);
+                self.pop_scope();
+            }
+            Node::ForNode {
+                var_name_token,
+                start_value,
+                end_value,
+                step_value_node,
+                body_node,
+            } => {
+                self.push_scope();
+                self.visit(start_value);
+                self.declare(var_name_token, Kind::Variable);
+                self.visit(end_value);
+                self.visit(step_value_node);
+                self.visit(body_node);
+                self.pop_scope();
+            }
+            Node::MatchNode { value, arms } => {
+                self.visit(value);
+                for (pattern, body) in arms {
+                    self.push_scope();
+                    match pattern {
+                        Node::VariantPattern { bindings, .. } => {
+                            for binding in bindings {
+                                self.declare(binding, Kind::Variable);
+                            }
+                        }
+                        _ => self.visit(pattern),
+                    }
+                    self.visit(body);
+                    self.pop_scope();
+                }
+            }
+            Node::Statements { statements } => {
+                self.unreachable(statements);
+                walk(self, node);
+            }
+            Node::TypeArray { len, .. } => {
+                if let Some(len) = len {
+                    if let Tokens::Identifier(_) = len.value {
+                        self.use_name(&len.value.into_string());
+                    }
+                }
+                walk(self, node);
+            }
+            Node::BinaryNode {
+                left,
+                right,
+                op_token,
+            } => {
+                self.always_true(left, right, op_token, node.get_pos());
+                walk(self, node);
+            }
+            _ => walk(self, node),
+        }
+    }
+}
@@ -0,0 +1,142 @@
+/*
+* Copyright 2020 to 2021 BlazifyOrg
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+*    http://www.apache.org/licenses/LICENSE-2.0
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use llvm_sys::core::{
+    LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildCondBr, LLVMBuildGlobalStringPtr,
+    LLVMBuildICmp, LLVMBuildIsNull, LLVMBuildLoad, LLVMBuildPhi, LLVMBuildPointerCast,
+    LLVMBuildStore, LLVMBuildStructGEP, LLVMConstInt, LLVMGetElementType, LLVMGetInsertBlock,
+    LLVMInt32TypeInContext, LLVMInt8TypeInContext, LLVMPointerType, LLVMPositionBuilderAtEnd,
+    LLVMTypeOf,
+};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use llvm_sys::LLVMIntPredicate::LLVMIntEQ;
+
+use bzxc_shared::{to_c_str, Type};
+
+use crate::Compiler;
+
+impl Compiler {
+    /*
+     * Copies the value to the heap and wraps it in an `any` box
+     */
+    pub(super) unsafe fn box_any(
+        &mut self,
+        val: LLVMValueRef,
+        tag: u32,
+        type_name: String,
+    ) -> LLVMValueRef {
+        let data = self.malloc(LLVMTypeOf(val));
+        LLVMBuildStore(self.builder, val, data);
+
+        let boxed = self.malloc(LLVMGetElementType(self.any_type()));
+        let fields = [
+            LLVMConstInt(LLVMInt32TypeInContext(self.context), tag as u64, 0),
+            LLVMBuildGlobalStringPtr(
+                self.builder,
+                to_c_str(type_name.as_str()).as_ptr(),
+                to_c_str("type_name").as_ptr(),
+            ),
+            LLVMBuildPointerCast(
+                self.builder,
+                data,
+                LLVMPointerType(LLVMInt8TypeInContext(self.context), 0),
+                to_c_str("data").as_ptr(),
+            ),
+        ];
+        for (i, field) in fields.iter().enumerate() {
+            LLVMBuildStore(
+                self.builder,
+                *field,
+                LLVMBuildStructGEP(self.builder, boxed, i as u32, to_c_str("any_gep").as_ptr()),
+            );
+        }
+
+        boxed
+    }
+
+    /*
+     * Reads a value of type `ty` out of an `any` box,
+     * panics if the box is empty or holds another type
+     */
+    pub(super) unsafe fn unbox_any(
+        &mut self,
+        boxed: LLVMValueRef,
+        ty: LLVMTypeRef,
+        tag: u32,
+        type_name: String,
+    ) -> LLVMValueRef {
+        let func = self.fn_value();
+        let entry = LLVMGetInsertBlock(self.builder);
+        let check =
+            LLVMAppendBasicBlockInContext(self.context, func, to_c_str("any_check").as_ptr());
+        let fail = LLVMAppendBasicBlockInContext(self.context, func, to_c_str("any_fail").as_ptr());
+        let ok = LLVMAppendBasicBlockInContext(self.context, func, to_c_str("any_ok").as_ptr());
+
+        let is_null = LLVMBuildIsNull(self.builder, boxed, to_c_str("is_null").as_ptr());
+        let void_name = LLVMBuildGlobalStringPtr(
+            self.builder,
+            to_c_str("void").as_ptr(),
+            to_c_str("type_name").as_ptr(),
+        );
+        LLVMBuildCondBr(self.builder, is_null, fail, check);
+
+        LLVMPositionBuilderAtEnd(self.builder, check);
+        let field = |i: u32| {
+            LLVMBuildLoad(
+                self.builder,
+                LLVMBuildStructGEP(self.builder, boxed, i, to_c_str("any_gep").as_ptr()),
+                to_c_str("any_field").as_ptr(),
+            )
+        };
+        let found_tag = field(0);
+        let found_name = field(1);
+        let matches = LLVMBuildICmp(
+            self.builder,
+            LLVMIntEQ,
+            found_tag,
+            LLVMConstInt(LLVMInt32TypeInContext(self.context), tag as u64, 0),
+            to_c_str("tag_cmp").as_ptr(),
+        );
+        LLVMBuildCondBr(self.builder, matches, ok, fail);
+
+        LLVMPositionBuilderAtEnd(self.builder, fail);
+        let name = LLVMBuildPhi(
+            self.builder,
+            LLVMTypeOf(void_name),
+            to_c_str("found").as_ptr(),
+        );
+        LLVMAddIncoming(
+            name,
+            [void_name, found_name].as_mut_ptr(),
+            [entry, check].as_mut_ptr(),
+            2,
+        );
+        self.panic(
+            format!("Type Error: expected `{}`, found `%s`", type_name).as_str(),
+            vec![name],
+        );
+
+        LLVMPositionBuilderAtEnd(self.builder, ok);
+        let data = LLVMBuildPointerCast(
+            self.builder,
+            field(2),
+            LLVMPointerType(ty, 0),
+            to_c_str("data").as_ptr(),
+        );
+        LLVMBuildLoad(self.builder, data, to_c_str("unboxed").as_ptr())
+    }
+
+    unsafe fn any_type(&self) -> LLVMTypeRef {
+        Type::Any.llvm(self.context, Default::default())
+    }
+}
@@ -0,0 +1,84 @@
+/*
+* Copyright 2020 to 2021 BlazifyOrg
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+*    http://www.apache.org/licenses/LICENSE-2.0
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use llvm_sys::core::{
+    LLVMBuildFPCast, LLVMBuildFPToSI, LLVMBuildFPToUI, LLVMBuildIntCast2, LLVMBuildPointerCast,
+    LLVMBuildSIToFP, LLVMBuildUIToFP, LLVMCountParamTypes, LLVMDoubleTypeInContext,
+    LLVMGetElementType, LLVMGetIntTypeWidth, LLVMGetTypeKind, LLVMInt32TypeInContext,
+    LLVMIsFunctionVarArg, LLVMTypeOf,
+};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use llvm_sys::LLVMTypeKind::{LLVMFloatTypeKind, LLVMIntegerTypeKind, LLVMPointerTypeKind};
+
+use bzxc_shared::to_c_str;
+
+use crate::Compiler;
+
+impl Compiler {
+    /*
+     * Converts between integer and floating point types,
+     * the signedness picks between sign and zero extension.
+     * Objects of a class are converted to the ones of its base class by their pointer
+     */
+    pub(super) unsafe fn cast(
+        &self,
+        val: LLVMValueRef,
+        ty: LLVMTypeRef,
+        from_signed: bool,
+        to_signed: bool,
+    ) -> LLVMValueRef {
+        let from = LLVMTypeOf(val);
+        let name = to_c_str("cast");
+        let is_int = |ty: LLVMTypeRef| LLVMGetTypeKind(ty) == LLVMIntegerTypeKind;
+
+        if LLVMGetTypeKind(from) == LLVMPointerTypeKind {
+            return LLVMBuildPointerCast(self.builder, val, ty, name.as_ptr());
+        }
+
+        match (is_int(from), is_int(ty)) {
+            (true, true) => {
+                LLVMBuildIntCast2(self.builder, val, ty, from_signed as i32, name.as_ptr())
+            }
+            (true, false) if from_signed => LLVMBuildSIToFP(self.builder, val, ty, name.as_ptr()),
+            (true, false) => LLVMBuildUIToFP(self.builder, val, ty, name.as_ptr()),
+            (false, true) if to_signed => LLVMBuildFPToSI(self.builder, val, ty, name.as_ptr()),
+            (false, true) => LLVMBuildFPToUI(self.builder, val, ty, name.as_ptr()),
+            (false, false) => LLVMBuildFPCast(self.builder, val, ty, name.as_ptr()),
+        }
+    }
+
+    /*
+     * Arguments passed to the `...` of a variadic function get the C default
+     * promotions, floats become doubles and integers narrower than an int become ints
+     */
+    pub(super) unsafe fn promote_var_args(&self, fun: LLVMValueRef, args: &mut Vec<LLVMValueRef>) {
+        let fun_ty = LLVMGetElementType(LLVMTypeOf(fun));
+        if LLVMIsFunctionVarArg(fun_ty) == 0 {
+            return;
+        }
+
+        for arg in args.iter_mut().skip(LLVMCountParamTypes(fun_ty) as usize) {
+            let ty = LLVMTypeOf(*arg);
+            match LLVMGetTypeKind(ty) {
+                LLVMFloatTypeKind => {
+                    *arg = self.cast(*arg, LLVMDoubleTypeInContext(self.context), true, true);
+                }
+                // bools and chars are unsigned, wider integers are passed as they are
+                LLVMIntegerTypeKind if LLVMGetIntTypeWidth(ty) < 32 => {
+                    *arg = self.cast(*arg, LLVMInt32TypeInContext(self.context), false, true);
+                }
+                _ => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+/*
+* Copyright 2020 to 2021 BlazifyOrg
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+*    http://www.apache.org/licenses/LICENSE-2.0
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use llvm_sys::core::{
+    LLVMAppendBasicBlockInContext, LLVMBuildAnd, LLVMBuildCall, LLVMBuildCondBr,
+    LLVMBuildExtractValue, LLVMBuildGlobalStringPtr, LLVMBuildICmp, LLVMConstAllOnes, LLVMConstInt,
+    LLVMConstNull, LLVMConstShl, LLVMGetIntTypeWidth, LLVMInt1TypeInContext,
+    LLVMPositionBuilderAtEnd, LLVMStructTypeInContext, LLVMTypeOf,
+};
+use llvm_sys::prelude::LLVMValueRef;
+use llvm_sys::LLVMIntPredicate::LLVMIntEQ;
+
+use bzxc_shared::{to_c_str, Token, Tokens};
+
+use crate::Compiler;
+
+impl Compiler {
+    /*
+     * `+`, `-` or `*` of two integers through the overflow intrinsics,
+     * panics when the result doesn't fit in their type
+     */
+    pub(super) unsafe fn checked(
+        &self,
+        op: &Token,
+        signed: bool,
+        lhs: LLVMValueRef,
+        rhs: LLVMValueRef,
+    ) -> LLVMValueRef {
+        let ty = LLVMTypeOf(lhs);
+        let (name, label) = match op.value {
+            Tokens::Plus => ("add", "tmpadd"),
+            Tokens::Minus => ("sub", "tmpsub"),
+            Tokens::Multiply => ("mul", "tmpmul"),
+            _ => unreachable!(),
+        };
+        let intrinsic = format!(
+            "llvm.{}{}.with.overflow.i{}",
+            if signed { "s" } else { "u" },
+            name,
+            LLVMGetIntTypeWidth(ty)
+        );
+        // intrinsics are declared like the functions of the C library
+        let func = self.libc(
+            intrinsic.as_str(),
+            LLVMStructTypeInContext(
+                self.context,
+                [ty, LLVMInt1TypeInContext(self.context)].as_mut_ptr(),
+                2,
+                0,
+            ),
+            vec![ty, ty],
+            false,
+        );
+
+        let result = LLVMBuildCall(
+            self.builder,
+            func,
+            [lhs, rhs].as_mut_ptr(),
+            2,
+            to_c_str(label).as_ptr(),
+        );
+        let overflow =
+            LLVMBuildExtractValue(self.builder, result, 1, to_c_str("overflow").as_ptr());
+        self.trap(overflow, "Overflow Error: `%s` overflowed at %s", op);
+        LLVMBuildExtractValue(self.builder, result, 0, to_c_str(label).as_ptr())
+    }
+
+    /*
+     * Panics before `/` or `%` divides by zero, or divides the smallest signed integer by -1
+     */
+    pub(super) unsafe fn check_divisor(
+        &self,
+        op: &Token,
+        signed: bool,
+        lhs: LLVMValueRef,
+        rhs: LLVMValueRef,
+    ) {
+        let ty = LLVMTypeOf(rhs);
+        let zero = LLVMBuildICmp(
+            self.builder,
+            LLVMIntEQ,
+            rhs,
+            LLVMConstNull(ty),
+            to_c_str("zero").as_ptr(),
+        );
+        self.trap(zero, "Division Error: `%s` divided by zero at %s", op);
+        if !signed {
+            return;
+        }
+
+        let min = LLVMConstShl(
+            LLVMConstInt(ty, 1, 0),
+            LLVMConstInt(ty, (LLVMGetIntTypeWidth(ty) - 1) as u64, 0),
+        );
+        let overflow = LLVMBuildAnd(
+            self.builder,
+            LLVMBuildICmp(self.builder, LLVMIntEQ, lhs, min, to_c_str("min").as_ptr()),
+            LLVMBuildICmp(
+                self.builder,
+                LLVMIntEQ,
+                rhs,
+                LLVMConstAllOnes(ty),
+                to_c_str("minus_one").as_ptr(),
+            ),
+            to_c_str("overflow").as_ptr(),
+        );
+        self.trap(overflow, "Overflow Error: `%s` overflowed at %s", op);
+    }
+
+    /*
+     * Panics with `message` when `failed` holds, it's given the operator and where it's written
+     */
+    unsafe fn trap(&self, failed: LLVMValueRef, message: &str, op: &Token) {
+        let ok = LLVMAppendBasicBlockInContext(
+            self.context,
+            self.fn_value(),
+            to_c_str("checked").as_ptr(),
+        );
+        let fail = LLVMAppendBasicBlockInContext(
+            self.context,
+            self.fn_value(),
+            to_c_str("check_failed").as_ptr(),
+        );
+        LLVMBuildCondBr(self.builder, failed, fail, ok);
+
+        LLVMPositionBuilderAtEnd(self.builder, fail);
+        let string = |string: &str| {
+            LLVMBuildGlobalStringPtr(
+                self.builder,
+                to_c_str(string).as_ptr(),
+                to_c_str("check_msg").as_ptr(),
+            )
+        };
+        self.panic(
+            message,
+            vec![
+                string(operator(&op.value)),
+                string(op.pos_start.location().as_str()),
+            ],
+        );
+
+        LLVMPositionBuilderAtEnd(self.builder, ok);
+    }
+}
+
+fn operator(op: &Tokens) -> &'static str {
+    match op {
+        Tokens::Plus => "+",
+        Tokens::Minus => "-",
+        Tokens::Multiply => "*",
+        Tokens::Divide => "/",
+        Tokens::Modulo => "%",
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,187 @@
+/*
+* Copyright 2020 to 2021 BlazifyOrg
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+*    http://www.apache.org/licenses/LICENSE-2.0
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use llvm_sys::core::{
+    LLVMAddCase, LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildBr,
+    LLVMBuildExtractValue, LLVMBuildInsertValue, LLVMBuildLoad, LLVMBuildPhi, LLVMBuildPointerCast,
+    LLVMBuildStore, LLVMBuildStructGEP, LLVMBuildSwitch, LLVMBuildUnreachable, LLVMConstInt,
+    LLVMConstNull, LLVMGetInsertBlock, LLVMGetUndef, LLVMInt32TypeInContext, LLVMInt8TypeInContext,
+    LLVMPointerType, LLVMPositionBuilderAtEnd, LLVMStructTypeInContext, LLVMTypeOf,
+};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+
+use bzxc_shared::{to_c_str, BindingId, LLVMNode};
+
+use crate::Compiler;
+
+impl Compiler {
+    /*
+     * Value of an enum, the payload is heap allocated as a struct of its values
+     */
+    pub(super) unsafe fn variant(
+        &mut self,
+        ty: LLVMTypeRef,
+        tag: u32,
+        payload: Vec<LLVMNode>,
+    ) -> LLVMValueRef {
+        let i8_ptr = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
+        let vals = payload
+            .into_iter()
+            .map(|val| self.compile(val))
+            .collect::<Vec<_>>();
+
+        let payload = if vals.is_empty() {
+            LLVMConstNull(i8_ptr)
+        } else {
+            let mut types = vals.iter().map(|val| LLVMTypeOf(*val)).collect::<Vec<_>>();
+            let payload_ty =
+                LLVMStructTypeInContext(self.context, types.as_mut_ptr(), types.len() as u32, 0);
+            let ptr = self.malloc(payload_ty);
+            for (i, val) in vals.into_iter().enumerate() {
+                let field =
+                    LLVMBuildStructGEP(self.builder, ptr, i as u32, to_c_str("field").as_ptr());
+                LLVMBuildStore(self.builder, val, field);
+            }
+            LLVMBuildPointerCast(self.builder, ptr, i8_ptr, to_c_str("payload").as_ptr())
+        };
+
+        let tagged = LLVMBuildInsertValue(
+            self.builder,
+            LLVMGetUndef(ty),
+            LLVMConstInt(LLVMInt32TypeInContext(self.context), tag as u64, 0),
+            0,
+            to_c_str("variant").as_ptr(),
+        );
+        LLVMBuildInsertValue(
+            self.builder,
+            tagged,
+            payload,
+            1,
+            to_c_str("variant").as_ptr(),
+        )
+    }
+
+    /*
+     * Switches on the tag of `val`, the arm taken binds the payload and is evaluated
+     */
+    pub(super) unsafe fn match_variant(
+        &mut self,
+        ty: LLVMTypeRef,
+        val: LLVMValueRef,
+        arms: Vec<(Option<u32>, Vec<(BindingId, String, LLVMTypeRef)>, LLVMNode)>,
+    ) -> LLVMValueRef {
+        let parent = self.fn_value();
+        let tag = LLVMBuildExtractValue(self.builder, val, 0, to_c_str("tag").as_ptr());
+        let payload = LLVMBuildExtractValue(self.builder, val, 1, to_c_str("payload").as_ptr());
+
+        let blocks = arms
+            .iter()
+            .map(|_| LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("arm").as_ptr()))
+            .collect::<Vec<_>>();
+        // without a `_` arm every variant is matched, so no other tag can reach the switch
+        let default_block = match arms.iter().position(|(tag, _, _)| tag.is_none()) {
+            Some(arm) => blocks[arm],
+            None => {
+                LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("no_arm").as_ptr())
+            }
+        };
+        let after_block =
+            LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("after").as_ptr());
+
+        let switch = LLVMBuildSwitch(self.builder, tag, default_block, arms.len() as u32);
+        for ((tag, _, _), block) in arms.iter().zip(&blocks) {
+            if let Some(tag) = tag {
+                LLVMAddCase(
+                    switch,
+                    LLVMConstInt(LLVMInt32TypeInContext(self.context), *tag as u64, 0),
+                    *block,
+                );
+            }
+        }
+        if !blocks.contains(&default_block) {
+            LLVMPositionBuilderAtEnd(self.builder, default_block);
+            LLVMBuildUnreachable(self.builder);
+        }
+
+        let mut incoming_vals = vec![];
+        let mut incoming_blocks = vec![];
+        for ((_, bindings, body), block) in arms.into_iter().zip(blocks) {
+            LLVMPositionBuilderAtEnd(self.builder, block);
+            self.bind_payload(payload, &bindings);
+
+            self.ret = false;
+            let val = self.compile(body);
+            if !self.ret {
+                incoming_vals.push(val);
+                incoming_blocks.push(LLVMGetInsertBlock(self.builder));
+                LLVMBuildBr(self.builder, after_block);
+            }
+        }
+
+        LLVMPositionBuilderAtEnd(self.builder, after_block);
+        // every arm returned, so nothing ever reaches the block after the match
+        self.ret = incoming_vals.is_empty();
+        if self.ret {
+            LLVMBuildUnreachable(self.builder);
+            return LLVMGetUndef(self.null_type());
+        }
+
+        if ty == self.null_type() {
+            return self.null();
+        }
+
+        let phi = LLVMBuildPhi(self.builder, ty, to_c_str("match_val").as_ptr());
+        LLVMAddIncoming(
+            phi,
+            incoming_vals.as_mut_ptr(),
+            incoming_blocks.as_mut_ptr(),
+            incoming_vals.len() as u32,
+        );
+        phi
+    }
+
+    /*
+     * Stores the values of the payload in the variables of `bindings`
+     */
+    unsafe fn bind_payload(
+        &mut self,
+        payload: LLVMValueRef,
+        bindings: &[(BindingId, String, LLVMTypeRef)],
+    ) {
+        if bindings.is_empty() {
+            return;
+        }
+
+        let mut types = bindings.iter().map(|(_, _, ty)| *ty).collect::<Vec<_>>();
+        let payload_ty =
+            LLVMStructTypeInContext(self.context, types.as_mut_ptr(), types.len() as u32, 0);
+        let ptr = LLVMBuildPointerCast(
+            self.builder,
+            payload,
+            LLVMPointerType(payload_ty, 0),
+            to_c_str("payload").as_ptr(),
+        );
+
+        for (i, (binding, name, ty)) in bindings.iter().enumerate() {
+            if name == "_" {
+                continue;
+            }
+
+            let field = LLVMBuildStructGEP(self.builder, ptr, i as u32, to_c_str("field").as_ptr());
+            let val = LLVMBuildLoad(self.builder, field, to_c_str(name.as_str()).as_ptr());
+            let alloca = self.create_entry_block_alloca(name.as_str(), *ty);
+            LLVMBuildStore(self.builder, val, alloca);
+            self.variables.insert(*binding, alloca);
+        }
+    }
+}
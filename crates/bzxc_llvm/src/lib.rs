@@ -14,38 +14,50 @@
 use llvm_sys::analysis::LLVMVerifierFailureAction::LLVMPrintMessageAction;
 use llvm_sys::analysis::LLVMVerifyFunction;
 use llvm_sys::core::{
-    LLVMAddFunction, LLVMAppendBasicBlockInContext, LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildAnd,
-    LLVMBuildBr, LLVMBuildCall, LLVMBuildCondBr, LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv,
-    LLVMBuildFMul, LLVMBuildFRem, LLVMBuildFSub, LLVMBuildGEP, LLVMBuildGlobalString,
-    LLVMBuildICmp, LLVMBuildInsertValue, LLVMBuildIntCast, LLVMBuildLoad, LLVMBuildMul,
-    LLVMBuildOr, LLVMBuildPointerCast, LLVMBuildRet, LLVMBuildStore, LLVMBuildStructGEP,
-    LLVMBuildSub, LLVMBuildUDiv, LLVMBuildURem, LLVMConstInt, LLVMConstNeg, LLVMConstNot,
-    LLVMConstNull, LLVMConstReal, LLVMCountStructElementTypes, LLVMCreateBuilderInContext,
-    LLVMDeleteFunction, LLVMDumpModule, LLVMDumpValue, LLVMFunctionType, LLVMGetArrayLength,
+    LLVMAddFunction, LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildAdd, LLVMBuildAlloca,
+    LLVMBuildAnd, LLVMBuildBr, LLVMBuildCall, LLVMBuildCondBr, LLVMBuildExtractValue,
+    LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv, LLVMBuildFMul, LLVMBuildFNeg, LLVMBuildFRem,
+    LLVMBuildFSub, LLVMBuildGEP, LLVMBuildGlobalString, LLVMBuildICmp, LLVMBuildInsertValue,
+    LLVMBuildIntCast, LLVMBuildLoad, LLVMBuildMul, LLVMBuildNeg, LLVMBuildNot, LLVMBuildOr,
+    LLVMBuildPhi, LLVMBuildPointerCast, LLVMBuildRet, LLVMBuildSDiv, LLVMBuildSRem, LLVMBuildStore,
+    LLVMBuildStructGEP, LLVMBuildSub, LLVMBuildUDiv, LLVMBuildURem, LLVMBuildUnreachable,
+    LLVMConstInt, LLVMConstIntOfArbitraryPrecision, LLVMConstNull, LLVMConstReal,
+    LLVMCountBasicBlocks, LLVMCountStructElementTypes, LLVMCreateBuilderInContext,
+    LLVMDeleteFunction, LLVMDumpModule, LLVMDumpValue, LLVMFunctionType, LLVMGetBasicBlockParent,
     LLVMGetElementType, LLVMGetFirstBasicBlock, LLVMGetFirstInstruction, LLVMGetInsertBlock,
-    LLVMGetNamedFunction, LLVMGetParam, LLVMGetStructElementTypes, LLVMGetUndef,
-    LLVMInsertBasicBlockInContext, LLVMInt1TypeInContext, LLVMInt32TypeInContext,
-    LLVMIsAConstantInt, LLVMPositionBuilderAtEnd, LLVMPositionBuilderBefore,
-    LLVMRunFunctionPassManager, LLVMSetLinkage, LLVMSetValueName2, LLVMStructGetTypeAtIndex,
+    LLVMGetInstructionParent, LLVMGetNamedFunction, LLVMGetParam, LLVMGetStructElementTypes,
+    LLVMGetTypeKind, LLVMGetUndef, LLVMInsertBasicBlockInContext, LLVMInt1TypeInContext,
+    LLVMInt32TypeInContext, LLVMIsAAllocaInst, LLVMPointerType, LLVMPositionBuilderAtEnd,
+    LLVMPositionBuilderBefore, LLVMRunFunctionPassManager, LLVMSetLinkage, LLVMSetValueName2,
     LLVMStructTypeInContext, LLVMTypeOf,
 };
 use llvm_sys::prelude::{
     LLVMBuilderRef, LLVMContextRef, LLVMModuleRef, LLVMPassManagerRef, LLVMTypeRef, LLVMValueRef,
 };
 use llvm_sys::LLVMIntPredicate::{
-    LLVMIntEQ, LLVMIntNE, LLVMIntUGE, LLVMIntUGT, LLVMIntULE, LLVMIntULT,
+    LLVMIntEQ, LLVMIntNE, LLVMIntSGE, LLVMIntSGT, LLVMIntSLE, LLVMIntSLT, LLVMIntUGE, LLVMIntUGT,
+    LLVMIntULE, LLVMIntULT,
 };
 use llvm_sys::LLVMLinkage::LLVMExternalLinkage;
 use llvm_sys::LLVMRealPredicate::{
     LLVMRealOEQ, LLVMRealONE, LLVMRealUGE, LLVMRealUGT, LLVMRealULE, LLVMRealULT,
 };
+use llvm_sys::LLVMTypeKind::LLVMIntegerTypeKind;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::mem::forget;
 
-use bzxc_shared::{to_c_str, LLVMNode, Tokens};
+use bzxc_shared::{to_c_str, BindingId, LLVMNode, Tokens, Type};
 
+mod any;
+mod cast;
+mod checked;
+mod enums;
+mod nullable;
 mod oop;
+mod runtime;
+
+use oop::{type_id, Class};
 
 #[derive(Debug, Clone)]
 pub struct Compiler {
@@ -54,11 +66,13 @@ pub struct Compiler {
     pub module: LLVMModuleRef,
     pub fpm: LLVMPassManagerRef,
     pub main: LLVMNode,
+    pub checked_arith: bool,
 
     fn_value_opt: Option<LLVMValueRef>,
-    variables: HashMap<String, LLVMValueRef>,
+    variables: HashMap<BindingId, LLVMValueRef>,
+    functions: HashMap<BindingId, LLVMValueRef>,
     objects: HashMap<(String, u32), usize>,
-    classes: HashMap<u32, (LLVMValueRef, LLVMValueRef, HashMap<String, LLVMValueRef>)>,
+    classes: HashMap<u32, Class>,
     ret: bool,
 }
 
@@ -76,8 +90,10 @@ impl Compiler {
             fpm,
             module,
             main,
+            checked_arith: false,
             fn_value_opt: None,
             variables: HashMap::new(),
+            functions: HashMap::new(),
             objects: HashMap::new(),
             classes: HashMap::new(),
             ret: false,
@@ -103,6 +119,15 @@ impl Compiler {
         LLVMBuildAlloca(builder, ty, to_c_str(name).as_ptr())
     }
 
+    /*
+     * Whether `alloca` is a variable of the function being compiled holding values of type `ty`
+     */
+    unsafe fn reassignable(&self, alloca: LLVMValueRef, ty: LLVMTypeRef) -> bool {
+        !LLVMIsAAllocaInst(alloca).is_null()
+            && LLVMGetBasicBlockParent(LLVMGetInstructionParent(alloca)) == self.fn_value()
+            && LLVMGetElementType(LLVMTypeOf(alloca)) == ty
+    }
+
     unsafe fn null(&self) -> LLVMValueRef {
         let null = LLVMGetUndef(LLVMStructTypeInContext(self.context, [].as_mut_ptr(), 0, 0));
         let ptr = self.create_entry_block_alloca("null", LLVMTypeOf(null));
@@ -111,6 +136,13 @@ impl Compiler {
         ptr
     }
 
+    unsafe fn null_type(&self) -> LLVMTypeRef {
+        LLVMPointerType(
+            LLVMStructTypeInContext(self.context, [].as_mut_ptr(), 0, 0),
+            0,
+        )
+    }
+
     pub unsafe fn compile_main(&mut self) {
         let func = LLVMAddFunction(
             self.module,
@@ -122,6 +154,7 @@ impl Compiler {
         LLVMPositionBuilderAtEnd(self.builder, entry);
 
         self.fn_value_opt = Some(func);
+        self.declare_functions(&self.main.clone());
         self.compile(self.main.clone());
 
         LLVMBuildRet(
@@ -143,6 +176,32 @@ impl Compiler {
         }
     }
 
+    /*
+     * Declares the top-level functions before any body is compiled,
+     * so they can be called above their definitions and from each other
+     */
+    unsafe fn declare_functions(&mut self, node: &LLVMNode) {
+        match node {
+            // generic instances are grouped in statements of their own
+            LLVMNode::Statements(stmts) => {
+                for statement in stmts {
+                    self.declare_functions(statement);
+                }
+            }
+            LLVMNode::Fun {
+                ty, name, binding, ..
+            } => {
+                let func = LLVMAddFunction(
+                    self.module,
+                    to_c_str(name.as_str()).as_ptr(),
+                    LLVMGetElementType(*ty),
+                );
+                self.functions.insert(*binding, func);
+            }
+            _ => (),
+        }
+    }
+
     unsafe fn compile(&mut self, node: LLVMNode) -> LLVMValueRef {
         match node {
             LLVMNode::Statements(stmts) => {
@@ -156,7 +215,11 @@ impl Compiler {
 
                 last.unwrap_or_else(|| self.null())
             }
-            LLVMNode::Int { ty, val } => LLVMConstInt(ty, val.try_into().unwrap(), 0),
+            // folded constants may be negative or wider than 64 bits, words are the low one first
+            LLVMNode::Int { ty, val } => {
+                let words = [val as u64, (val >> 64) as u64];
+                LLVMConstIntOfArbitraryPrecision(ty, 2, words.as_ptr())
+            }
             LLVMNode::Float { ty, val } => LLVMConstReal(ty, val.try_into().unwrap()),
             LLVMNode::Boolean { ty, val } => LLVMConstInt(ty, val.try_into().unwrap(), 0),
             LLVMNode::Char { ty, val } => LLVMConstInt(ty, val.try_into().unwrap(), 0),
@@ -176,19 +239,17 @@ impl Compiler {
                 op_token,
             } => {
                 let val = self.compile(*val);
-                if !LLVMIsAConstantInt(val).is_null() {
-                    match op_token.value {
-                        Tokens::Plus => val,
-                        Tokens::Minus => LLVMConstNeg(val),
-                        Tokens::Keyword("not") => LLVMConstNot(val),
-                        _ => unreachable!(),
+                let is_int = LLVMGetTypeKind(LLVMTypeOf(val)) == LLVMIntegerTypeKind;
+                match op_token.value {
+                    Tokens::Plus => val,
+                    Tokens::Minus if is_int => {
+                        LLVMBuildNeg(self.builder, val, to_c_str("tmpneg").as_ptr())
                     }
-                } else {
-                    match op_token.value {
-                        Tokens::Plus => val,
-                        Tokens::Minus => LLVMConstNeg(val),
-                        _ => unreachable!(),
+                    Tokens::Minus => LLVMBuildFNeg(self.builder, val, to_c_str("tmpneg").as_ptr()),
+                    Tokens::Keyword("not") => {
+                        LLVMBuildNot(self.builder, val, to_c_str("tmpnot").as_ptr())
                     }
+                    _ => unreachable!(),
                 }
             }
             LLVMNode::Binary {
@@ -196,11 +257,19 @@ impl Compiler {
                 left,
                 right,
                 op_token,
+                signed,
             } => {
                 let lhs = self.compile(*left);
                 let rhs = self.compile(*right);
-                if !LLVMIsAConstantInt(lhs).is_null() {
+                if LLVMGetTypeKind(LLVMTypeOf(lhs)) == LLVMIntegerTypeKind {
+                    let checked = self.checked_arith;
+                    if checked && [Tokens::Divide, Tokens::Modulo].contains(&op_token.value) {
+                        self.check_divisor(&op_token, signed, lhs, rhs);
+                    }
                     match op_token.value {
+                        Tokens::Plus | Tokens::Minus | Tokens::Multiply if checked => {
+                            self.checked(&op_token, signed, lhs, rhs)
+                        }
                         Tokens::Plus => {
                             LLVMBuildAdd(self.builder, lhs, rhs, to_c_str("tmpadd").as_ptr())
                         }
@@ -210,9 +279,15 @@ impl Compiler {
                         Tokens::Multiply => {
                             LLVMBuildMul(self.builder, lhs, rhs, to_c_str("tmpmul").as_ptr())
                         }
+                        Tokens::Divide if signed => {
+                            LLVMBuildSDiv(self.builder, lhs, rhs, to_c_str("tmpdiv").as_ptr())
+                        }
                         Tokens::Divide => {
                             LLVMBuildUDiv(self.builder, lhs, rhs, to_c_str("tmpdiv").as_ptr())
                         }
+                        Tokens::Modulo if signed => {
+                            LLVMBuildSRem(self.builder, lhs, rhs, to_c_str("tmpmod").as_ptr())
+                        }
                         Tokens::Modulo => {
                             LLVMBuildURem(self.builder, lhs, rhs, to_c_str("tmpmod").as_ptr())
                         }
@@ -220,7 +295,7 @@ impl Compiler {
                             self.builder,
                             LLVMBuildICmp(
                                 self.builder,
-                                LLVMIntULT,
+                                if signed { LLVMIntSLT } else { LLVMIntULT },
                                 lhs,
                                 rhs,
                                 to_c_str("tmpcmp").as_ptr(),
@@ -232,7 +307,7 @@ impl Compiler {
                             self.builder,
                             LLVMBuildICmp(
                                 self.builder,
-                                LLVMIntUGT,
+                                if signed { LLVMIntSGT } else { LLVMIntUGT },
                                 lhs,
                                 rhs,
                                 to_c_str("tmpcmp").as_ptr(),
@@ -244,7 +319,7 @@ impl Compiler {
                             self.builder,
                             LLVMBuildICmp(
                                 self.builder,
-                                LLVMIntULE,
+                                if signed { LLVMIntSLE } else { LLVMIntULE },
                                 lhs,
                                 rhs,
                                 to_c_str("tmpcmp").as_ptr(),
@@ -256,7 +331,7 @@ impl Compiler {
                             self.builder,
                             LLVMBuildICmp(
                                 self.builder,
-                                LLVMIntUGE,
+                                if signed { LLVMIntSGE } else { LLVMIntUGE },
                                 lhs,
                                 rhs,
                                 to_c_str("tmpcmp").as_ptr(),
@@ -394,14 +469,24 @@ impl Compiler {
             LLVMNode::Fun {
                 ty,
                 name,
+                binding,
                 params,
                 body,
             } => {
-                let func = LLVMAddFunction(
-                    self.module,
-                    to_c_str(name.as_str()).as_ptr(),
-                    LLVMGetElementType(ty),
-                );
+                // top-level functions and methods are declared before their bodies are compiled
+                let declared = match self.functions.get(&binding) {
+                    Some(func) => *func,
+                    None => LLVMGetNamedFunction(self.module, to_c_str(name.as_str()).as_ptr()),
+                };
+                let func = if !declared.is_null() && LLVMCountBasicBlocks(declared) == 0 {
+                    declared
+                } else {
+                    LLVMAddFunction(
+                        self.module,
+                        to_c_str(name.as_str()).as_ptr(),
+                        LLVMGetElementType(ty),
+                    )
+                };
 
                 let parent = self.fn_value_opt.clone();
 
@@ -413,30 +498,32 @@ impl Compiler {
 
                 self.fn_value_opt = Some(func);
 
+                // the variables of the body are dropped with it, the passes run below may free them
+                let variables = self.variables.clone();
                 self.variables.reserve(params.len());
 
-                for i in 0..params.len() {
+                for (i, (param, arg_name, _)) in params.iter().enumerate() {
                     let arg = LLVMGetParam(func, i as u32);
-                    let arg_name = params.get(i).unwrap().0.as_str().clone();
                     LLVMSetValueName2(arg, to_c_str(arg_name).as_ptr(), arg_name.len());
                     let alloca = self.create_entry_block_alloca(arg_name, LLVMTypeOf(arg));
 
                     LLVMBuildStore(self.builder, arg, alloca);
-                    self.variables.insert(arg_name.to_string(), alloca);
+                    self.variables.insert(*param, alloca);
                 }
 
                 let ret = self.ret.clone();
                 self.ret = false;
-                self.compile(*body);
+                let val = self.compile(*body);
 
                 if !self.ret {
-                    LLVMBuildRet(self.builder, self.null());
+                    LLVMBuildRet(self.builder, val);
                 }
 
                 LLVMPositionBuilderAtEnd(self.builder, parental_block);
                 self.fn_value_opt = parent;
 
                 self.ret = ret;
+                self.variables = variables;
 
                 if LLVMVerifyFunction(func, LLVMPrintMessageAction) == 0 {
                     LLVMRunFunctionPassManager(self.fpm, func);
@@ -445,9 +532,7 @@ impl Compiler {
                     LLVMDeleteFunction(func);
                 }
 
-                let alloca = self.create_entry_block_alloca(name.as_str(), LLVMTypeOf(func));
-                LLVMBuildStore(self.builder, func, alloca);
-                self.variables.insert(name, alloca);
+                self.functions.insert(binding, func);
                 func
             }
             LLVMNode::Extern {
@@ -473,16 +558,34 @@ impl Compiler {
 
                 self.null()
             }
-            LLVMNode::Let { ty: _, name, val } => {
+            LLVMNode::Let {
+                ty: _,
+                name,
+                binding,
+                val,
+            } => {
                 let val = self.compile(*val);
-                let alloca = self.create_entry_block_alloca(name.as_str(), LLVMTypeOf(val));
+                // a reassignment stores into the variable it reassigns, loops read it from there
+                let alloca = match self.variables.get(&binding) {
+                    Some(&alloca) if self.reassignable(alloca, LLVMTypeOf(val)) => alloca,
+                    _ => self.create_entry_block_alloca(name.as_str(), LLVMTypeOf(val)),
+                };
                 LLVMBuildStore(self.builder, val, alloca);
-                self.variables.insert(name.to_string(), alloca);
+                self.variables.insert(binding, alloca);
                 self.null()
             }
-            LLVMNode::Var { ty: _, name } => match self.variables.get(name.as_str()) {
-                Some(val) => LLVMBuildLoad(self.builder, *val, to_c_str(name.as_str()).as_ptr()),
-                None => LLVMGetNamedFunction(self.module, to_c_str(name.as_str()).as_ptr()),
+            LLVMNode::Var {
+                ty: _,
+                name,
+                binding,
+            } => match binding {
+                Some(binding) if self.variables.contains_key(&binding) => LLVMBuildLoad(
+                    self.builder,
+                    self.variables[&binding],
+                    to_c_str(name.as_str()).as_ptr(),
+                ),
+                Some(binding) if self.functions.contains_key(&binding) => self.functions[&binding],
+                _ => LLVMGetNamedFunction(self.module, to_c_str(name.as_str()).as_ptr()),
             },
             LLVMNode::Call { ty: _, fun, args } => {
                 let mut args = args
@@ -490,9 +593,11 @@ impl Compiler {
                     .map(|arg| self.compile(arg.clone()))
                     .collect::<Vec<_>>();
 
+                let fun = self.compile(*fun);
+                self.promote_var_args(fun, &mut args);
                 LLVMBuildCall(
                     self.builder,
-                    self.compile(*fun),
+                    fun,
                     args.as_mut_ptr(),
                     args.len() as u32,
                     to_c_str("call_fun").as_ptr(),
@@ -504,9 +609,36 @@ impl Compiler {
                 self.ret = true;
                 rett
             }
-            LLVMNode::Null { ty } => LLVMGetUndef(ty),
+            LLVMNode::Null { ty } => LLVMConstNull(ty),
+            LLVMNode::BoxAny {
+                tag,
+                type_name,
+                val,
+                ..
+            } => {
+                let val = self.compile(*val);
+                self.box_any(val, tag, type_name)
+            }
+            LLVMNode::Cast {
+                ty,
+                val,
+                from_signed,
+                to_signed,
+            } => {
+                let val = self.compile(*val);
+                self.cast(val, ty, from_signed, to_signed)
+            }
+            LLVMNode::UnboxAny {
+                ty,
+                tag,
+                type_name,
+                val,
+            } => {
+                let boxed = self.compile(*val);
+                self.unbox_any(boxed, ty, tag, type_name)
+            }
             LLVMNode::If {
-                ty: _,
+                ty,
                 cases,
                 else_case,
             } => {
@@ -537,6 +669,8 @@ impl Compiler {
                     LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("after").as_ptr());
                 blocks.push(after_block);
 
+                let mut incoming_vals = vec![];
+                let mut incoming_blocks = vec![];
                 for (i, (cond, body)) in cases.iter().enumerate() {
                     let then_block = blocks[i];
                     let else_block = blocks[i + 1];
@@ -553,22 +687,49 @@ impl Compiler {
                     LLVMBuildCondBr(self.builder, condition, conditional_block, else_block);
 
                     LLVMPositionBuilderAtEnd(self.builder, conditional_block);
-                    self.compile(body.clone());
+                    self.ret = false;
+                    let val = self.compile(body.clone());
                     if !self.ret {
+                        incoming_vals.push(val);
+                        incoming_blocks.push(LLVMGetInsertBlock(self.builder));
                         LLVMBuildBr(self.builder, after_block);
                     };
                 }
 
+                // every branch returned, so nothing ever reaches the block after the if
+                let mut returned = else_block.is_some() && incoming_vals.is_empty();
                 if let Some(else_block) = else_block {
                     LLVMPositionBuilderAtEnd(self.builder, else_block);
-                    self.compile(*else_case.unwrap());
-                    LLVMBuildBr(self.builder, after_block);
+                    self.ret = false;
+                    let val = self.compile(*else_case.unwrap());
+                    if !self.ret {
+                        incoming_vals.push(val);
+                        incoming_blocks.push(LLVMGetInsertBlock(self.builder));
+                        LLVMBuildBr(self.builder, after_block);
+                    }
+                    returned &= self.ret;
                 }
 
                 LLVMPositionBuilderAtEnd(self.builder, after_block);
-                self.ret = false;
+                self.ret = returned;
 
-                self.null()
+                if returned {
+                    LLVMBuildUnreachable(self.builder);
+                    return LLVMGetUndef(self.null_type());
+                }
+
+                if else_block.is_none() || ty == self.null_type() {
+                    return self.null();
+                }
+
+                let phi = LLVMBuildPhi(self.builder, ty, to_c_str("if_val").as_ptr());
+                LLVMAddIncoming(
+                    phi,
+                    incoming_vals.as_mut_ptr(),
+                    incoming_blocks.as_mut_ptr(),
+                    incoming_vals.len() as u32,
+                );
+                phi
             }
             LLVMNode::While { ty: _, cond, body } => {
                 let parent = self.fn_value();
@@ -604,6 +765,7 @@ impl Compiler {
             LLVMNode::For {
                 ty: _,
                 var: var_,
+                binding,
                 start,
                 end,
                 step,
@@ -621,7 +783,7 @@ impl Compiler {
 
                 let var = self.create_entry_block_alloca(var_.as_str(), LLVMTypeOf(start));
                 LLVMBuildStore(self.builder, start, var);
-                self.variables.insert(var_, var);
+                self.variables.insert(binding, var);
 
                 let cond_block = LLVMAppendBasicBlockInContext(
                     self.context,
@@ -667,6 +829,10 @@ impl Compiler {
             }
             LLVMNode::Array { ty, elements } => {
                 let arr = self.create_entry_block_alloca("array_alloca", LLVMGetElementType(ty));
+                // `[]` given a length starts zeroed
+                if elements.is_empty() {
+                    LLVMBuildStore(self.builder, LLVMConstNull(LLVMGetElementType(ty)), arr);
+                }
 
                 for (i, element) in elements.iter().enumerate() {
                     let element = self.compile(element.clone());
@@ -705,9 +871,10 @@ impl Compiler {
 
                 LLVMBuildLoad(self.builder, element_ptr, to_c_str("load").as_ptr())
             }
-            LLVMNode::Object { ty, properties } => self.create_obj(ty, properties),
+            LLVMNode::Object { ty, properties } => self.create_obj(ty, properties, None),
             LLVMNode::CObject { ty: _ty, object } => {
                 let obj = self.compile(*object);
+                let header = self.header(LLVMTypeOf(obj));
                 let object = LLVMBuildLoad(self.builder, obj, to_c_str("load").as_ptr());
                 let count = LLVMCountStructElementTypes(LLVMTypeOf(object));
                 let mut raw_vec: Vec<LLVMTypeRef> = Vec::with_capacity(count as usize);
@@ -718,7 +885,7 @@ impl Compiler {
                     LLVMGetStructElementTypes(LLVMTypeOf(object), ptr);
                     Vec::from_raw_parts(ptr, count as usize, count as usize)
                 };
-                c_obj_fields.remove(0);
+                c_obj_fields.drain(..header as usize);
                 let struct_val = LLVMGetUndef(LLVMStructTypeInContext(
                     self.context,
                     c_obj_fields.as_mut_ptr(),
@@ -729,7 +896,7 @@ impl Compiler {
                 let alloc = self.create_entry_block_alloca("alloc", LLVMTypeOf(struct_val));
                 LLVMBuildStore(self.builder, struct_val, alloc);
 
-                for i in 1..count {
+                for i in header..count {
                     let val = LLVMBuildLoad(
                         self.builder,
                         LLVMBuildStructGEP(self.builder, obj, i, to_c_str("struct_gep").as_ptr()),
@@ -742,7 +909,7 @@ impl Compiler {
                         LLVMBuildStructGEP(
                             self.builder,
                             alloc,
-                            i - 1,
+                            i - header,
                             to_c_str("struct_gep").as_ptr(),
                         ),
                     );
@@ -751,14 +918,8 @@ impl Compiler {
                 alloc
             }
             LLVMNode::CToBzxObject { ty, object } => {
-                let ty = LLVMGetElementType(ty);
-                let struct_val = LLVMBuildInsertValue(
-                    self.builder,
-                    LLVMGetUndef(ty),
-                    LLVMConstNull(LLVMStructGetTypeAtIndex(ty, 0)),
-                    0,
-                    to_c_str("c_to_bzx_obj_load").as_ptr(),
-                );
+                let header = self.header(ty);
+                let struct_val = LLVMConstNull(LLVMGetElementType(ty));
 
                 let obj = self.compile(*object);
                 let object = LLVMBuildLoad(self.builder, obj, to_c_str("load").as_ptr());
@@ -779,7 +940,7 @@ impl Compiler {
                         LLVMBuildStructGEP(
                             self.builder,
                             alloc,
-                            i + 1,
+                            i + header,
                             to_c_str("struct_gep").as_ptr(),
                         ),
                     );
@@ -809,7 +970,7 @@ impl Compiler {
                 let ptr = self.obj_property(struct_ty, property);
                 LLVMBuildStore(self.builder, val, ptr);
 
-                struct_ty
+                val
             }
             LLVMNode::ObjectMethodCall {
                 ty: _,
@@ -817,53 +978,49 @@ impl Compiler {
                 object,
                 args,
             } => {
-                let ptr = self.compile(*object.clone());
-
-                let class = self
-                    .classes
-                    .get(
-                        &(LLVMGetArrayLength(LLVMGetElementType(LLVMStructGetTypeAtIndex(
-                            LLVMGetElementType(LLVMTypeOf(ptr)),
-                            0,
-                        ))) as usize as u32),
-                    )
-                    .clone();
-                let is_class = class.is_some();
+                let is_super = matches!(*object, LLVMNode::Super { .. });
+                let ptr = self.compile(*object);
+                let class = Type::object_id(LLVMTypeOf(ptr))
+                    .and_then(|id| self.classes.get(&id))
+                    .cloned();
 
                 let mut compiled_args = Vec::with_capacity(args.len());
-
-                if is_class {
-                    compiled_args.push(ptr);
-                }
                 for arg in args {
                     let compiled = self.clone().compile(arg.clone());
                     compiled_args.push(compiled);
                 }
 
-                if !is_class {
-                    let func = self.obj_property(ptr, property.clone());
-                    let call = LLVMBuildCall(
-                        self.builder,
-                        func,
-                        compiled_args.as_mut_ptr(),
-                        compiled_args.len() as u32,
-                        to_c_str(property.as_str()).as_ptr(),
-                    );
+                let class = match class {
+                    Some(class) => class,
+                    None => {
+                        let func = self.obj_property(ptr, property.clone());
+                        return LLVMBuildCall(
+                            self.builder,
+                            func,
+                            compiled_args.as_mut_ptr(),
+                            compiled_args.len() as u32,
+                            to_c_str(property.as_str()).as_ptr(),
+                        );
+                    }
+                };
 
-                    return call;
+                if property == "constructor" {
+                    return self.static_call(ptr, class.constructor, compiled_args);
                 }
 
-                let func = class.clone().unwrap().2.get(&property).unwrap().clone();
-
-                let call = LLVMBuildCall(
-                    self.builder,
-                    func,
-                    compiled_args.as_mut_ptr(),
-                    compiled_args.len() as u32,
-                    to_c_str(property.as_str()).as_ptr(),
-                );
+                let slot = class
+                    .vtable
+                    .iter()
+                    .position(|(name, _)| *name == property)
+                    .unwrap();
+                let method = class.vtable[slot].1;
 
-                call
+                // `super.method()` runs the method of the base class, even when it is overridden
+                if is_super {
+                    self.static_call(ptr, method, compiled_args)
+                } else {
+                    self.virtual_call(ptr, slot, method, compiled_args)
+                }
             }
             LLVMNode::Class {
                 ty,
@@ -871,29 +1028,47 @@ impl Compiler {
                 methods,
                 constructor,
                 name: class,
+                binding,
                 static_obj,
+                base,
             } => {
                 let static_obj = self.compile(*static_obj);
-                let alloca = self.create_entry_block_alloca("class", LLVMTypeOf(static_obj));
-                LLVMBuildStore(self.builder, static_obj, alloca);
-                self.variables.insert(class.clone(), alloca);
+                let global = self.global("class", LLVMTypeOf(static_obj));
+                LLVMBuildStore(self.builder, static_obj, global);
+                self.variables.insert(binding, global);
 
-                let klass = self.create_obj(ty, properties);
-                let constructor = self.class_method(class.clone(), LLVMTypeOf(klass), *constructor);
+                let declared = self.declare_method(class.clone(), ty, *constructor.clone());
 
-                let mut n_methods = HashMap::new();
-
-                for (name, method) in methods {
-                    n_methods.insert(name, self.class_method(class.clone(), ty, method));
+                // overriding methods take the slots of the ones of the base class
+                let mut vtable = match base {
+                    Some(base) => self.classes[&type_id(base)].vtable.clone(),
+                    None => vec![],
+                };
+                for (name, method) in &methods {
+                    let method = self.declare_method(class.clone(), ty, method.clone());
+                    match vtable.iter_mut().find(|(slot, _)| slot == name) {
+                        Some(slot) => slot.1 = method,
+                        None => vtable.push((name.clone(), method)),
+                    }
                 }
+
+                let header = self.vtable(&class, &vtable);
+                let klass = self.create_obj(ty, properties, Some(header));
                 self.classes.insert(
-                    LLVMGetArrayLength(LLVMGetElementType(LLVMStructGetTypeAtIndex(
-                        LLVMGetElementType(ty),
-                        0,
-                    ))) as usize as u32,
-                    (klass, constructor, n_methods),
+                    type_id(ty),
+                    Class {
+                        name: class.clone(),
+                        prototype: klass,
+                        constructor: declared,
+                        vtable,
+                    },
                 );
 
+                self.class_method(class.clone(), ty, *constructor);
+                for (_, method) in methods {
+                    self.class_method(class.clone(), ty, method);
+                }
+
                 self.null()
             }
             LLVMNode::ClassInit {
@@ -901,18 +1076,13 @@ impl Compiler {
                 class,
                 constructor_params,
             } => {
-                let (base, constructor, _) = self
-                    .classes
-                    .get(
-                        &(LLVMGetArrayLength(LLVMGetElementType(LLVMStructGetTypeAtIndex(
-                            LLVMGetElementType(class),
-                            0,
-                        ))) as usize as u32),
-                    )
-                    .unwrap()
-                    .clone();
+                let Class {
+                    prototype,
+                    constructor,
+                    ..
+                } = self.classes[&type_id(class)].clone();
 
-                let base = LLVMBuildLoad(self.builder, base, to_c_str("base").as_ptr());
+                let base = LLVMBuildLoad(self.builder, prototype, to_c_str("base").as_ptr());
 
                 let ptr = self.create_entry_block_alloca("class", LLVMTypeOf(base));
                 LLVMBuildStore(self.builder, base, ptr);
@@ -934,6 +1104,93 @@ impl Compiler {
 
                 ptr.into()
             }
+            LLVMNode::Interface {
+                ty,
+                name,
+                methods,
+                val,
+            } => {
+                let val = self.compile(*val);
+                self.interface(ty, &name, &methods, val)
+            }
+            LLVMNode::InterfaceCall {
+                ty,
+                object,
+                slot,
+                args,
+            } => {
+                let object = self.compile(*object);
+                let args = args.into_iter().map(|arg| self.compile(arg)).collect();
+                self.interface_call(ty, object, slot, args)
+            }
+            LLVMNode::NullCheck { ty: _, val, equal } => {
+                let val = self.compile(*val);
+                let null = self.is_null(val);
+                if equal {
+                    null
+                } else {
+                    LLVMBuildNot(self.builder, null, to_c_str("not_null").as_ptr())
+                }
+            }
+            LLVMNode::Wrap { ty, val } => {
+                let val = self.compile(*val);
+                self.wrap(ty, val)
+            }
+            LLVMNode::Unwrap { ty, val } => {
+                let val = self.compile(*val);
+                self.unwrap(ty, val)
+            }
+            LLVMNode::SafeAccess {
+                ty,
+                name,
+                binding,
+                object,
+                access,
+            } => {
+                let object = self.compile(*object);
+                self.safe_access(ty, name, binding, object, *access)
+            }
+            LLVMNode::Elvis { ty, val, default } => {
+                let val = self.compile(*val);
+                self.elvis(ty, val, *default)
+            }
+            LLVMNode::Tuple { ty, elements } => {
+                let mut tuple = LLVMGetUndef(ty);
+                for (i, element) in elements.into_iter().enumerate() {
+                    let element = self.compile(element);
+                    tuple = LLVMBuildInsertValue(
+                        self.builder,
+                        tuple,
+                        element,
+                        i as u32,
+                        to_c_str("tuple").as_ptr(),
+                    );
+                }
+                tuple
+            }
+            LLVMNode::TupleAccess {
+                ty: _,
+                tuple,
+                index,
+            } => {
+                let tuple = self.compile(*tuple);
+                LLVMBuildExtractValue(self.builder, tuple, index, to_c_str("element").as_ptr())
+            }
+            LLVMNode::Variant { ty, tag, payload } => self.variant(ty, tag, payload),
+            LLVMNode::Match { ty, val, arms } => {
+                let val = self.compile(*val);
+                self.match_variant(ty, val, arms)
+            }
+            LLVMNode::Super { ty } => LLVMBuildPointerCast(
+                self.builder,
+                LLVMBuildLoad(
+                    self.builder,
+                    self.variables[&BindingId::SOUL],
+                    to_c_str("soul").as_ptr(),
+                ),
+                ty,
+                to_c_str("super").as_ptr(),
+            ),
         }
     }
 }
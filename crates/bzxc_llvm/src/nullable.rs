@@ -0,0 +1,155 @@
+/*
+* Copyright 2020 to 2021 BlazifyOrg
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+*    http://www.apache.org/licenses/LICENSE-2.0
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use llvm_sys::core::{
+    LLVMAddIncoming, LLVMAppendBasicBlockInContext, LLVMBuildBr, LLVMBuildCondBr,
+    LLVMBuildExtractValue, LLVMBuildInsertValue, LLVMBuildIsNull, LLVMBuildNot, LLVMBuildPhi,
+    LLVMBuildPointerCast, LLVMBuildStore, LLVMConstInt, LLVMConstNull, LLVMGetInsertBlock,
+    LLVMGetTypeKind, LLVMGetUndef, LLVMInt1TypeInContext, LLVMPositionBuilderAtEnd, LLVMTypeOf,
+};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use llvm_sys::LLVMTypeKind::LLVMPointerTypeKind;
+
+use bzxc_shared::{to_c_str, BindingId, LLVMNode};
+
+use crate::Compiler;
+
+impl Compiler {
+    /*
+     * Whether the nullable value `val` is null, nullable pointers are null themselves
+     * and other values are paired with whether they're set
+     */
+    pub(super) unsafe fn is_null(&self, val: LLVMValueRef) -> LLVMValueRef {
+        if LLVMGetTypeKind(LLVMTypeOf(val)) == LLVMPointerTypeKind {
+            return LLVMBuildIsNull(self.builder, val, to_c_str("is_null").as_ptr());
+        }
+
+        let set = LLVMBuildExtractValue(self.builder, val, 0, to_c_str("is_set").as_ptr());
+        LLVMBuildNot(self.builder, set, to_c_str("is_null").as_ptr())
+    }
+
+    /*
+     * Value of the nullable type `ty` holding `val`
+     */
+    pub(super) unsafe fn wrap(&self, ty: LLVMTypeRef, val: LLVMValueRef) -> LLVMValueRef {
+        if LLVMGetTypeKind(ty) == LLVMPointerTypeKind {
+            return LLVMBuildPointerCast(self.builder, val, ty, to_c_str("wrap").as_ptr());
+        }
+
+        let set = LLVMBuildInsertValue(
+            self.builder,
+            LLVMGetUndef(ty),
+            LLVMConstInt(LLVMInt1TypeInContext(self.context), 1, 0),
+            0,
+            to_c_str("wrap").as_ptr(),
+        );
+        LLVMBuildInsertValue(self.builder, set, val, 1, to_c_str("wrap").as_ptr())
+    }
+
+    /*
+     * Value a nullable value checked not to be null holds
+     */
+    pub(super) unsafe fn unwrap(&self, ty: LLVMTypeRef, val: LLVMValueRef) -> LLVMValueRef {
+        if LLVMGetTypeKind(LLVMTypeOf(val)) == LLVMPointerTypeKind {
+            return LLVMBuildPointerCast(self.builder, val, ty, to_c_str("unwrap").as_ptr());
+        }
+
+        LLVMBuildExtractValue(self.builder, val, 1, to_c_str("unwrap").as_ptr())
+    }
+
+    /*
+     * Makes the access after a `?.`, with `object` bound to `name`, when the object isn't null
+     */
+    pub(super) unsafe fn safe_access(
+        &mut self,
+        ty: LLVMTypeRef,
+        name: String,
+        binding: BindingId,
+        object: LLVMValueRef,
+        access: LLVMNode,
+    ) -> LLVMValueRef {
+        let alloca = self.create_entry_block_alloca(name.as_str(), LLVMTypeOf(object));
+        LLVMBuildStore(self.builder, object, alloca);
+        self.variables.insert(binding, alloca);
+
+        let null = self.is_null(object);
+        self.branch(
+            ty,
+            null,
+            |_| LLVMConstNull(ty),
+            |compiler| {
+                let val = compiler.compile(access);
+                compiler.wrap(ty, val)
+            },
+        )
+    }
+
+    /*
+     * Value of `val` or, when it's null, of `default`
+     */
+    pub(super) unsafe fn elvis(
+        &mut self,
+        ty: LLVMTypeRef,
+        val: LLVMValueRef,
+        default: LLVMNode,
+    ) -> LLVMValueRef {
+        let null = self.is_null(val);
+        self.branch(
+            ty,
+            null,
+            |compiler| compiler.compile(default),
+            |compiler| compiler.unwrap(ty, val),
+        )
+    }
+
+    /*
+     * Evaluates `then` when `cond` holds and `otherwise` when it doesn't, only the value
+     * of the one taken is computed
+     */
+    unsafe fn branch(
+        &mut self,
+        ty: LLVMTypeRef,
+        cond: LLVMValueRef,
+        then: impl FnOnce(&mut Self) -> LLVMValueRef,
+        otherwise: impl FnOnce(&mut Self) -> LLVMValueRef,
+    ) -> LLVMValueRef {
+        let parent = self.fn_value();
+        let then_block =
+            LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("then").as_ptr());
+        let else_block =
+            LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("else").as_ptr());
+        let after_block =
+            LLVMAppendBasicBlockInContext(self.context, parent, to_c_str("after").as_ptr());
+        LLVMBuildCondBr(self.builder, cond, then_block, else_block);
+
+        LLVMPositionBuilderAtEnd(self.builder, then_block);
+        let then = then(self);
+        let then_block = LLVMGetInsertBlock(self.builder);
+        LLVMBuildBr(self.builder, after_block);
+
+        LLVMPositionBuilderAtEnd(self.builder, else_block);
+        let otherwise = otherwise(self);
+        let else_block = LLVMGetInsertBlock(self.builder);
+        LLVMBuildBr(self.builder, after_block);
+
+        LLVMPositionBuilderAtEnd(self.builder, after_block);
+        let phi = LLVMBuildPhi(self.builder, ty, to_c_str("branch_val").as_ptr());
+        LLVMAddIncoming(
+            phi,
+            [then, otherwise].as_mut_ptr(),
+            [then_block, else_block].as_mut_ptr(),
+            2,
+        );
+        phi
+    }
+}
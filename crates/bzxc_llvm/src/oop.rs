@@ -1,42 +1,67 @@
 use llvm_sys::core::{
-    LLVMBuildInsertValue, LLVMBuildStore, LLVMBuildStructGEP, LLVMConstNull, LLVMFunctionType,
-    LLVMGetArrayLength, LLVMGetElementType, LLVMGetReturnType, LLVMGetUndef, LLVMPointerType,
-    LLVMStructGetTypeAtIndex, LLVMTypeOf,
+    LLVMAddFunction, LLVMAddGlobal, LLVMArrayType, LLVMBuildBitCast, LLVMBuildCall,
+    LLVMBuildExtractValue, LLVMBuildGEP, LLVMBuildInsertValue, LLVMBuildLoad, LLVMBuildPointerCast,
+    LLVMBuildStore, LLVMBuildStructGEP, LLVMConstArray, LLVMConstBitCast, LLVMConstInBoundsGEP,
+    LLVMConstInt, LLVMConstNull, LLVMCountParamTypes, LLVMFunctionType, LLVMGetElementType,
+    LLVMGetNamedGlobal, LLVMGetParamTypes, LLVMGetReturnType, LLVMGetUndef, LLVMInt32TypeInContext,
+    LLVMInt8TypeInContext, LLVMPointerType, LLVMSetGlobalConstant, LLVMSetInitializer, LLVMTypeOf,
 };
 use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
 
-use bzxc_shared::{to_c_str, LLVMNode};
+use bzxc_shared::{to_c_str, BindingId, LLVMNode, Type};
 
 use crate::Compiler;
 
+/*
+ * A compiled class, its methods in the order of the slots of its vtable
+ */
+#[derive(Debug, Clone)]
+pub(super) struct Class {
+    pub name: String,
+    pub prototype: LLVMValueRef,
+    pub constructor: LLVMValueRef,
+    pub vtable: Vec<(String, LLVMValueRef)>,
+}
+
+/*
+ * Id of the objects the pointer type `ty` points to
+ */
+pub(super) unsafe fn type_id(ty: LLVMTypeRef) -> u32 {
+    Type::object_id(ty).expect("not an object")
+}
+
 impl Compiler {
+    /*
+     * Objects of classes start with a pointer to the vtable of their class
+     */
     pub(super) unsafe fn create_obj(
         &mut self,
         ty: LLVMTypeRef,
         properties: Vec<(String, LLVMNode)>,
+        vtable: Option<LLVMValueRef>,
     ) -> LLVMValueRef {
-        let aligner_ty = LLVMGetElementType(LLVMStructGetTypeAtIndex(LLVMGetElementType(ty), 0));
+        let id = type_id(ty);
+        // prototypes of classes are global, functions defined anywhere make objects of them
+        let ptr = match vtable {
+            Some(_) => self.global("prototype", LLVMGetElementType(ty)),
+            None => self.create_entry_block_alloca("obj", LLVMGetElementType(ty)),
+        };
 
-        let aligner = LLVMGetArrayLength(aligner_ty) as usize as u32;
-
-        let aligner_val = LLVMConstNull(aligner_ty);
-        let aligner_ptr = self.create_entry_block_alloca("aligner", aligner_ty);
-        LLVMBuildStore(self.builder, aligner_val, aligner_ptr);
-
-        let struct_val = LLVMBuildInsertValue(
-            self.builder,
-            LLVMGetUndef(LLVMGetElementType(ty)),
-            aligner_ptr,
-            0,
-            to_c_str("c_to_bzx_obj_load").as_ptr(),
-        );
-
-        let ptr = self.create_entry_block_alloca("obj", LLVMTypeOf(struct_val));
-        LLVMBuildStore(self.builder, struct_val, ptr);
+        let header = match vtable {
+            Some(vtable) => {
+                LLVMBuildStore(
+                    self.builder,
+                    vtable,
+                    LLVMBuildStructGEP(self.builder, ptr, 0, to_c_str("header").as_ptr()),
+                );
+                1
+            }
+            None => 0,
+        };
 
         for (i, (name, val)) in properties.iter().enumerate() {
-            let idx = i + 1;
-            self.objects.insert((name.clone(), aligner), idx);
+            let idx = i + header;
+            self.objects.insert((name.clone(), id), idx);
             LLVMBuildStore(
                 self.builder,
                 self.compile(val.clone()),
@@ -52,6 +77,22 @@ impl Compiler {
         ptr
     }
 
+    /*
+     * Zeroed global of the type `ty`, written when the statement defining it runs
+     */
+    pub(super) unsafe fn global(&self, name: &str, ty: LLVMTypeRef) -> LLVMValueRef {
+        let global = LLVMAddGlobal(self.module, ty, to_c_str(name).as_ptr());
+        LLVMSetInitializer(global, LLVMConstNull(ty));
+        global
+    }
+
+    /*
+     * Number of fields objects of the pointer type `ty` have before their properties
+     */
+    pub(super) unsafe fn header(&self, ty: LLVMTypeRef) -> u32 {
+        Type::object_id(ty).map_or(0, |id| self.classes.contains_key(&id) as u32)
+    }
+
     pub(super) unsafe fn obj_property(
         &mut self,
         object: LLVMValueRef,
@@ -59,13 +100,7 @@ impl Compiler {
     ) -> LLVMValueRef {
         let i = self
             .objects
-            .get(&(
-                property,
-                LLVMGetArrayLength(LLVMGetElementType(LLVMStructGetTypeAtIndex(
-                    LLVMGetElementType(LLVMTypeOf(object)),
-                    0,
-                ))) as usize as u32,
-            ))
+            .get(&(property, type_id(LLVMTypeOf(object))))
             .unwrap();
 
         LLVMBuildStructGEP(
@@ -76,25 +111,24 @@ impl Compiler {
         )
     }
 
-    pub(super) unsafe fn class_method(
-        &mut self,
-        class: String,
-        klass: LLVMTypeRef,
-        method: LLVMNode,
-    ) -> LLVMValueRef {
+    /*
+     * Methods take the object they're called on as their first parameter, `soul`
+     */
+    unsafe fn method(&self, class: &str, klass: LLVMTypeRef, method: LLVMNode) -> LLVMNode {
         match method {
             LLVMNode::Fun {
                 body,
                 name,
+                binding,
                 params,
                 ty,
             } => {
-                let mut n_params = vec![("soul".to_string(), klass)];
+                let mut n_params = vec![(BindingId::SOUL, "soul".to_string(), klass)];
                 n_params.extend(params);
 
                 let mut pty = n_params
                     .iter()
-                    .map(|(_, ty)| ty.clone())
+                    .map(|(_, _, ty)| ty.clone())
                     .collect::<Vec<_>>();
 
                 let ty = LLVMFunctionType(
@@ -103,14 +137,296 @@ impl Compiler {
                     pty.len() as u32,
                     0,
                 );
-                self.compile(LLVMNode::Fun {
+                LLVMNode::Fun {
                     body,
                     name: format!("{}%{}", class, name),
+                    binding,
                     params: n_params.clone(),
                     ty: LLVMPointerType(ty, 0),
-                })
+                }
             }
             _ => unreachable!(),
         }
     }
+
+    /*
+     * Declares a method before its body is compiled, so methods can call each other
+     */
+    pub(super) unsafe fn declare_method(
+        &mut self,
+        class: String,
+        klass: LLVMTypeRef,
+        method: LLVMNode,
+    ) -> LLVMValueRef {
+        match self.method(&class, klass, method) {
+            LLVMNode::Fun { name, ty, .. } => LLVMAddFunction(
+                self.module,
+                to_c_str(name.as_str()).as_ptr(),
+                LLVMGetElementType(ty),
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    pub(super) unsafe fn class_method(
+        &mut self,
+        class: String,
+        klass: LLVMTypeRef,
+        method: LLVMNode,
+    ) -> LLVMValueRef {
+        let method = self.method(&class, klass, method);
+        self.compile(method)
+    }
+
+    /*
+     * Constant table of the methods of `class`, its objects start with a pointer to it
+     */
+    pub(super) unsafe fn vtable(
+        &self,
+        class: &str,
+        slots: &[(String, LLVMValueRef)],
+    ) -> LLVMValueRef {
+        let i8_ptr = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
+        let mut methods = slots
+            .iter()
+            .map(|(_, method)| LLVMConstBitCast(*method, i8_ptr))
+            .collect::<Vec<_>>();
+
+        let vtable = LLVMAddGlobal(
+            self.module,
+            LLVMArrayType(i8_ptr, methods.len() as u32),
+            to_c_str(format!("{}%vtable", class).as_str()).as_ptr(),
+        );
+        LLVMSetInitializer(
+            vtable,
+            LLVMConstArray(i8_ptr, methods.as_mut_ptr(), methods.len() as u32),
+        );
+        LLVMSetGlobalConstant(vtable, 1);
+
+        LLVMConstBitCast(vtable, i8_ptr)
+    }
+
+    /*
+     * Calls the method in the `slot` of the vtable of the object `soul` points to,
+     * typed after `method`, the one of the class `soul` is known as
+     */
+    pub(super) unsafe fn virtual_call(
+        &self,
+        soul: LLVMValueRef,
+        slot: usize,
+        method: LLVMValueRef,
+        mut args: Vec<LLVMValueRef>,
+    ) -> LLVMValueRef {
+        let i8_ptr = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
+        let header = LLVMBuildLoad(
+            self.builder,
+            LLVMBuildStructGEP(self.builder, soul, 0, to_c_str("header").as_ptr()),
+            to_c_str("header").as_ptr(),
+        );
+        let vtable = LLVMBuildBitCast(
+            self.builder,
+            header,
+            LLVMPointerType(i8_ptr, 0),
+            to_c_str("vtable").as_ptr(),
+        );
+        let slot = LLVMBuildLoad(
+            self.builder,
+            LLVMBuildGEP(
+                self.builder,
+                vtable,
+                [LLVMConstInt(
+                    LLVMInt32TypeInContext(self.context),
+                    slot as u64,
+                    0,
+                )]
+                .as_mut_ptr(),
+                1,
+                to_c_str("slot").as_ptr(),
+            ),
+            to_c_str("method").as_ptr(),
+        );
+
+        let fun_ty = LLVMGetElementType(LLVMTypeOf(method));
+        let mut params = vec![LLVMTypeOf(soul); LLVMCountParamTypes(fun_ty) as usize];
+        LLVMGetParamTypes(fun_ty, params.as_mut_ptr());
+        params[0] = LLVMTypeOf(soul);
+        let fun_ty = LLVMFunctionType(
+            LLVMGetReturnType(fun_ty),
+            params.as_mut_ptr(),
+            params.len() as u32,
+            0,
+        );
+        let fun = LLVMBuildBitCast(
+            self.builder,
+            slot,
+            LLVMPointerType(fun_ty, 0),
+            to_c_str("method").as_ptr(),
+        );
+
+        args.insert(0, soul);
+        LLVMBuildCall(
+            self.builder,
+            fun,
+            args.as_mut_ptr(),
+            args.len() as u32,
+            to_c_str("call").as_ptr(),
+        )
+    }
+
+    /*
+     * Calls `method` directly, `soul` seen as the class the method was defined in
+     */
+    pub(super) unsafe fn static_call(
+        &self,
+        soul: LLVMValueRef,
+        method: LLVMValueRef,
+        mut args: Vec<LLVMValueRef>,
+    ) -> LLVMValueRef {
+        let fun_ty = LLVMGetElementType(LLVMTypeOf(method));
+        let mut params = vec![LLVMTypeOf(soul); LLVMCountParamTypes(fun_ty) as usize];
+        LLVMGetParamTypes(fun_ty, params.as_mut_ptr());
+
+        args.insert(
+            0,
+            LLVMBuildPointerCast(self.builder, soul, params[0], to_c_str("soul").as_ptr()),
+        );
+        LLVMBuildCall(
+            self.builder,
+            method,
+            args.as_mut_ptr(),
+            args.len() as u32,
+            to_c_str("call").as_ptr(),
+        )
+    }
+
+    /*
+     * Pairs `object` with the slots the methods of `interface` have in the vtable of its class,
+     * the table of slots is made once for every class and interface
+     */
+    pub(super) unsafe fn interface(
+        &self,
+        ty: LLVMTypeRef,
+        interface: &str,
+        methods: &[String],
+        object: LLVMValueRef,
+    ) -> LLVMValueRef {
+        let class = &self.classes[&type_id(LLVMTypeOf(object))];
+        let i32_ty = LLVMInt32TypeInContext(self.context);
+        let name = to_c_str(format!("{}%{}", class.name, interface).as_str()).into_owned();
+
+        let mut slots = LLVMGetNamedGlobal(self.module, name.as_ptr());
+        if slots.is_null() {
+            let mut indices = methods
+                .iter()
+                .map(|method| {
+                    let slot = class.vtable.iter().position(|(name, _)| name == method);
+                    LLVMConstInt(i32_ty, slot.unwrap() as u64, 0)
+                })
+                .collect::<Vec<_>>();
+
+            slots = LLVMAddGlobal(
+                self.module,
+                LLVMArrayType(i32_ty, indices.len() as u32),
+                name.as_ptr(),
+            );
+            LLVMSetInitializer(
+                slots,
+                LLVMConstArray(i32_ty, indices.as_mut_ptr(), indices.len() as u32),
+            );
+            LLVMSetGlobalConstant(slots, 1);
+        }
+
+        let zero = LLVMConstInt(i32_ty, 0, 0);
+        let slots = LLVMConstInBoundsGEP(slots, [zero, zero].as_mut_ptr(), 2);
+        let object = LLVMBuildBitCast(
+            self.builder,
+            object,
+            LLVMPointerType(LLVMInt8TypeInContext(self.context), 0),
+            to_c_str("object").as_ptr(),
+        );
+
+        let value = LLVMBuildInsertValue(
+            self.builder,
+            LLVMGetUndef(ty),
+            object,
+            0,
+            to_c_str("interface").as_ptr(),
+        );
+        LLVMBuildInsertValue(
+            self.builder,
+            value,
+            slots,
+            1,
+            to_c_str("interface").as_ptr(),
+        )
+    }
+
+    /*
+     * Calls the method in the `slot` of the interface value `interface`,
+     * through the vtable of the class of its object
+     */
+    pub(super) unsafe fn interface_call(
+        &self,
+        ty: LLVMTypeRef,
+        interface: LLVMValueRef,
+        slot: usize,
+        mut args: Vec<LLVMValueRef>,
+    ) -> LLVMValueRef {
+        let i8_ptr = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
+        let i32_ty = LLVMInt32TypeInContext(self.context);
+
+        let object = LLVMBuildExtractValue(self.builder, interface, 0, to_c_str("object").as_ptr());
+        let slots = LLVMBuildExtractValue(self.builder, interface, 1, to_c_str("slots").as_ptr());
+        let slot = LLVMBuildLoad(
+            self.builder,
+            LLVMBuildGEP(
+                self.builder,
+                slots,
+                [LLVMConstInt(i32_ty, slot as u64, 0)].as_mut_ptr(),
+                1,
+                to_c_str("slot").as_ptr(),
+            ),
+            to_c_str("slot").as_ptr(),
+        );
+
+        // the vtable is the first field of every object of a class
+        let header = LLVMBuildBitCast(
+            self.builder,
+            object,
+            LLVMPointerType(LLVMPointerType(i8_ptr, 0), 0),
+            to_c_str("header").as_ptr(),
+        );
+        let vtable = LLVMBuildLoad(self.builder, header, to_c_str("vtable").as_ptr());
+        let method = LLVMBuildLoad(
+            self.builder,
+            LLVMBuildGEP(
+                self.builder,
+                vtable,
+                [slot].as_mut_ptr(),
+                1,
+                to_c_str("method").as_ptr(),
+            ),
+            to_c_str("method").as_ptr(),
+        );
+
+        args.insert(0, object);
+        let mut params = args.iter().map(|arg| LLVMTypeOf(*arg)).collect::<Vec<_>>();
+        let fun = LLVMBuildBitCast(
+            self.builder,
+            method,
+            LLVMPointerType(
+                LLVMFunctionType(ty, params.as_mut_ptr(), params.len() as u32, 0),
+                0,
+            ),
+            to_c_str("method").as_ptr(),
+        );
+
+        LLVMBuildCall(
+            self.builder,
+            fun,
+            args.as_mut_ptr(),
+            args.len() as u32,
+            to_c_str("call").as_ptr(),
+        )
+    }
 }
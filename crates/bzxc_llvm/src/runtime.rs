@@ -0,0 +1,132 @@
+/*
+* Copyright 2020 to 2021 BlazifyOrg
+* Licensed under the Apache License, Version 2.0 (the "License");
+* you may not use this file except in compliance with the License.
+* You may obtain a copy of the License at
+*    http://www.apache.org/licenses/LICENSE-2.0
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific language governing permissions and
+* limitations under the License.
+*/
+
+use llvm_sys::core::{
+    LLVMAddFunction, LLVMBuildCall, LLVMBuildGlobalStringPtr, LLVMBuildPointerCast,
+    LLVMBuildUnreachable, LLVMConstInt, LLVMFunctionType, LLVMGetNamedFunction,
+    LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMInt8TypeInContext, LLVMPointerType,
+    LLVMSetLinkage, LLVMSizeOf, LLVMVoidTypeInContext,
+};
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use llvm_sys::LLVMLinkage::LLVMExternalLinkage;
+
+use bzxc_shared::to_c_str;
+
+use crate::Compiler;
+
+impl Compiler {
+    /*
+     * Declares a function of the C library, once per module
+     */
+    pub(super) unsafe fn libc(
+        &self,
+        name: &str,
+        ret: LLVMTypeRef,
+        mut params: Vec<LLVMTypeRef>,
+        var_args: bool,
+    ) -> LLVMValueRef {
+        let func = LLVMGetNamedFunction(self.module, to_c_str(name).as_ptr());
+        if !func.is_null() {
+            return func;
+        }
+
+        let func = LLVMAddFunction(
+            self.module,
+            to_c_str(name).as_ptr(),
+            LLVMFunctionType(
+                ret,
+                params.as_mut_ptr(),
+                params.len() as u32,
+                var_args as i32,
+            ),
+        );
+        LLVMSetLinkage(func, LLVMExternalLinkage);
+        func
+    }
+
+    /*
+     * Heap allocates a value of type `ty`, returning a pointer to it
+     */
+    pub(super) unsafe fn malloc(&self, ty: LLVMTypeRef) -> LLVMValueRef {
+        let i8_ptr = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
+        let malloc = self.libc(
+            "malloc",
+            i8_ptr,
+            vec![LLVMInt64TypeInContext(self.context)],
+            false,
+        );
+
+        let ptr = LLVMBuildCall(
+            self.builder,
+            malloc,
+            [LLVMSizeOf(ty)].as_mut_ptr(),
+            1,
+            to_c_str("malloc").as_ptr(),
+        );
+        LLVMBuildPointerCast(
+            self.builder,
+            ptr,
+            LLVMPointerType(ty, 0),
+            to_c_str("malloc_cast").as_ptr(),
+        )
+    }
+
+    /*
+     * Prints the formatted message to stderr and exits with status 1,
+     * the current block is terminated
+     */
+    pub(super) unsafe fn panic(&self, message: &str, args: Vec<LLVMValueRef>) {
+        let i32_ty = LLVMInt32TypeInContext(self.context);
+        let dprintf = self.libc(
+            "dprintf",
+            i32_ty,
+            vec![
+                i32_ty,
+                LLVMPointerType(LLVMInt8TypeInContext(self.context), 0),
+            ],
+            true,
+        );
+        let exit = self.libc(
+            "exit",
+            LLVMVoidTypeInContext(self.context),
+            vec![i32_ty],
+            false,
+        );
+
+        let mut dprintf_args = vec![
+            LLVMConstInt(i32_ty, 2, 0),
+            LLVMBuildGlobalStringPtr(
+                self.builder,
+                to_c_str(format!("{}\n", message).as_str()).as_ptr(),
+                to_c_str("panic_msg").as_ptr(),
+            ),
+        ];
+        dprintf_args.extend(args);
+
+        LLVMBuildCall(
+            self.builder,
+            dprintf,
+            dprintf_args.as_mut_ptr(),
+            dprintf_args.len() as u32,
+            to_c_str("").as_ptr(),
+        );
+        LLVMBuildCall(
+            self.builder,
+            exit,
+            [LLVMConstInt(i32_ty, 1, 0)].as_mut_ptr(),
+            1,
+            to_c_str("").as_ptr(),
+        );
+        LLVMBuildUnreachable(self.builder);
+    }
+}
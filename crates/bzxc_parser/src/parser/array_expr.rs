@@ -75,6 +75,9 @@ impl Parser {
             self.advance();
         }
 
-        res.success(Node::ArrayNode { element_nodes })
+        res.success(Node::ArrayNode {
+            element_nodes,
+            token,
+        })
     }
 }
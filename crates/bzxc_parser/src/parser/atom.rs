@@ -13,7 +13,7 @@
 
 use super::Parser;
 use crate::parse_result::ParseResult;
-use bzxc_shared::{Error, Node, Token, Tokens};
+use bzxc_shared::{Error, Node, Symbol, Token, Tokens};
 
 impl Parser {
     /*
@@ -60,17 +60,41 @@ impl Parser {
             if res.error.is_some() {
                 return res;
             }
+
+            // a comma makes it a tuple, `(a,)` has a single element
+            let mut elements = vec![];
+            while self.current_token.value == Tokens::Comma {
+                res.register_advancement();
+                self.advance();
+
+                if elements.is_empty() {
+                    elements.push(expr.clone().unwrap());
+                }
+                if self.current_token.value == Tokens::RightParenthesis {
+                    break;
+                }
+
+                let element = res.register(self.expr());
+                if res.error.is_some() {
+                    return res;
+                }
+                elements.push(element.unwrap());
+            }
+
             if self.current_token.clone().value != Tokens::RightParenthesis {
                 return res.failure(Error::new(
                     "Invalid Syntax",
                     self.current_token.clone().pos_start,
                     self.current_token.clone().pos_end,
-                    "Expected ')'",
+                    "Expected ')' or ','",
                 ));
             }
 
             res.register_advancement();
             self.advance();
+            if !elements.is_empty() {
+                return res.success(Node::TupleNode { elements });
+            }
             return res.success(expr.unwrap());
         } else if token.value == Tokens::LeftSquareBraces {
             let array_expr = res.register(self.array_expr());
@@ -114,6 +138,24 @@ impl Parser {
                 return res;
             }
             return res.success(class_def.unwrap());
+        } else if token.value == Tokens::Keyword("interface") {
+            let interface_def = res.register(self.interface_def());
+            if res.error.is_some() {
+                return res;
+            }
+            return res.success(interface_def.unwrap());
+        } else if token.value == Tokens::Keyword("enum") {
+            let enum_def = res.register(self.enum_def());
+            if res.error.is_some() {
+                return res;
+            }
+            return res.success(enum_def.unwrap());
+        } else if token.value == Tokens::Keyword("match") {
+            let match_expr = res.register(self.match_expr());
+            if res.error.is_some() {
+                return res;
+            }
+            return res.success(match_expr.unwrap());
         } else if token.value == Tokens::Keyword("new") {
             let class_init = res.register(self.class_init());
             if res.error.is_some() {
@@ -122,7 +164,7 @@ impl Parser {
             return res.success(class_init.unwrap());
         } else if token.value == Tokens::Keyword("soul") {
             let token = Token::new(
-                Tokens::Identifier("soul"),
+                Tokens::Identifier(Symbol::intern("soul")),
                 self.current_token.pos_start,
                 self.current_token.pos_end,
             );
@@ -130,6 +172,16 @@ impl Parser {
             res.register_advancement();
 
             return res.success(Node::VarAccessNode { token });
+        } else if token.value == Tokens::Keyword("super") {
+            self.advance();
+            res.register_advancement();
+
+            return res.success(Node::SuperNode { token });
+        } else if token.value == Tokens::Keyword("null") {
+            self.advance();
+            res.register_advancement();
+
+            return res.success(Node::NullNode { token });
         } else if token.value == Tokens::Keyword("extern") {
             let extern_def = res.register(self.extern_def());
             if res.error.is_some() {
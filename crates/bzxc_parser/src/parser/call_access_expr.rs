@@ -1,4 +1,4 @@
-use bzxc_shared::{Error, Node, Tokens};
+use bzxc_shared::{Error, Node, Symbol, Token, Tokens};
 
 use crate::parse_result::ParseResult;
 
@@ -10,17 +10,27 @@ impl Parser {
         expr: Option<Node>,
         mut res: ParseResult,
     ) -> ParseResult {
-        if self.current_token.value == Tokens::Dot {
+        let type_args = match expr {
+            Some(Node::VarAccessNode { .. }) => self.call_type_args(&mut res),
+            _ => vec![],
+        };
+
+        if [Tokens::Dot, Tokens::SafeDot].contains(&self.current_token.value) {
+            let (target, safe) = Self::access_target(expr.clone().unwrap(), &self.current_token);
             self.advance();
             res.register_advancement();
 
+            if let Tokens::Int(_) = self.current_token.value {
+                return self.tuple_access(target, safe, res);
+            }
+
             if let Tokens::Identifier(_) = self.current_token.value {
             } else {
                 return res.failure(Error::new(
                     "Invalid Syntax",
                     self.current_token.pos_start.clone(),
                     self.current_token.pos_end.clone(),
-                    "Expected identifier",
+                    "Expected identifier or index",
                 ));
             }
 
@@ -40,11 +50,14 @@ impl Parser {
                     return res;
                 }
 
-                return res.success(Node::ObjectPropEdit {
-                    object: Box::new(expr.clone().unwrap()),
-                    property: id,
-                    new_val: Box::new(val.unwrap()),
-                });
+                return res.success(Self::safe_access(
+                    Node::ObjectPropEdit {
+                        object: Box::new(target),
+                        property: id,
+                        new_val: Box::new(val.unwrap()),
+                    },
+                    safe,
+                ));
             } else if self.current_token.value == Tokens::LeftParenthesis {
                 let mut arg_nodes: Vec<Node> = vec![];
                 res.register_advancement();
@@ -94,28 +107,35 @@ impl Parser {
                 }
 
                 l = Node::ObjectMethodCall {
-                    object: Box::new(expr.clone().unwrap()),
+                    object: Box::new(target),
                     property: id,
                     args: arg_nodes,
-                }
+                };
+                l = Self::safe_access(l, safe);
             } else {
                 l = Node::ObjectPropAccess {
-                    object: Box::new(expr.clone().unwrap()),
+                    object: Box::new(target),
                     property: id,
                 };
+                l = Self::safe_access(l, safe);
             }
 
-            while self.current_token.value == Tokens::Dot {
+            while [Tokens::Dot, Tokens::SafeDot].contains(&self.current_token.value) {
+                let (target, safe) = Self::access_target(l, &self.current_token);
                 self.advance();
                 res.register_advancement();
 
+                if let Tokens::Int(_) = self.current_token.value {
+                    return self.tuple_access(target, safe, res);
+                }
+
                 if let Tokens::Identifier(_) = self.current_token.value {
                 } else {
                     return res.failure(Error::new(
                         "Invalid Syntax",
                         self.current_token.pos_start.clone(),
                         self.current_token.pos_end.clone(),
-                        "Expected identifier",
+                        "Expected identifier or index",
                     ));
                 }
 
@@ -133,11 +153,14 @@ impl Parser {
                         return res;
                     }
 
-                    return res.success(Node::ObjectPropEdit {
-                        object: Box::new(l),
-                        property: id,
-                        new_val: Box::new(expr.unwrap()),
-                    });
+                    return res.success(Self::safe_access(
+                        Node::ObjectPropEdit {
+                            object: Box::new(target),
+                            property: id,
+                            new_val: Box::new(expr.unwrap()),
+                        },
+                        safe,
+                    ));
                 } else if self.current_token.value == Tokens::LeftParenthesis {
                     let mut arg_nodes: Vec<Node> = vec![];
                     res.register_advancement();
@@ -187,15 +210,17 @@ impl Parser {
                     }
 
                     l = Node::ObjectMethodCall {
-                        object: Box::new(l),
+                        object: Box::new(target),
                         property: id,
                         args: arg_nodes,
-                    }
+                    };
+                    l = Self::safe_access(l, safe);
                 } else {
                     l = Node::ObjectPropAccess {
-                        object: Box::new(l),
+                        object: Box::new(target),
                         property: id,
                     };
+                    l = Self::safe_access(l, safe);
                 }
             }
             return res.success(l);
@@ -248,6 +273,7 @@ impl Parser {
             }
             return res.success(Node::CallNode {
                 node_to_call: Box::new(expr.clone().unwrap()),
+                type_args,
                 args: arg_nodes,
             });
         } else if self.current_token.value == Tokens::LeftSquareBraces {
@@ -279,4 +305,77 @@ impl Parser {
 
         res.success(expr.unwrap())
     }
+
+    /*
+     * Parses the index after the dot of `pair.0`, accesses after it are parsed on the element
+     */
+    fn tuple_access(
+        &mut self,
+        target: Node,
+        safe: Option<(Node, Token)>,
+        mut res: ParseResult,
+    ) -> ParseResult {
+        let access = Node::TupleAccess {
+            tuple: Box::new(target),
+            index: self.current_token,
+        };
+        res.register_advancement();
+        self.advance();
+
+        self.call_access_expr(Some(Self::safe_access(access, safe)), res)
+    }
+
+    /*
+     * Object the property after `dot` is accessed on, after a `?.` it's a placeholder
+     * bound to the object when it isn't null
+     */
+    fn access_target(object: Node, dot: &Token) -> (Node, Option<(Node, Token)>) {
+        if dot.value != Tokens::SafeDot {
+            return (object, None);
+        }
+
+        let name = Token::new(
+            Tokens::Identifier(Symbol::intern(&format!("%safe{}%", dot.pos_start.index))),
+            dot.pos_start,
+            dot.pos_end,
+        );
+        (Node::VarAccessNode { token: name }, Some((object, name)))
+    }
+
+    /*
+     * Wraps an access made after a `?.` so it's skipped when the object is null
+     */
+    fn safe_access(access: Node, safe: Option<(Node, Token)>) -> Node {
+        match safe {
+            Some((object, name)) => Node::SafeAccessNode {
+                object: Box::new(object),
+                name,
+                access: Box::new(access),
+            },
+            None => access,
+        }
+    }
+
+    /*
+     * Type arguments of a call like `max<float>(a, b)`, a `<` which isn't followed by types
+     * and a `>(` is a comparison and is left as it is
+     */
+    fn call_type_args(&mut self, res: &mut ParseResult) -> Vec<Node> {
+        if self.current_token.value != Tokens::LessThan {
+            return vec![];
+        }
+
+        let start = self.token_index;
+        let mut attempt = ParseResult::new();
+        match self.type_args(&mut attempt) {
+            Ok(args) if self.current_token.value == Tokens::LeftParenthesis => {
+                res.register(attempt);
+                args
+            }
+            _ => {
+                self.reverse(self.token_index - start);
+                vec![]
+            }
+        }
+    }
 }
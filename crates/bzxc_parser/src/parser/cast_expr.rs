@@ -0,0 +1,46 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Node, Tokens};
+
+impl Parser {
+    /*
+     * Parse a factor followed by any number of `as type` casts
+     */
+    pub(crate) fn cast_expr(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+        let mut node = res.register(self.factor());
+        if res.error.is_some() {
+            return res;
+        }
+
+        while self.current_token.value == Tokens::Keyword("as") {
+            res.register_advancement();
+            self.advance();
+
+            let ty = res.register(self.type_expr());
+            if res.error.is_some() {
+                return res;
+            }
+
+            node = Option::from(Node::CastNode {
+                node: Box::new(node.unwrap()),
+                ty: Box::new(ty.unwrap()),
+            });
+        }
+
+        res.success(node.unwrap())
+    }
+}
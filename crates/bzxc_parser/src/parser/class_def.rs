@@ -13,7 +13,7 @@
 
 use super::Parser;
 use crate::parse_result::ParseResult;
-use bzxc_shared::{Error, Node, Token, Tokens};
+use bzxc_shared::{Error, Node, Tokens};
 
 impl Parser {
     /*
@@ -47,24 +47,43 @@ impl Parser {
             ));
         }
 
-        let mut constructor_def = false;
+        let type_params = match self.type_params(&mut res) {
+            Ok(params) => params,
+            Err(error) => return res.failure(error),
+        };
+
+        let mut base = None;
+        if self.current_token.value == Tokens::Keyword("extends") {
+            res.register_advancement();
+            self.advance();
+
+            base = res.register(self.type_expr());
+            if res.error.is_some() {
+                return res;
+            }
+        }
+
+        let mut interfaces = vec![];
+        if self.current_token.value == Tokens::Keyword("implements") {
+            loop {
+                res.register_advancement();
+                self.advance();
+
+                let interface = res.register(self.type_expr());
+                if res.error.is_some() {
+                    return res;
+                }
+                interfaces.push(interface.unwrap());
+
+                if self.current_token.value != Tokens::Comma {
+                    break;
+                }
+            }
+        }
+
         let mut methods = vec![];
         let mut static_members = vec![];
-
-        let mut constructor = (
-            vec![],
-            Box::new(Node::Statements {
-                statements: vec![Node::ReturnNode {
-                    value: Box::new(Some(Node::VarAccessNode {
-                        token: Token::new(
-                            Tokens::Identifier("soul"),
-                            self.current_token.pos_start,
-                            self.current_token.pos_end,
-                        ),
-                    })),
-                }],
-            }),
-        );
+        let mut constructor = None;
         let mut properties = vec![];
 
         if self.current_token.value != Tokens::LeftCurlyBraces {
@@ -103,27 +122,18 @@ impl Parser {
             }
 
             match statement.clone().unwrap() {
-                Node::VarAssignNode {
-                    name,
-                    value,
-                    reassignable: _,
-                } => {
+                Node::VarAssignNode { name, .. } => {
                     if is_static {
-                        static_members.push((name, *value));
+                        static_members.push((name, statement.unwrap()));
                     } else {
-                        properties.push((name, *value));
+                        properties.push((name, statement.unwrap()));
                     }
                 }
-                Node::FunDef {
-                    name,
-                    arg_tokens,
-                    body_node,
-                } => {
+                Node::FunDef { name, .. } => {
                     // if name is none and there is no constructor, then it is a constructor orelse it is a function and if static keyword is present it is static member
                     if name.is_none() {
-                        if !constructor_def {
-                            constructor_def = true;
-                            constructor = (arg_tokens, body_node);
+                        if constructor.is_none() {
+                            constructor = statement;
                         } else {
                             return res.failure(Error::new(
                                 "Syntax Error",
@@ -134,16 +144,9 @@ impl Parser {
                         }
                     } else {
                         if is_static {
-                            static_members.push((
-                                name.unwrap(),
-                                Node::FunDef {
-                                    name,
-                                    arg_tokens,
-                                    body_node,
-                                },
-                            ));
+                            static_members.push((name.unwrap(), statement.unwrap()));
                         } else {
-                            methods.push((name.unwrap(), arg_tokens, *body_node));
+                            methods.push((name.unwrap(), statement.unwrap()));
                         }
                     }
                 }
@@ -171,11 +174,14 @@ impl Parser {
         res.register_advancement();
 
         res.success(Node::ClassDefNode {
-            constructor,
+            constructor: Box::new(constructor),
             methods,
             name,
             properties,
             static_members,
+            type_params,
+            base: Box::new(base),
+            interfaces,
         })
     }
 }
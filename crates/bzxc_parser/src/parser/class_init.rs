@@ -50,6 +50,11 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
+        let type_args = match self.type_args(&mut res) {
+            Ok(args) => args,
+            Err(error) => return res.failure(error),
+        };
+
         if self.current_token.value == Tokens::LeftParenthesis {
             res.register_advancement();
             self.advance();
@@ -107,6 +112,7 @@ impl Parser {
 
         res.success(Node::ClassInitNode {
             name,
+            type_args,
             constructor_params,
         })
     }
@@ -39,7 +39,7 @@ impl Parser {
             });
         }
 
-        let mut left = res.register(self.arith_expr());
+        let mut left = res.register(self.elvis_expr());
         if res.error.is_some() {
             return res;
         }
@@ -58,7 +58,7 @@ impl Parser {
             res.register_advancement();
             self.advance();
 
-            let right = res.register(self.arith_expr());
+            let right = res.register(self.elvis_expr());
             if res.error.is_some() {
                 return res;
             }
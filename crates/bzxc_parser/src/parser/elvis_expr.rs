@@ -0,0 +1,47 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Node, Tokens};
+
+impl Parser {
+    /*
+     * Parses a `?:` expression, the default on the right is used when the value is null
+     */
+    pub(crate) fn elvis_expr(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        let value = res.register(self.arith_expr());
+        if res.error.is_some() {
+            return res;
+        }
+
+        if self.current_token.value != Tokens::Elvis {
+            return res.success(value.unwrap());
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let default = res.register(self.elvis_expr());
+        if res.error.is_some() {
+            return res;
+        }
+
+        res.success(Node::ElvisNode {
+            value: Box::new(value.unwrap()),
+            default: Box::new(default.unwrap()),
+        })
+    }
+}
@@ -0,0 +1,124 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Error, Node, Tokens};
+
+impl Parser {
+    /*
+     * Parses an enum, its variants are separated by commas or newlines
+     * and the ones carrying a payload list the types of it
+     */
+    pub(crate) fn enum_def(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        if self.current_token.value != Tokens::Keyword("enum") {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected 'enum'",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let name = self.current_token;
+        if let Tokens::Identifier(_) = name.value {
+            res.register_advancement();
+            self.advance();
+        } else {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected identifier",
+            ));
+        }
+
+        if self.current_token.value != Tokens::LeftCurlyBraces {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected '{'",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let mut variants = vec![];
+        while self.current_token.value != Tokens::RightCurlyBraces {
+            if self.current_token.value == Tokens::Newline
+                || self.current_token.value == Tokens::Comma
+            {
+                res.register_advancement();
+                self.advance();
+                continue;
+            }
+
+            let variant = self.current_token;
+            if let Tokens::Identifier(_) = variant.value {
+                res.register_advancement();
+                self.advance();
+            } else {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected variant or '}'",
+                ));
+            }
+
+            let mut payload = vec![];
+            if self.current_token.value == Tokens::LeftParenthesis {
+                loop {
+                    res.register_advancement();
+                    self.advance();
+
+                    let ty = res.register(self.type_expr());
+                    if res.error.is_some() {
+                        return res;
+                    }
+                    payload.push(ty.unwrap());
+
+                    if self.current_token.value != Tokens::Comma {
+                        break;
+                    }
+                }
+
+                if self.current_token.value != Tokens::RightParenthesis {
+                    return res.failure(Error::new(
+                        "Invalid Syntax",
+                        self.current_token.pos_start,
+                        self.current_token.pos_end,
+                        "Expected ',' or ')'",
+                    ));
+                }
+
+                res.register_advancement();
+                self.advance();
+            }
+
+            variants.push((variant, payload));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        res.success(Node::EnumDefNode { name, variants })
+    }
+}
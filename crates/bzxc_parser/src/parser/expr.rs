@@ -25,11 +25,16 @@ impl Parser {
 
         if self.current_token.value == Tokens::Keyword("val")
             || self.current_token.value == Tokens::Keyword("var")
+            || self.current_token.value == Tokens::Keyword("const")
         {
             let var_type: String = self.current_token.value.into_string();
             res.register_advancement();
             self.advance();
 
+            if self.current_token.value == Tokens::LeftParenthesis && var_type != "const" {
+                return self.tuple_assign(var_type == "var", res);
+            }
+
             if let Tokens::Identifier(_) = self.current_token.value {
             } else {
                 return res.failure(Error::new(
@@ -44,21 +49,40 @@ impl Parser {
             res.register_advancement();
             self.advance();
 
-            if self.current_token.value != Tokens::Equals {
-                return res.failure(Error::new(
-                    "Invalid Syntax",
-                    self.current_token.pos_start.clone(),
-                    self.current_token.pos_end.clone(),
-                    "Expected '='",
-                ));
+            let ty = res.register(self.type_annotation());
+            if res.error.is_some() {
+                return res;
             }
 
-            res.register_advancement();
-            self.advance();
+            // a declaration without a value starts with the zero value of its type
+            let expr = if ty.is_some() && self.current_token.value != Tokens::Equals {
+                ty.clone()
+            } else {
+                if self.current_token.value != Tokens::Equals {
+                    return res.failure(Error::new(
+                        "Invalid Syntax",
+                        self.current_token.pos_start.clone(),
+                        self.current_token.pos_end.clone(),
+                        "Expected '=' or ':'",
+                    ));
+                }
 
-            let expr = res.register(self.expr());
-            if res.error.is_some() {
-                return res;
+                res.register_advancement();
+                self.advance();
+
+                let expr = res.register(self.expr());
+                if res.error.is_some() {
+                    return res;
+                }
+                expr
+            };
+
+            if var_type == "const" {
+                return res.success(Node::ConstNode {
+                    name: var_name,
+                    ty: Box::new(ty),
+                    value: Box::new(expr.unwrap()),
+                });
             }
 
             let reassignable = if var_type == String::from("var") {
@@ -68,6 +92,7 @@ impl Parser {
             };
             return res.success(Node::VarAssignNode {
                 name: var_name.clone(),
+                ty: Box::new(ty),
                 value: Box::new(expr.unwrap()),
                 reassignable,
             });
@@ -106,4 +131,64 @@ impl Parser {
 
         res.success(left.unwrap())
     }
+
+    /*
+     * Parses the names and the value of `var (a, b) = value`
+     */
+    fn tuple_assign(&mut self, reassignable: bool, mut res: ParseResult) -> ParseResult {
+        let mut names = vec![];
+        loop {
+            res.register_advancement();
+            self.advance();
+
+            if let Tokens::Identifier(_) = self.current_token.value {
+            } else {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected Identifier",
+                ));
+            }
+            names.push(self.current_token);
+            res.register_advancement();
+            self.advance();
+
+            if self.current_token.value != Tokens::Comma {
+                break;
+            }
+        }
+
+        if self.current_token.value != Tokens::RightParenthesis {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected ')' or ','",
+            ));
+        }
+        res.register_advancement();
+        self.advance();
+
+        if self.current_token.value != Tokens::Equals {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected '='",
+            ));
+        }
+        res.register_advancement();
+        self.advance();
+
+        let value = res.register(self.expr());
+        if res.error.is_some() {
+            return res;
+        }
+        res.success(Node::TupleAssignNode {
+            names,
+            value: Box::new(value.unwrap()),
+            reassignable,
+        })
+    }
 }
@@ -25,7 +25,7 @@ impl Parser {
 
         if self.current_token.value != Tokens::Keyword("extern") {
             return res.failure(Error::new(
-                pos_start.file_name,
+                "Invalid Syntax",
                 pos_start,
                 self.current_token.pos_end,
                 "Expected extern keyword",
@@ -35,7 +35,7 @@ impl Parser {
         self.advance();
         res.register_advancement();
 
-        let expr = res.register(self.expr());
+        let expr = res.register(self.extern_type());
         if res.error.is_some() {
             return res;
         }
@@ -46,7 +46,7 @@ impl Parser {
             res.register_advancement();
         } else {
             return res.failure(Error::new(
-                pos_start.file_name,
+                "Invalid Syntax",
                 pos_start,
                 self.current_token.pos_end,
                 "Expected identifier",
@@ -55,7 +55,7 @@ impl Parser {
 
         if self.current_token.value != Tokens::LeftParenthesis {
             return res.failure(Error::new(
-                pos_start.file_name,
+                "Invalid Syntax",
                 pos_start,
                 self.current_token.pos_end,
                 "Expected (",
@@ -74,7 +74,7 @@ impl Parser {
 
                 if self.current_token.value != Tokens::Dot {
                     return res.failure(Error::new(
-                        pos_start.file_name,
+                        "Invalid Syntax",
                         pos_start,
                         self.current_token.pos_end,
                         "Expected .",
@@ -86,7 +86,7 @@ impl Parser {
 
                 if self.current_token.value != Tokens::Dot {
                     return res.failure(Error::new(
-                        pos_start.file_name,
+                        "Invalid Syntax",
                         pos_start,
                         self.current_token.pos_end,
                         "Expected .",
@@ -100,7 +100,7 @@ impl Parser {
                 break;
             }
 
-            let arg = res.register(self.expr());
+            let arg = res.register(self.extern_type());
             if res.error.is_some() {
                 return res;
             }
@@ -116,7 +116,7 @@ impl Parser {
 
         if self.current_token.value != Tokens::RightParenthesis {
             return res.failure(Error::new(
-                pos_start.file_name,
+                "Invalid Syntax",
                 pos_start,
                 self.current_token.pos_end,
                 "Expected )",
@@ -133,4 +133,26 @@ impl Parser {
             var_args,
         })
     }
+
+    /*
+     * Parses the type of a parameter or the return value of an extern function,
+     * followed by `?` when it may be null
+     */
+    fn extern_type(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+        let mut ty = res.register(self.expr());
+        if res.error.is_some() {
+            return res;
+        }
+
+        if self.current_token.value == Tokens::QuestionMark {
+            res.register_advancement();
+            self.advance();
+            ty = Some(Node::TypeNullable {
+                element: Box::new(ty.unwrap()),
+            });
+        }
+
+        res.success(ty.unwrap())
+    }
 }
@@ -33,15 +33,74 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
+        let (fun_name, type_params, args, return_type) = match self.fun_signature(&mut res) {
+            Ok(signature) => signature,
+            Err(error) => return res.failure(error),
+        };
+
+        if self.current_token.value != Tokens::LeftCurlyBraces {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start.clone(),
+                self.current_token.pos_end.clone(),
+                "Expected '{'",
+            ));
+        }
+        self.advance();
+        res.register_advancement();
+
+        let body_node = res.register(self.statements());
+        if res.error.is_some() {
+            return res;
+        }
+
+        if self.current_token.value != Tokens::RightCurlyBraces {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start.clone(),
+                self.current_token.pos_end.clone(),
+                "Expected '}'",
+            ));
+        }
+        self.advance();
+        res.register_advancement();
+
+        res.success(Node::FunDef {
+            name: fun_name,
+            type_params,
+            args,
+            return_type: Box::new(return_type),
+            body_node: Box::new(body_node.clone().unwrap()),
+        })
+    }
+
+    /*
+     * Parses the name, type parameters, parameters and return type of a function
+     */
+    pub(crate) fn fun_signature(
+        &mut self,
+        res: &mut ParseResult,
+    ) -> Result<
+        (
+            Option<Token>,
+            Vec<Token>,
+            Vec<(Token, Option<Node>)>,
+            Option<Node>,
+        ),
+        Error,
+    > {
         let mut fun_name: Option<Token> = None;
+        let mut type_params = vec![];
         if let Tokens::Identifier(_) = self.current_token.value {
             fun_name = Some(self.current_token.clone());
 
             res.register_advancement();
             self.advance();
 
+            type_params = self.type_params(res)?;
+
             if self.current_token.value != Tokens::LeftParenthesis {
-                return res.failure(Error::new(
+                return Err(Error::new(
                     "Invalid Syntax",
                     self.current_token.pos_start.clone(),
                     self.current_token.pos_end.clone(),
@@ -49,7 +108,7 @@ impl Parser {
                 ));
             }
         } else if self.current_token.value != Tokens::LeftParenthesis {
-            return res.failure(Error::new(
+            return Err(Error::new(
                 "Invalid Syntax",
                 self.current_token.pos_start.clone(),
                 self.current_token.pos_end.clone(),
@@ -60,25 +119,35 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
-        let mut args_name_tokens: Vec<Token> = vec![];
+        let mut args: Vec<(Token, Option<Node>)> = vec![];
         if let Tokens::Identifier(_) = self.current_token.value {
             let name = self.current_token.clone();
-            args_name_tokens.push(name);
 
             res.register_advancement();
             self.advance();
 
+            let ty = res.register(self.type_annotation());
+            if let Some(error) = res.error.clone() {
+                return Err(error);
+            }
+            args.push((name, ty));
+
             while self.current_token.value == Tokens::Comma {
                 res.register_advancement();
                 self.advance();
 
                 if let Tokens::Identifier(_) = self.current_token.value {
                     let new_arg_token = self.current_token.clone();
-                    args_name_tokens.push(new_arg_token);
                     res.register_advancement();
                     self.advance();
+
+                    let ty = res.register(self.type_annotation());
+                    if let Some(error) = res.error.clone() {
+                        return Err(error);
+                    }
+                    args.push((new_arg_token, ty));
                 } else {
-                    return res.failure(Error::new(
+                    return Err(Error::new(
                         "Invalid Syntax",
                         self.current_token.pos_start.clone(),
                         self.current_token.pos_end.clone(),
@@ -88,7 +157,7 @@ impl Parser {
             }
 
             if self.current_token.value != Tokens::RightParenthesis {
-                return res.failure(Error::new(
+                return Err(Error::new(
                     "Invalid Syntax",
                     self.current_token.pos_start.clone(),
                     self.current_token.pos_end.clone(),
@@ -96,7 +165,7 @@ impl Parser {
                 ));
             }
         } else if self.current_token.value != Tokens::RightParenthesis {
-            return res.failure(Error::new(
+            return Err(Error::new(
                 "Invalid Syntax",
                 self.current_token.pos_start.clone(),
                 self.current_token.pos_end.clone(),
@@ -107,37 +176,10 @@ impl Parser {
         res.register_advancement();
         self.advance();
 
-        if self.current_token.value != Tokens::LeftCurlyBraces {
-            return res.failure(Error::new(
-                "Invalid Syntax",
-                self.current_token.pos_start.clone(),
-                self.current_token.pos_end.clone(),
-                "Expected '{'",
-            ));
-        }
-        self.advance();
-        res.register_advancement();
-
-        let body_node = res.register(self.statements());
-        if res.error.is_some() {
-            return res;
-        }
-
-        if self.current_token.value != Tokens::RightCurlyBraces {
-            return res.failure(Error::new(
-                "Invalid Syntax",
-                self.current_token.pos_start.clone(),
-                self.current_token.pos_end.clone(),
-                "Expected '}'",
-            ));
+        let return_type = res.register(self.type_annotation());
+        if let Some(error) = res.error.clone() {
+            return Err(error);
         }
-        self.advance();
-        res.register_advancement();
-
-        res.success(Node::FunDef {
-            name: fun_name,
-            body_node: Box::new(body_node.clone().unwrap()),
-            arg_tokens: args_name_tokens,
-        })
+        Ok((fun_name, type_params, args, return_type))
     }
 }
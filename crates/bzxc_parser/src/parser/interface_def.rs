@@ -0,0 +1,104 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Error, Node, Tokens};
+
+impl Parser {
+    /*
+     * Parses an interface, the signatures of the methods its classes must have
+     */
+    pub(crate) fn interface_def(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        if self.current_token.value != Tokens::Keyword("interface") {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected 'interface'",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let name = self.current_token;
+        if let Tokens::Identifier(_) = name.value {
+            res.register_advancement();
+            self.advance();
+        } else {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected identifier",
+            ));
+        }
+
+        if self.current_token.value != Tokens::LeftCurlyBraces {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected '{'",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let mut methods = vec![];
+        while self.current_token.value != Tokens::RightCurlyBraces {
+            if self.current_token.value == Tokens::Newline {
+                res.register_advancement();
+                self.advance();
+                continue;
+            }
+
+            if self.current_token.value != Tokens::Keyword("fun") {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected 'fun' or '}'",
+                ));
+            }
+
+            res.register_advancement();
+            self.advance();
+
+            let pos = (self.current_token.pos_start, self.current_token.pos_end);
+            match self.fun_signature(&mut res) {
+                Ok((Some(name), type_params, args, return_type)) if type_params.is_empty() => {
+                    methods.push((name, args, return_type));
+                }
+                Ok(_) => {
+                    return res.failure(Error::new(
+                        "Invalid Syntax",
+                        pos.0,
+                        pos.1,
+                        "Expected a method name without type parameters",
+                    ))
+                }
+                Err(error) => return res.failure(error),
+            }
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        res.success(Node::InterfaceDefNode { name, methods })
+    }
+}
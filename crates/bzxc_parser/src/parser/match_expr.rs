@@ -0,0 +1,205 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Error, Node, Tokens};
+
+impl Parser {
+    /*
+     * Parses a match on the variant of an enum, every arm is a pattern
+     * followed by `=>` and a statement or a block
+     */
+    pub(crate) fn match_expr(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        if self.current_token.value != Tokens::Keyword("match") {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected 'match'",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let value = res.register(self.expr());
+        if res.error.is_some() {
+            return res;
+        }
+
+        if self.current_token.value != Tokens::LeftCurlyBraces {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected '{'",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let mut arms = vec![];
+        while self.current_token.value != Tokens::RightCurlyBraces {
+            if self.current_token.value == Tokens::Newline
+                || self.current_token.value == Tokens::Comma
+            {
+                res.register_advancement();
+                self.advance();
+                continue;
+            }
+
+            let pattern = res.register(self.pattern());
+            if res.error.is_some() {
+                return res;
+            }
+
+            if self.current_token.value != Tokens::Arrow {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected '=>'",
+                ));
+            }
+
+            res.register_advancement();
+            self.advance();
+
+            let body = if self.current_token.value == Tokens::LeftCurlyBraces {
+                res.register_advancement();
+                self.advance();
+
+                let body = res.register(self.statements());
+                if res.error.is_some() {
+                    return res;
+                }
+
+                if self.current_token.value != Tokens::RightCurlyBraces {
+                    return res.failure(Error::new(
+                        "Invalid Syntax",
+                        self.current_token.pos_start,
+                        self.current_token.pos_end,
+                        "Expected '}'",
+                    ));
+                }
+
+                res.register_advancement();
+                self.advance();
+                body
+            } else {
+                let body = res.register(self.statement());
+                if res.error.is_some() {
+                    return res;
+                }
+                body
+            };
+
+            arms.push((pattern.unwrap(), body.unwrap()));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        res.success(Node::MatchNode {
+            value: Box::new(value.unwrap()),
+            arms,
+        })
+    }
+
+    /*
+     * Parses a pattern like `_`, `Red`, `Color.Red` or `Ok(value)`
+     */
+    fn pattern(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        let mut enum_name = None;
+        let mut variant = self.current_token;
+        if !matches!(variant.value, Tokens::Identifier(_)) {
+            return res.failure(Error::new(
+                "Invalid Syntax",
+                variant.pos_start,
+                variant.pos_end,
+                "Expected pattern",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        if self.current_token.value == Tokens::Dot {
+            res.register_advancement();
+            self.advance();
+
+            if !matches!(self.current_token.value, Tokens::Identifier(_)) {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected variant",
+                ));
+            }
+
+            enum_name = Some(variant);
+            variant = self.current_token;
+            res.register_advancement();
+            self.advance();
+        }
+
+        let mut bindings = vec![];
+        if self.current_token.value == Tokens::LeftParenthesis {
+            loop {
+                res.register_advancement();
+                self.advance();
+
+                if !matches!(self.current_token.value, Tokens::Identifier(_)) {
+                    return res.failure(Error::new(
+                        "Invalid Syntax",
+                        self.current_token.pos_start,
+                        self.current_token.pos_end,
+                        "Expected identifier",
+                    ));
+                }
+                bindings.push(self.current_token);
+
+                res.register_advancement();
+                self.advance();
+
+                if self.current_token.value != Tokens::Comma {
+                    break;
+                }
+            }
+
+            if self.current_token.value != Tokens::RightParenthesis {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected ',' or ')'",
+                ));
+            }
+
+            res.register_advancement();
+            self.advance();
+        }
+
+        res.success(Node::VariantPattern {
+            enum_name,
+            variant,
+            bindings,
+        })
+    }
+}
@@ -21,9 +21,12 @@ mod c_object;
 mod c_to_bzx_obj;
 mod call;
 mod call_access_expr;
+mod cast_expr;
 mod class_def;
 mod class_init;
 mod comp_expr;
+mod elvis_expr;
+mod enum_def;
 mod expr;
 mod extern_def;
 mod factor;
@@ -31,12 +34,15 @@ mod for_expr;
 mod fun_def;
 mod if_expr;
 mod index_expr;
+mod interface_def;
+mod match_expr;
 mod obj_expr;
 mod obj_prop_expr;
 mod power;
 mod statement;
 mod statements;
 mod term;
+mod type_expr;
 mod var_expr;
 mod while_expr;
 
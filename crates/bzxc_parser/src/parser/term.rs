@@ -21,7 +21,7 @@ impl Parser {
      */
     pub(crate) fn term(&mut self) -> ParseResult {
         let mut res = ParseResult::new();
-        let mut left = res.register(self.factor());
+        let mut left = res.register(self.cast_expr());
         if res.error.is_some() {
             return res;
         }
@@ -32,7 +32,7 @@ impl Parser {
             res.register_advancement();
             self.advance();
 
-            let right = res.register(self.factor());
+            let right = res.register(self.cast_expr());
             if res.error.is_some() {
                 return res;
             }
@@ -0,0 +1,258 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+
+use super::Parser;
+use crate::parse_result::ParseResult;
+use bzxc_shared::{Error, Node, Token, Tokens, Type};
+
+impl Parser {
+    /*
+     * Parses a type annotation like `int`, `[T]`, `[int; 10]`, `(int, T)`,
+     * `Stack<int>` or `string?`
+     */
+    pub(crate) fn type_expr(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+        let mut ty = res.register(self.type_atom());
+        if res.error.is_some() {
+            return res;
+        }
+
+        while self.current_token.value == Tokens::QuestionMark {
+            res.register_advancement();
+            self.advance();
+            ty = Some(Node::TypeNullable {
+                element: Box::new(ty.unwrap()),
+            });
+        }
+
+        res.success(ty.unwrap())
+    }
+
+    /*
+     * Parses a type without the `?` making it nullable
+     */
+    fn type_atom(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+        let token = self.current_token.clone();
+
+        match token.value {
+            Tokens::Keyword(keyword) if Type::keyword(keyword).is_some() => {
+                res.register_advancement();
+                self.advance();
+                return res.success(Node::TypeKeyword { token });
+            }
+            _ => (),
+        }
+
+        if token.value == Tokens::LeftSquareBraces {
+            res.register_advancement();
+            self.advance();
+
+            let element = res.register(self.type_expr());
+            if res.error.is_some() {
+                return res;
+            }
+
+            // `;` is lexed as the end of a line
+            let mut len = None;
+            if self.current_token.value == Tokens::Newline {
+                res.register_advancement();
+                self.advance();
+
+                // the length is a number or the name of a `const`
+                len = match self.current_token.value {
+                    Tokens::Int(_) | Tokens::Identifier(_) => Some(self.current_token.clone()),
+                    _ => {
+                        return res.failure(Error::new(
+                            "Invalid Syntax",
+                            self.current_token.pos_start,
+                            self.current_token.pos_end,
+                            "Expected the length of the array",
+                        ))
+                    }
+                };
+                res.register_advancement();
+                self.advance();
+            }
+
+            if self.current_token.value != Tokens::RightSquareBraces {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected ']' or ';'",
+                ));
+            }
+
+            res.register_advancement();
+            self.advance();
+            return res.success(Node::TypeArray {
+                element: Box::new(element.unwrap()),
+                len,
+            });
+        }
+
+        if token.value == Tokens::LeftParenthesis {
+            let mut elements = vec![];
+            loop {
+                res.register_advancement();
+                self.advance();
+
+                let element = res.register(self.type_expr());
+                if res.error.is_some() {
+                    return res;
+                }
+                elements.push(element.unwrap());
+
+                if self.current_token.value != Tokens::Comma {
+                    break;
+                }
+            }
+
+            if self.current_token.value != Tokens::RightParenthesis {
+                return res.failure(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected ')' or ','",
+                ));
+            }
+
+            res.register_advancement();
+            self.advance();
+            return res.success(Node::TypeTuple { elements });
+        }
+
+        if let Tokens::Identifier(_) = token.value {
+            res.register_advancement();
+            self.advance();
+
+            return match self.type_args(&mut res) {
+                Ok(args) => res.success(Node::TypeName { name: token, args }),
+                Err(error) => res.failure(error),
+            };
+        }
+
+        res.failure(Error::new(
+            "Invalid Syntax",
+            token.pos_start,
+            token.pos_end,
+            "Expected type",
+        ))
+    }
+
+    /*
+     * Parses an optional list of type arguments like `<int, T>`
+     */
+    pub(crate) fn type_args(&mut self, res: &mut ParseResult) -> Result<Vec<Node>, Error> {
+        let mut args = vec![];
+
+        if self.current_token.value != Tokens::LessThan {
+            return Ok(args);
+        }
+
+        loop {
+            res.register_advancement();
+            self.advance();
+
+            let arg = res.register(self.type_expr());
+            if let Some(error) = res.error.clone() {
+                return Err(error);
+            }
+            args.push(arg.unwrap());
+
+            if self.current_token.value != Tokens::Comma {
+                break;
+            }
+        }
+
+        if self.current_token.value != Tokens::GreaterThan {
+            return Err(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected '>' or ','",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+        Ok(args)
+    }
+
+    /*
+     * Parses an optional list of type parameters like `<K, V>`
+     */
+    pub(crate) fn type_params(&mut self, res: &mut ParseResult) -> Result<Vec<Token>, Error> {
+        let mut params = vec![];
+
+        if self.current_token.value != Tokens::LessThan {
+            return Ok(params);
+        }
+
+        loop {
+            res.register_advancement();
+            self.advance();
+
+            if let Tokens::Identifier(_) = self.current_token.value {
+                params.push(self.current_token);
+                res.register_advancement();
+                self.advance();
+            } else {
+                return Err(Error::new(
+                    "Invalid Syntax",
+                    self.current_token.pos_start,
+                    self.current_token.pos_end,
+                    "Expected identifier",
+                ));
+            }
+
+            if self.current_token.value != Tokens::Comma {
+                break;
+            }
+        }
+
+        if self.current_token.value != Tokens::GreaterThan {
+            return Err(Error::new(
+                "Invalid Syntax",
+                self.current_token.pos_start,
+                self.current_token.pos_end,
+                "Expected '>' or ','",
+            ));
+        }
+
+        res.register_advancement();
+        self.advance();
+        Ok(params)
+    }
+
+    /*
+     * Parses an optional `: type` annotation
+     */
+    pub(crate) fn type_annotation(&mut self) -> ParseResult {
+        let mut res = ParseResult::new();
+
+        if self.current_token.value != Tokens::Colon {
+            return res;
+        }
+
+        res.register_advancement();
+        self.advance();
+
+        let ty = res.register(self.type_expr());
+        if res.error.is_some() {
+            return res;
+        }
+        res.success(ty.unwrap())
+    }
+}
@@ -16,6 +16,7 @@ use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::hash::Hash;
 
 use codespan_reporting::diagnostic::{Diagnostic, Label};
@@ -23,19 +24,28 @@ use codespan_reporting::files::SimpleFiles;
 use codespan_reporting::term;
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use llvm_sys::core::{
-    LLVMArrayType, LLVMFloatTypeInContext, LLVMFunctionType, LLVMInt128TypeInContext,
-    LLVMInt1TypeInContext, LLVMInt8TypeInContext, LLVMPointerType, LLVMStructTypeInContext,
+    LLVMArrayType, LLVMDoubleTypeInContext, LLVMFloatTypeInContext, LLVMFunctionType,
+    LLVMGetElementType, LLVMGetTypeKind, LLVMInt128TypeInContext, LLVMInt16TypeInContext,
+    LLVMInt1TypeInContext, LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMInt8TypeInContext,
+    LLVMPointerType, LLVMStructCreateNamed, LLVMStructSetBody, LLVMStructTypeInContext,
 };
 use llvm_sys::prelude::{LLVMContextRef, LLVMTypeRef};
+use llvm_sys::LLVMTypeKind;
+use serde::{Serialize, Serializer};
+
+mod session;
+mod visit;
+pub use session::{BindingId, Session, Symbol};
+pub use visit::{fold_children, walk, walk_mut, Fold, Visitor, VisitorMut, Walk};
 
 /*
 * Enum of all the Token Types
 */
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
 pub enum Tokens {
     Int(i128),
     Float(f64),
-    String(&'static str),
+    String(Symbol),
     Boolean(bool),
     Char(char),
     Colon,
@@ -54,7 +64,7 @@ pub enum Tokens {
     RightSquareBraces,
     Power,
     Keyword(&'static str),
-    Identifier(&'static str),
+    Identifier(Symbol),
     Equals,
     PlusEquals,
     MinusEquals,
@@ -68,15 +78,15 @@ pub enum Tokens {
     LessThanEquals,
     GreaterThan,
     GreaterThanEquals,
+    QuestionMark,
+    SafeDot,
+    Elvis,
+    Arrow,
     Newline,
     EOF,
     Unknown,
 }
 
-pub fn to_static_str(string: String) -> &'static str {
-    Box::leak(string.to_owned().into_boxed_str())
-}
-
 impl Tokens {
     /*
      * Convert a Token value to int if possible
@@ -106,10 +116,10 @@ impl Tokens {
      * Convert a Token value to string if possible
      */
     pub fn into_string(self) -> String {
-        if let Tokens::String(i) | Tokens::Identifier(i) | Tokens::Keyword(i) = self {
-            i.to_string()
-        } else {
-            panic!()
+        match self {
+            Tokens::String(i) | Tokens::Identifier(i) => i.as_string(),
+            Tokens::Keyword(i) => i.to_string(),
+            _ => panic!(),
         }
     }
 
@@ -139,12 +149,15 @@ impl Tokens {
 /*
 * Custom Error struct for capturing errors
 */
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Error {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
     pub name: &'static str,
     pub pos_start: Position,
     pub pos_end: Position,
-    pub description: &'static str,
+    pub description: String,
+    pub labels: Vec<(Position, Position, String)>,
 }
 
 impl Error {
@@ -155,32 +168,87 @@ impl Error {
         name: &'static str,
         pos_start: Position,
         pos_end: Position,
-        description: &'static str,
+        description: impl Into<String>,
     ) -> Error {
         Error {
+            severity: Severity::Error,
+            code: None,
             name,
             pos_start,
             pos_end,
-            description,
+            description: description.into(),
+            labels: vec![],
         }
     }
 
+    /*
+     * Creates a diagnostic which doesn't stop the compilation
+     */
+    pub fn warning(
+        name: &'static str,
+        pos_start: Position,
+        pos_end: Position,
+        description: impl Into<String>,
+    ) -> Error {
+        Error {
+            severity: Severity::Warning,
+            ..Error::new(name, pos_start, pos_end, description)
+        }
+    }
+
+    /*
+     * Names the check which raised the diagnostic, like the lint of a warning
+     */
+    pub fn with_code(mut self, code: &'static str) -> Error {
+        self.code = Some(code);
+        self
+    }
+
+    /*
+     * Adds a secondary label pointing at something the error is about, like a definition
+     */
+    pub fn with_label(
+        mut self,
+        pos_start: Position,
+        pos_end: Position,
+        message: impl Into<String>,
+    ) -> Error {
+        self.labels.push((pos_start, pos_end, message.into()));
+        self
+    }
+
     /*
      * Prettifies the Error
      */
     pub fn prettify(&self) {
         let mut files = SimpleFiles::new();
-        let file_id = files.add(self.pos_start.file_name, self.pos_start.file_content);
+        let (name, content) = Session::file(self.pos_start.file);
+        let file_id = files.add(name, content);
 
-        let diagnostic = Diagnostic::error()
+        let mut labels = vec![Label::primary(
+            file_id,
+            self.pos_start.index..self.pos_end.index.max(self.pos_start.index),
+        )
+        .with_message(&self.description)];
+        for (pos_start, pos_end, message) in &self.labels {
+            labels.push(
+                Label::secondary(file_id, pos_start.index..pos_end.index.max(pos_start.index))
+                    .with_message(message),
+            );
+        }
+
+        let severity = match self.severity {
+            Severity::Error => codespan_reporting::diagnostic::Severity::Error,
+            Severity::Warning => codespan_reporting::diagnostic::Severity::Warning,
+        };
+        let mut diagnostic = Diagnostic::new(severity)
             .with_message(self.name)
-            .with_labels(vec![Label::primary(
-                file_id,
-                (self.pos_start.index as usize)..(self.pos_end.index as usize),
-            )
-            .with_message(self.description)]);
+            .with_labels(labels);
+        if let Some(code) = self.code {
+            diagnostic = diagnostic.with_code(code);
+        }
 
-        let writer = StandardStream::stderr(ColorChoice::Always);
+        let writer = StandardStream::stderr(ColorChoice::Auto);
         let config = codespan_reporting::term::Config::default();
 
         term::emit(&mut writer.lock(), &config, &files, &diagnostic);
@@ -188,34 +256,37 @@ impl Error {
 }
 
 /*
-* Position struct for error pretty-printing
+* Whether a diagnostic stops the compilation
 */
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/*
+* Position struct for error pretty-printing
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
     pub index: usize,
-    pub file_name: &'static str,
-    pub file_content: &'static str,
+    pub file: usize,
 }
 
 impl Position {
     /* Just for Testing */
     pub fn proto() -> Self {
         Position {
-            file_content: "",
-            file_name: "",
+            file: usize::MAX,
             index: 0,
         }
     }
 
     /*
-     * Creates a new Position Struct
+     * Creates a new Position Struct, `file` is the index of the file in the session
      */
-    pub fn new(index: usize, file_name: &'static str, file_content: &'static str) -> Position {
-        Position {
-            index,
-            file_name,
-            file_content,
-        }
+    pub fn new(index: usize, file: usize) -> Position {
+        Position { index, file }
     }
 
     /*
@@ -225,12 +296,32 @@ impl Position {
         self.index += 1;
         self.clone()
     }
+
+    /*
+     * `file:line:column` of the position, lines and columns are counted from 1
+     */
+    pub fn location(&self) -> String {
+        let (name, content) = Session::file(self.file);
+        let before = &content[..self.index.min(content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        format!("{}:{}:{}", name, line, column)
+    }
+}
+
+/*
+* Positions are serialized as their byte offset, the file is the same for the whole tree
+*/
+impl Serialize for Position {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.index as u64)
+    }
 }
 
 /*
 * Token struct for tokens in a program
 */
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
 pub struct Token {
     pub value: Tokens,
     pub pos_start: Position,
@@ -253,7 +344,8 @@ impl Token {
 /*
 * Enum Node returned by Parser
 */
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(clippy::type_complexity)]
 pub enum Node {
     WhileNode {
         condition_node: Box<Node>,
@@ -266,9 +358,15 @@ pub enum Node {
     },
     VarAssignNode {
         name: Token,
+        ty: Box<Option<Node>>,
         value: Box<Node>,
         reassignable: bool,
     },
+    ConstNode {
+        name: Token,
+        ty: Box<Option<Node>>,
+        value: Box<Node>,
+    },
     VarAccessNode {
         token: Token,
     },
@@ -288,8 +386,10 @@ pub enum Node {
     },
     FunDef {
         name: Option<Token>,
+        type_params: Vec<Token>,
+        args: Vec<(Token, Option<Node>)>,
+        return_type: Box<Option<Node>>,
         body_node: Box<Node>,
-        arg_tokens: Vec<Token>,
     },
     ForNode {
         var_name_token: Token,
@@ -301,8 +401,12 @@ pub enum Node {
     CharNode {
         token: Token,
     },
+    /*
+     * `type_args` are written out like in `max<float>(a, b)`
+     */
     CallNode {
         node_to_call: Box<Node>,
+        type_args: Vec<Node>,
         args: Vec<Node>,
     },
     BooleanNode {
@@ -315,6 +419,7 @@ pub enum Node {
     },
     ArrayNode {
         element_nodes: Vec<Node>,
+        token: Token,
     },
     ArrayAcess {
         array: Box<Node>,
@@ -344,14 +449,22 @@ pub enum Node {
         args: Vec<Node>,
     },
     ClassDefNode {
-        methods: Vec<(Token, Vec<Token>, Node)>,
+        methods: Vec<(Token, Node)>,
         properties: Vec<(Token, Node)>,
-        constructor: (Vec<Token>, Box<Node>),
+        constructor: Box<Option<Node>>,
         static_members: Vec<(Token, Node)>,
         name: Token,
+        type_params: Vec<Token>,
+        base: Box<Option<Node>>,
+        interfaces: Vec<Node>,
+    },
+    InterfaceDefNode {
+        name: Token,
+        methods: Vec<(Token, Vec<(Token, Option<Node>)>, Option<Node>)>,
     },
     ClassInitNode {
         name: Token,
+        type_args: Vec<Node>,
         constructor_params: Vec<Node>,
     },
     ExternNode {
@@ -363,6 +476,14 @@ pub enum Node {
     TypeKeyword {
         token: Token,
     },
+    TypeName {
+        name: Token,
+        args: Vec<Node>,
+    },
+    TypeArray {
+        element: Box<Node>,
+        len: Option<Token>,
+    },
     CObject {
         object: Box<Node>,
     },
@@ -370,6 +491,65 @@ pub enum Node {
         object: Box<Node>,
         bzx_object: Box<Node>,
     },
+    CastNode {
+        node: Box<Node>,
+        ty: Box<Node>,
+    },
+    SuperNode {
+        token: Token,
+    },
+    NullNode {
+        token: Token,
+    },
+    ElvisNode {
+        value: Box<Node>,
+        default: Box<Node>,
+    },
+    /*
+     * `access` made on `object` after a `?.`, where the object is the variable `name`
+     */
+    SafeAccessNode {
+        object: Box<Node>,
+        name: Token,
+        access: Box<Node>,
+    },
+    TypeNullable {
+        element: Box<Node>,
+    },
+    EnumDefNode {
+        name: Token,
+        variants: Vec<(Token, Vec<Node>)>,
+    },
+    MatchNode {
+        value: Box<Node>,
+        arms: Vec<(Node, Node)>,
+    },
+    /*
+     * `variant` of an enum, with its payload bound to `bindings`, `_` matches anything
+     */
+    VariantPattern {
+        enum_name: Option<Token>,
+        variant: Token,
+        bindings: Vec<Token>,
+    },
+    TupleNode {
+        elements: Vec<Node>,
+    },
+    TupleAccess {
+        tuple: Box<Node>,
+        index: Token,
+    },
+    /*
+     * `var (a, b) = value`, the elements of the tuple are bound to `names`, `_` skips one
+     */
+    TupleAssignNode {
+        names: Vec<Token>,
+        value: Box<Node>,
+        reassignable: bool,
+    },
+    TypeTuple {
+        elements: Vec<Node>,
+    },
 }
 
 impl Node {
@@ -384,11 +564,9 @@ impl Node {
                 typee: _,
                 value,
             } => (name.pos_start, value.get_pos().1),
-            Node::VarAssignNode {
-                name,
-                value,
-                reassignable: _,
-            } => (name.pos_start, value.get_pos().1),
+            Node::VarAssignNode { name, value, .. } | Node::ConstNode { name, value, .. } => {
+                (name.pos_start, value.get_pos().1)
+            }
             Node::VarAccessNode { token } => (token.pos_start, token.pos_end),
             Node::UnaryNode { node, op_token } => (node.get_pos().0, op_token.pos_end),
             Node::StringNode { token } => (token.pos_start, token.pos_end),
@@ -404,12 +582,13 @@ impl Node {
             Node::FunDef {
                 name,
                 body_node,
-                arg_tokens,
+                args,
+                ..
             } => (
                 if name.is_some() {
                     name.clone().unwrap().pos_start
-                } else if !arg_tokens.is_empty() {
-                    arg_tokens.first().unwrap().pos_start
+                } else if !args.is_empty() {
+                    args.first().unwrap().0.pos_start
                 } else {
                     body_node.get_pos().0
                 },
@@ -423,7 +602,9 @@ impl Node {
                 step_value_node: _,
             } => (var_name_token.pos_start, body_node.get_pos().1),
             Node::CharNode { token } => (token.pos_start, token.pos_end),
-            Node::CallNode { node_to_call, args } => (
+            Node::CallNode {
+                node_to_call, args, ..
+            } => (
                 node_to_call.get_pos().0,
                 if !args.is_empty() {
                     args.last().unwrap().get_pos().1
@@ -437,21 +618,24 @@ impl Node {
                 right,
                 op_token: _,
             } => (left.get_pos().0, right.get_pos().1),
-            Node::ArrayNode { element_nodes } => {
+            Node::ArrayNode {
+                element_nodes,
+                token,
+            } => {
                 if !element_nodes.is_empty() {
                     (
                         element_nodes.first().unwrap().get_pos().0,
                         element_nodes.last().unwrap().get_pos().1,
                     )
                 } else {
-                    (Position::proto(), Position::proto())
+                    (token.pos_start, token.pos_end)
                 }
             }
             Node::ArrayAcess { array, index } => (array.get_pos().0, index.get_pos().1),
-            Node::Statements { statements } => (
-                statements.first().unwrap().get_pos().0,
-                statements.last().unwrap().get_pos().1,
-            ),
+            Node::Statements { statements } => match (statements.first(), statements.last()) {
+                (Some(first), Some(last)) => (first.get_pos().0, last.get_pos().1),
+                _ => (Position::proto(), Position::proto()),
+            },
             Node::ReturnNode { value } => {
                 if let Some(val) = *value.clone() {
                     (val.get_pos().0, val.get_pos().1)
@@ -459,10 +643,10 @@ impl Node {
                     (Position::proto(), Position::proto())
                 }
             }
-            Node::ObjectDefNode { properties } => (
-                properties.first().unwrap().0.pos_start,
-                properties.last().unwrap().1.get_pos().1,
-            ),
+            Node::ObjectDefNode { properties } => match (properties.first(), properties.last()) {
+                (Some(first), Some(last)) => (first.0.pos_start, last.1.get_pos().1),
+                _ => (Position::proto(), Position::proto()),
+            },
             Node::CObject { object } => object.get_pos(),
             Node::CToBzxObject { bzx_object, object } => {
                 (bzx_object.get_pos().0, object.get_pos().1)
@@ -485,10 +669,13 @@ impl Node {
                     property.pos_end
                 },
             ),
-            Node::ClassDefNode { name, .. } => (name.pos_start, name.pos_end),
+            Node::ClassDefNode { name, .. }
+            | Node::InterfaceDefNode { name, .. }
+            | Node::EnumDefNode { name, .. } => (name.pos_start, name.pos_end),
             Node::ClassInitNode {
                 name,
                 constructor_params,
+                ..
             } => (
                 name.pos_start,
                 if !constructor_params.is_empty() {
@@ -499,6 +686,44 @@ impl Node {
             ),
             Node::ExternNode { name, .. } => (name.pos_start, name.pos_end),
             Node::TypeKeyword { token } => (token.pos_start, token.pos_end),
+            Node::TypeName { name, args } => (
+                name.pos_start,
+                match args.last() {
+                    Some(arg) => arg.get_pos().1,
+                    None => name.pos_end,
+                },
+            ),
+            Node::TypeArray { element, .. } => element.get_pos(),
+            Node::CastNode { node, ty } => (node.get_pos().0, ty.get_pos().1),
+            Node::SuperNode { token } | Node::NullNode { token } => {
+                (token.pos_start, token.pos_end)
+            }
+            Node::ElvisNode { value, default } => (value.get_pos().0, default.get_pos().1),
+            Node::SafeAccessNode { object, access, .. } => (object.get_pos().0, access.get_pos().1),
+            Node::TypeNullable { element } => element.get_pos(),
+            Node::MatchNode { value, arms } => (
+                value.get_pos().0,
+                match arms.last() {
+                    Some((_, body)) => body.get_pos().1,
+                    None => value.get_pos().1,
+                },
+            ),
+            Node::VariantPattern {
+                enum_name,
+                variant,
+                bindings,
+            } => (
+                enum_name.unwrap_or(*variant).pos_start,
+                bindings.last().unwrap_or(variant).pos_end,
+            ),
+            Node::TupleNode { elements } | Node::TypeTuple { elements } => (
+                elements.first().unwrap().get_pos().0,
+                elements.last().unwrap().get_pos().1,
+            ),
+            Node::TupleAccess { tuple, index } => (tuple.get_pos().0, index.pos_end),
+            Node::TupleAssignNode { names, value, .. } => {
+                (names.first().unwrap().pos_start, value.get_pos().1)
+            }
         }
     }
 }
@@ -516,6 +741,7 @@ pub fn to_c_str(mut s: &str) -> Cow<CStr> {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::type_complexity)]
 pub enum LLVMNode {
     Statements(Vec<Self>),
 
@@ -544,26 +770,36 @@ pub enum LLVMNode {
         val: Box<Self>,
         op_token: Token,
     },
+    /*
+     * `signed` tells how integer operands are divided and compared
+     */
     Binary {
         ty: LLVMTypeRef,
         left: Box<Self>,
         right: Box<Self>,
         op_token: Token,
+        signed: bool,
     },
     Fun {
         name: String,
+        binding: BindingId,
         ty: LLVMTypeRef,
-        params: Vec<(String, LLVMTypeRef)>,
+        params: Vec<(BindingId, String, LLVMTypeRef)>,
         body: Box<Self>,
     },
     Let {
         name: String,
+        binding: BindingId,
         ty: LLVMTypeRef,
         val: Box<Self>,
     },
+    /*
+     * Value of the binding, a function without one is looked up by its name
+     */
     Var {
         ty: LLVMTypeRef,
         name: String,
+        binding: Option<BindingId>,
     },
     Call {
         ty: LLVMTypeRef,
@@ -590,6 +826,7 @@ pub enum LLVMNode {
     For {
         ty: LLVMTypeRef,
         var: String,
+        binding: BindingId,
         start: Box<Self>,
         end: Box<Self>,
         step: Box<Self>,
@@ -636,10 +873,12 @@ pub enum LLVMNode {
     Class {
         ty: LLVMTypeRef,
         name: String,
+        binding: BindingId,
         properties: Vec<(String, Self)>,
         methods: Vec<(String, Self)>,
         constructor: Box<Self>,
         static_obj: Box<Self>,
+        base: Option<LLVMTypeRef>,
     },
     ClassInit {
         ty: LLVMTypeRef,
@@ -653,89 +892,200 @@ pub enum LLVMNode {
         args: Vec<Self>,
         var_args: bool,
     },
+    BoxAny {
+        ty: LLVMTypeRef,
+        tag: u32,
+        type_name: String,
+        val: Box<Self>,
+    },
+    UnboxAny {
+        ty: LLVMTypeRef,
+        tag: u32,
+        type_name: String,
+        val: Box<Self>,
+    },
+    Cast {
+        ty: LLVMTypeRef,
+        val: Box<Self>,
+        from_signed: bool,
+        to_signed: bool,
+    },
+    /*
+     * `soul` seen as the base class, methods called on it aren't dispatched virtually
+     */
+    Super {
+        ty: LLVMTypeRef,
+    },
+    /*
+     * Object of a class seen through the interface `name`, which has `methods`
+     */
+    Interface {
+        ty: LLVMTypeRef,
+        name: String,
+        methods: Vec<String>,
+        val: Box<Self>,
+    },
+    InterfaceCall {
+        ty: LLVMTypeRef,
+        object: Box<Self>,
+        slot: usize,
+        args: Vec<Self>,
+    },
+    NullCheck {
+        ty: LLVMTypeRef,
+        val: Box<Self>,
+        equal: bool,
+    },
+    /*
+     * Value flowing into a place of the nullable type `ty`
+     */
+    Wrap {
+        ty: LLVMTypeRef,
+        val: Box<Self>,
+    },
+    Unwrap {
+        ty: LLVMTypeRef,
+        val: Box<Self>,
+    },
+    SafeAccess {
+        ty: LLVMTypeRef,
+        name: String,
+        binding: BindingId,
+        object: Box<Self>,
+        access: Box<Self>,
+    },
+    Elvis {
+        ty: LLVMTypeRef,
+        val: Box<Self>,
+        default: Box<Self>,
+    },
+    Variant {
+        ty: LLVMTypeRef,
+        tag: u32,
+        payload: Vec<Self>,
+    },
+    /*
+     * Arms are the tag they match, `None` for `_`, and the bindings, names and types of the payload
+     */
+    Match {
+        ty: LLVMTypeRef,
+        val: Box<Self>,
+        arms: Vec<(Option<u32>, Vec<(BindingId, String, LLVMTypeRef)>, Self)>,
+    },
+    Tuple {
+        ty: LLVMTypeRef,
+        elements: Vec<Self>,
+    },
+    TupleAccess {
+        ty: LLVMTypeRef,
+        tuple: Box<Self>,
+        index: u32,
+    },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum TypedNode {
     Statements(Vec<Self>),
 
     Int {
         ty: Type,
+        pos: (Position, Position),
         val: i128,
     },
     Float {
         ty: Type,
+        pos: (Position, Position),
         val: f64,
     },
     Boolean {
         ty: Type,
+        pos: (Position, Position),
         val: bool,
     },
     Char {
         ty: Type,
+        pos: (Position, Position),
         val: char,
     },
     String {
         ty: Type,
+        pos: (Position, Position),
         val: String,
     },
     Unary {
         ty: Type,
+        pos: (Position, Position),
         val: Box<Self>,
         op_token: Token,
     },
     Binary {
         ty: Type,
+        pos: (Position, Position),
         left: Box<Self>,
         right: Box<Self>,
         op_token: Token,
     },
     Fun {
         ty: Type,
+        pos: (Position, Position),
         name: String,
+        binding: BindingId,
         params: Vec<Binder>,
         body: Box<Self>,
     },
     Let {
         ty: Type,
+        pos: (Position, Position),
         name: String,
+        binding: BindingId,
         val: Box<Self>,
     },
     ReLet {
         ty: Type,
+        pos: (Position, Position),
         prev: Type,
         name: String,
+        binding: BindingId,
         val: Box<Self>,
     },
     Var {
         ty: Type,
+        pos: (Position, Position),
         name: String,
+        binding: BindingId,
     },
     Call {
         ty: Type,
+        pos: (Position, Position),
         fun: Box<Self>,
         args: Vec<Self>,
     },
     Return {
         ty: Type,
+        pos: (Position, Position),
         val: Box<Self>,
     },
     Null {
         ty: Type,
+        pos: (Position, Position),
     },
     If {
         ty: Type,
+        pos: (Position, Position),
         cases: Vec<(Self, Self)>,
         else_case: Option<Box<Self>>,
     },
     While {
         ty: Type,
+        pos: (Position, Position),
         cond: Box<Self>,
         body: Box<Self>,
     },
     For {
         ty: Type,
+        pos: (Position, Position),
         var: String,
+        binding: BindingId,
         start: Box<Self>,
         end: Box<Self>,
         step: Box<Self>,
@@ -743,62 +1093,149 @@ pub enum TypedNode {
     },
     Array {
         ty: Type,
+        pos: (Position, Position),
         elements: Vec<Self>,
     },
     Index {
         ty: Type,
+        pos: (Position, Position),
         array: Box<Self>,
         idx: Box<Self>,
     },
     Object {
         ty: Type,
+        pos: (Position, Position),
         properties: BTreeMap<String, Self>,
     },
     CObject {
         ty: Type,
+        pos: (Position, Position),
         object: Box<Self>,
     },
     CToBzxObject {
         ty: Type,
+        pos: (Position, Position),
         object: Box<Self>,
     },
     ObjectAccess {
         ty: Type,
+        pos: (Position, Position),
         object: Box<Self>,
         property: String,
     },
     ObjectEdit {
         ty: Type,
+        pos: (Position, Position),
         object: Box<Self>,
         new_val: Box<Self>,
         property: String,
     },
     ObjectMethodCall {
         ty: Type,
+        pos: (Position, Position),
         object: Box<Self>,
         property: String,
         args: Vec<Self>,
     },
     Class {
         name: String,
+        binding: BindingId,
         ty: Type,
+        pos: (Position, Position),
         properties: BTreeMap<String, Self>,
         methods: BTreeMap<String, Self>,
         constructor: Box<Self>,
         static_obj: Box<Self>,
+        base: Option<Type>,
     },
     ClassInit {
         ty: Type,
+        pos: (Position, Position),
         class: Type,
         constructor_params: Vec<Self>,
     },
     Extern {
         ty: Type,
+        pos: (Position, Position),
         name: String,
         return_type: Box<Self>,
         args: Vec<Self>,
         var_args: bool,
     },
+    Cast {
+        ty: Type,
+        pos: (Position, Position),
+        val: Box<Self>,
+    },
+    /*
+     * A value flowing into a place of type `ty`, boxed into or unboxed out of `any` on the way
+     */
+    Convert {
+        ty: Type,
+        pos: (Position, Position),
+        val: Box<Self>,
+    },
+    Super {
+        ty: Type,
+        pos: (Position, Position),
+    },
+    /*
+     * Whether `val` is null, or isn't when `equal` is false
+     */
+    NullCheck {
+        ty: Type,
+        pos: (Position, Position),
+        val: Box<Self>,
+        equal: bool,
+    },
+    /*
+     * Value of a nullable variable checked not to be null
+     */
+    Unwrap {
+        ty: Type,
+        pos: (Position, Position),
+        val: Box<Self>,
+    },
+    SafeAccess {
+        ty: Type,
+        pos: (Position, Position),
+        name: String,
+        binding: BindingId,
+        object: Box<Self>,
+        access: Box<Self>,
+    },
+    Elvis {
+        ty: Type,
+        pos: (Position, Position),
+        val: Box<Self>,
+        default: Box<Self>,
+    },
+    /*
+     * Value of the enum `ty` with the variant numbered `tag`, holding `payload`
+     */
+    Variant {
+        ty: Type,
+        pos: (Position, Position),
+        tag: u32,
+        payload: Vec<Self>,
+    },
+    Match {
+        ty: Type,
+        pos: (Position, Position),
+        val: Box<Self>,
+        arms: Vec<(Option<u32>, Vec<Binder>, Self)>,
+    },
+    Tuple {
+        ty: Type,
+        pos: (Position, Position),
+        elements: Vec<Self>,
+    },
+    TupleAccess {
+        ty: Type,
+        pos: (Position, Position),
+        tuple: Box<Self>,
+        index: u32,
+    },
 }
 
 impl TypedNode {
@@ -810,7 +1247,19 @@ impl TypedNode {
                         return ty.clone();
                     }
                 }
-                Type::Null
+
+                /*
+                 * Without an explicit return a block evaluates to its last expression,
+                 * declarations don't produce a value
+                 */
+                match stmts.last() {
+                    Some(TypedNode::Let { .. })
+                    | Some(TypedNode::ReLet { .. })
+                    | Some(TypedNode::Class { .. })
+                    | Some(TypedNode::Extern { .. })
+                    | None => Type::Null,
+                    Some(stmt) => stmt.get_type(),
+                }
             }
             TypedNode::Int { ty, .. }
             | TypedNode::Float { ty, .. }
@@ -825,7 +1274,7 @@ impl TypedNode {
             | TypedNode::Return { ty, .. }
             | TypedNode::Unary { ty, .. }
             | TypedNode::Binary { ty, .. }
-            | TypedNode::Null { ty }
+            | TypedNode::Null { ty, .. }
             | TypedNode::If { ty, .. }
             | TypedNode::While { ty, .. }
             | TypedNode::For { ty, .. }
@@ -839,105 +1288,458 @@ impl TypedNode {
             | TypedNode::ObjectMethodCall { ty, .. }
             | TypedNode::Class { ty, .. }
             | TypedNode::ClassInit { ty, .. }
-            | TypedNode::Extern { ty, .. } => ty.clone(),
+            | TypedNode::Extern { ty, .. }
+            | TypedNode::Cast { ty, .. }
+            | TypedNode::Convert { ty, .. }
+            | TypedNode::Super { ty, .. }
+            | TypedNode::NullCheck { ty, .. }
+            | TypedNode::Unwrap { ty, .. }
+            | TypedNode::SafeAccess { ty, .. }
+            | TypedNode::Elvis { ty, .. }
+            | TypedNode::Variant { ty, .. }
+            | TypedNode::Match { ty, .. }
+            | TypedNode::Tuple { ty, .. }
+            | TypedNode::TupleAccess { ty, .. } => ty.clone(),
         }
     }
-}
 
-static mut I: i32 = 0;
-static mut OBJECT_ALIGNER: usize = 0;
+    pub fn get_pos(&self) -> (Position, Position) {
+        match self {
+            TypedNode::Statements(stmts) => match (stmts.first(), stmts.last()) {
+                (Some(first), Some(last)) => (first.get_pos().0, last.get_pos().1),
+                _ => (Position::proto(), Position::proto()),
+            },
+            TypedNode::Int { pos, .. }
+            | TypedNode::Float { pos, .. }
+            | TypedNode::Boolean { pos, .. }
+            | TypedNode::Char { pos, .. }
+            | TypedNode::String { pos, .. }
+            | TypedNode::Fun { pos, .. }
+            | TypedNode::Let { pos, .. }
+            | TypedNode::ReLet { pos, .. }
+            | TypedNode::Var { pos, .. }
+            | TypedNode::Call { pos, .. }
+            | TypedNode::Return { pos, .. }
+            | TypedNode::Unary { pos, .. }
+            | TypedNode::Binary { pos, .. }
+            | TypedNode::Null { pos, .. }
+            | TypedNode::If { pos, .. }
+            | TypedNode::While { pos, .. }
+            | TypedNode::For { pos, .. }
+            | TypedNode::Array { pos, .. }
+            | TypedNode::Index { pos, .. }
+            | TypedNode::Object { pos, .. }
+            | TypedNode::CObject { pos, .. }
+            | TypedNode::CToBzxObject { pos, .. }
+            | TypedNode::ObjectAccess { pos, .. }
+            | TypedNode::ObjectEdit { pos, .. }
+            | TypedNode::ObjectMethodCall { pos, .. }
+            | TypedNode::Class { pos, .. }
+            | TypedNode::ClassInit { pos, .. }
+            | TypedNode::Extern { pos, .. }
+            | TypedNode::Cast { pos, .. }
+            | TypedNode::Convert { pos, .. }
+            | TypedNode::Super { pos, .. }
+            | TypedNode::NullCheck { pos, .. }
+            | TypedNode::Unwrap { pos, .. }
+            | TypedNode::SafeAccess { pos, .. }
+            | TypedNode::Elvis { pos, .. }
+            | TypedNode::Variant { pos, .. }
+            | TypedNode::Match { pos, .. }
+            | TypedNode::Tuple { pos, .. }
+            | TypedNode::TupleAccess { pos, .. } => *pos,
+        }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord)]
 pub enum Type {
-    Int,
-    Float,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
     Boolean,
     Char,
     String,
     Array(Box<Self>, u32),
     Fun(Vec<Self>, Box<Self>),
-    Object(BTreeMap<String, Self>),
+    /*
+     * Objects made by a literal or a class are told apart by their id,
+     * the shapes demanded by accesses of their properties have none
+     */
+    Object(Option<u32>, BTreeMap<String, Self>),
     Class(Box<Self>),
+    Interface(String),
+    Enum(String),
+    Tuple(Vec<Self>),
+    Nullable(Box<Self>),
     Null,
+    Any,
 
     Var(i32),
 }
 
 impl Type {
     pub fn fresh_var() -> Self {
-        let ret = unsafe { Self::Var(I) };
-        unsafe { I += 1 };
-        ret
+        Self::Var(Session::fresh_var())
     }
 
     pub fn create_obj(props: BTreeMap<String, Self>) -> Self {
-        unsafe {
-            OBJECT_ALIGNER += 1;
-            let mut tree = BTreeMap::new();
-            tree.insert(
-                "%alignment%".to_string(),
-                Type::Array(Box::new(Type::Int), OBJECT_ALIGNER as u32),
-            );
-            tree.extend(props);
-            Self::Object(tree)
+        Self::Object(Some(Session::fresh_object()), props)
+    }
+
+    /*
+     * Object of a class extending `base`, laid out with the fields of the base first
+     * so a pointer to it can be used where one to the base is expected
+     */
+    pub fn create_class_obj(props: BTreeMap<String, Self>, base: Option<&Self>) -> Self {
+        let mut layout: Vec<String> = match base {
+            Some(base) => base.fields().into_iter().map(|(name, _)| name).collect(),
+            None => vec![],
+        };
+        for name in props.keys() {
+            if !layout.contains(name) {
+                layout.push(name.clone());
+            }
         }
+
+        let obj = Self::create_obj(props);
+        // the fields inherited from a base class come first
+        Session::set_layout(obj.id().unwrap(), layout);
+        obj
     }
 
-    pub fn last_aligner() -> usize {
-        unsafe { OBJECT_ALIGNER }
-    }
-
-    pub fn llvm(&self, ctx: LLVMContextRef, tvars: BTreeMap<Type, Type>) -> LLVMTypeRef {
-        unsafe {
-            match self {
-                Type::Int => LLVMInt128TypeInContext(ctx),
-                Type::Float => LLVMFloatTypeInContext(ctx),
-                Type::Boolean => LLVMInt1TypeInContext(ctx),
-                Type::Char => LLVMInt8TypeInContext(ctx),
-                Type::String => LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
-                Type::Array(ty, i) => LLVMPointerType(LLVMArrayType(ty.llvm(ctx, tvars), *i), 0),
-                Type::Fun(params, ret) => LLVMPointerType(
-                    LLVMFunctionType(
-                        ret.llvm(ctx, tvars.clone()),
-                        params
-                            .iter()
-                            .map(|p| p.llvm(ctx, tvars.clone()))
-                            .collect::<Vec<_>>()
-                            .as_mut_ptr(),
-                        params.len().try_into().unwrap(),
-                        0,
-                    ),
+    /*
+     * Id of an object or of the objects of a class
+     */
+    pub fn id(&self) -> Option<u32> {
+        match self {
+            Type::Object(id, _) => *id,
+            Type::Class(obj) => obj.id(),
+            _ => None,
+        }
+    }
+
+    /*
+     * Whether objects of the type are made by a class, they start with the vtable of it
+     */
+    pub fn is_class_obj(&self) -> bool {
+        matches!(self.id(), Some(id) if Session::layout(id).is_some())
+    }
+
+    /*
+     * Id of the objects the pointer type `ty` points to, if it was made for an object,
+     * `ty` has to be a type of a live LLVM context
+     */
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn object_id(ty: LLVMTypeRef) -> Option<u32> {
+        if LLVMGetTypeKind(ty) != LLVMTypeKind::LLVMPointerTypeKind {
+            return None;
+        }
+        Session::object_id(LLVMGetElementType(ty))
+    }
+
+    /*
+     * Properties of an object in the order they are laid out
+     */
+    pub fn fields(&self) -> Vec<(String, Self)> {
+        let tree = match self {
+            Type::Object(_, tree) => tree,
+            _ => return vec![],
+        };
+
+        let layout = self.id().and_then(Session::layout);
+        match layout {
+            Some(layout) => layout
+                .into_iter()
+                .filter_map(|name| tree.get(&name).map(|ty| (name, ty.clone())))
+                .collect(),
+            None => tree
+                .iter()
+                .map(|(name, ty)| (name.clone(), ty.clone()))
+                .collect(),
+        }
+    }
+
+    /*
+     * Type named by a type keyword, `int` and `float` are the C ones
+     */
+    pub fn keyword(keyword: &str) -> Option<Self> {
+        Some(match keyword {
+            "i8" => Type::I8,
+            "i16" => Type::I16,
+            "i32" | "int" => Type::I32,
+            "i64" => Type::I64,
+            "i128" => Type::I128,
+            "u8" => Type::U8,
+            "u16" => Type::U16,
+            "u32" => Type::U32,
+            "u64" => Type::U64,
+            "u128" => Type::U128,
+            "f32" | "float" => Type::F32,
+            "f64" => Type::F64,
+            "bool" => Type::Boolean,
+            "char" => Type::Char,
+            "string" => Type::String,
+            "void" => Type::Null,
+            "any" => Type::Any,
+            _ => return None,
+        })
+    }
+
+    pub fn is_int(&self) -> bool {
+        matches!(
+            self,
+            Type::I8
+                | Type::I16
+                | Type::I32
+                | Type::I64
+                | Type::I128
+                | Type::U8
+                | Type::U16
+                | Type::U32
+                | Type::U64
+                | Type::U128
+        )
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::F32 | Type::F64)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::I128 | Type::F32 | Type::F64
+        )
+    }
+
+    /*
+     * LLVM type values of the type have in `ctx`, which has to be a live context
+     */
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn llvm(&self, ctx: LLVMContextRef, tvars: BTreeMap<Type, Type>) -> LLVMTypeRef {
+        match self {
+            Type::I8 | Type::U8 => LLVMInt8TypeInContext(ctx),
+            Type::I16 | Type::U16 => LLVMInt16TypeInContext(ctx),
+            Type::I32 | Type::U32 => LLVMInt32TypeInContext(ctx),
+            Type::I64 | Type::U64 => LLVMInt64TypeInContext(ctx),
+            Type::I128 | Type::U128 => LLVMInt128TypeInContext(ctx),
+            Type::F32 => LLVMFloatTypeInContext(ctx),
+            Type::F64 => LLVMDoubleTypeInContext(ctx),
+            Type::Boolean => LLVMInt1TypeInContext(ctx),
+            Type::Char => LLVMInt8TypeInContext(ctx),
+            Type::String => LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
+            Type::Array(ty, i) => LLVMPointerType(LLVMArrayType(ty.llvm(ctx, tvars), *i), 0),
+            Type::Fun(params, ret) => LLVMPointerType(
+                LLVMFunctionType(
+                    ret.llvm(ctx, tvars.clone()),
+                    params
+                        .iter()
+                        .map(|p| p.llvm(ctx, tvars.clone()))
+                        .collect::<Vec<_>>()
+                        .as_mut_ptr(),
+                    params.len().try_into().unwrap(),
                     0,
                 ),
-                Type::Null => LLVMPointerType(
-                    LLVMStructTypeInContext(ctx, [].as_mut_ptr(), 0.try_into().unwrap(), 0),
+                0,
+            ),
+            Type::Null => LLVMPointerType(
+                LLVMStructTypeInContext(ctx, [].as_mut_ptr(), 0.try_into().unwrap(), 0),
+                0,
+            ),
+            // inference that couldn't decide on a type falls back to `any`
+            Type::Var(tvar) => match tvars.clone().get(&Type::Var(*tvar)) {
+                Some(ty) => ty.llvm(ctx, tvars),
+                None => Type::Any.llvm(ctx, tvars),
+            },
+            /*
+             * Boxed value, tagged with its type and the name of it for runtime errors
+             */
+            Type::Any => LLVMPointerType(
+                LLVMStructTypeInContext(
+                    ctx,
+                    [
+                        LLVMInt32TypeInContext(ctx),
+                        LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
+                        LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
+                    ]
+                    .as_mut_ptr(),
+                    3,
                     0,
                 ),
-                Type::Var(tvar) => tvars
-                    .clone()
-                    .get(&Type::Var(*tvar))
-                    .unwrap()
-                    .llvm(ctx, tvars),
-                Type::Object(tree) => LLVMPointerType(
-                    LLVMStructTypeInContext(
+                0,
+            ),
+            /*
+             * Objects with an id get a named struct for every layout they're given,
+             * the ones of a class start with a pointer to its vtable
+             */
+            Type::Object(id, _) => {
+                let mut fields = vec![];
+                if self.is_class_obj() {
+                    fields.push(LLVMPointerType(LLVMInt8TypeInContext(ctx), 0));
+                }
+                fields.extend(
+                    self.fields()
+                        .iter()
+                        .map(|(_, ty)| ty.llvm(ctx, tvars.clone())),
+                );
+
+                let object = match id {
+                    Some(id) => Session::object_type(*id, fields.clone(), || {
+                        let name = format!("object.{}", id);
+                        let named = LLVMStructCreateNamed(ctx, to_c_str(&name).as_ptr());
+                        LLVMStructSetBody(
+                            named,
+                            fields.as_mut_ptr(),
+                            fields.len().try_into().unwrap(),
+                            0,
+                        );
+                        named
+                    }),
+                    None => LLVMStructTypeInContext(
                         ctx,
-                        tree.iter()
-                            .map(|(_, v)| v.llvm(ctx, tvars.clone()))
-                            .collect::<Vec<_>>()
-                            .as_mut_ptr(),
-                        tree.len().try_into().unwrap(),
+                        fields.as_mut_ptr(),
+                        fields.len().try_into().unwrap(),
                         0,
                     ),
+                };
+                LLVMPointerType(object, 0)
+            }
+            Type::Class(obj) => obj.llvm(ctx, tvars),
+            /*
+             * Pointers are null themselves, other values are paired with whether they're set
+             */
+            Type::Nullable(ty) => {
+                let ty = ty.llvm(ctx, tvars);
+                if LLVMGetTypeKind(ty) == LLVMTypeKind::LLVMPointerTypeKind {
+                    ty
+                } else {
+                    LLVMStructTypeInContext(
+                        ctx,
+                        [LLVMInt1TypeInContext(ctx), ty].as_mut_ptr(),
+                        2,
+                        0,
+                    )
+                }
+            }
+            /*
+             * The object and the slots its methods have in the vtable of its class
+             */
+            Type::Interface(_) => LLVMStructTypeInContext(
+                ctx,
+                [
+                    LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
+                    LLVMPointerType(LLVMInt32TypeInContext(ctx), 0),
+                ]
+                .as_mut_ptr(),
+                2,
+                0,
+            ),
+            /*
+             * Tuples are passed around by value
+             */
+            Type::Tuple(elements) => {
+                let mut elements = elements
+                    .iter()
+                    .map(|ty| ty.llvm(ctx, tvars.clone()))
+                    .collect::<Vec<_>>();
+                LLVMStructTypeInContext(
+                    ctx,
+                    elements.as_mut_ptr(),
+                    elements.len().try_into().unwrap(),
                     0,
-                ),
-                Type::Class(obj) => obj.llvm(ctx, tvars),
+                )
             }
+            /*
+             * Tag of the variant and its payload, which is left null by variants without one
+             */
+            Type::Enum(_) => LLVMStructTypeInContext(
+                ctx,
+                [
+                    LLVMInt32TypeInContext(ctx),
+                    LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
+                ]
+                .as_mut_ptr(),
+                2,
+                0,
+            ),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/*
+ * Types are displayed the way they are written in BlazeX source, the ones
+ * which aren't known yet are `_`
+ */
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |types: &Vec<Type>| {
+            types
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Type::I8 => write!(f, "i8"),
+            Type::I16 => write!(f, "i16"),
+            Type::I32 => write!(f, "i32"),
+            Type::I64 => write!(f, "i64"),
+            Type::I128 => write!(f, "i128"),
+            Type::U8 => write!(f, "u8"),
+            Type::U16 => write!(f, "u16"),
+            Type::U32 => write!(f, "u32"),
+            Type::U64 => write!(f, "u64"),
+            Type::U128 => write!(f, "u128"),
+            Type::F32 => write!(f, "f32"),
+            Type::F64 => write!(f, "f64"),
+            Type::Boolean => write!(f, "bool"),
+            Type::Char => write!(f, "char"),
+            Type::String => write!(f, "string"),
+            Type::Null => write!(f, "void"),
+            Type::Any => write!(f, "any"),
+            Type::Array(ty, 0) => write!(f, "[{}]", ty),
+            Type::Array(ty, len) => write!(f, "[{}; {}]", ty, len),
+            Type::Fun(params, ret) => write!(f, "fun({}) -> {}", list(params), ret),
+            Type::Tuple(elements) => write!(f, "({})", list(elements)),
+            Type::Object(_, tree) => write!(
+                f,
+                "{{ {} }}",
+                tree.iter()
+                    .map(|(name, ty)| format!("{}: {}", name, ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Type::Class(obj) => write!(f, "class {}", obj),
+            Type::Interface(name) | Type::Enum(name) => write!(f, "{}", name),
+            Type::Nullable(ty) => write!(f, "{}?", ty),
+            Type::Var(_) => write!(f, "_"),
+        }
+    }
+}
+
+/*
+ * Dumps show types by how they're displayed
+ */
+impl Serialize for Type {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Binder {
     pub ty: Type,
     pub name: String,
+    pub binding: BindingId,
 }
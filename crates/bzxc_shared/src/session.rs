@@ -0,0 +1,224 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use llvm_sys::prelude::LLVMTypeRef;
+use serde::{Serialize, Serializer};
+
+thread_local! {
+    static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+}
+
+/*
+ * State of a single compilation, the strings of its files, the counters numbering
+ * its type variables, objects and bindings, and the layouts of its objects.
+ * It lives as long as `Session::enter` runs, so nothing outlasts the compilation
+ */
+#[derive(Default)]
+pub struct Session {
+    files: Vec<(String, String)>,
+    symbols: Vec<String>,
+    interned: HashMap<String, u32>,
+    vars: i32,
+    objects: u32,
+    bindings: u32,
+    layouts: BTreeMap<u32, Vec<String>>,
+    object_types: HashMap<(u32, Vec<LLVMTypeRef>), LLVMTypeRef>,
+    object_ids: HashMap<LLVMTypeRef, u32>,
+}
+
+/*
+ * Restores the session which was running before `Session::enter`, even on a panic
+ */
+struct Restore(Option<Session>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        SESSION.with(|session| *session.borrow_mut() = previous);
+    }
+}
+
+impl Session {
+    /*
+     * Runs `f` in a fresh session, dropped once it returns
+     */
+    pub fn enter<T>(f: impl FnOnce() -> T) -> T {
+        let previous = SESSION.with(|session| session.replace(Some(Session::default())));
+        let _restore = Restore(previous);
+        f()
+    }
+
+    pub(crate) fn with<T>(f: impl FnOnce(&mut Session) -> T) -> T {
+        SESSION.with(|session| {
+            f(session
+                .borrow_mut()
+                .as_mut()
+                .expect("used outside of a compilation session"))
+        })
+    }
+
+    /*
+     * Like `Session::with`, but nothing is run outside of a session or while it's in use
+     */
+    fn try_with<T>(f: impl FnOnce(&mut Session) -> T) -> Option<T> {
+        SESSION.with(|session| session.try_borrow_mut().ok()?.as_mut().map(f))
+    }
+
+    /*
+     * Adds a source file, positions refer to it by the index returned
+     */
+    pub fn add_file(name: String, content: String) -> usize {
+        Self::with(|session| {
+            session.files.push((name, content));
+            session.files.len() - 1
+        })
+    }
+
+    /*
+     * Name and content of the file at `index`, empty for positions made up by the compiler
+     */
+    pub fn file(index: usize) -> (String, String) {
+        Self::with(|session| session.files.get(index).cloned().unwrap_or_default())
+    }
+
+    pub(crate) fn fresh_var() -> i32 {
+        Self::with(|session| {
+            session.vars += 1;
+            session.vars - 1
+        })
+    }
+
+    pub(crate) fn fresh_object() -> u32 {
+        Self::with(|session| {
+            session.objects += 1;
+            session.objects
+        })
+    }
+
+    pub(crate) fn fresh_binding() -> u32 {
+        Self::with(|session| {
+            session.bindings += 1;
+            session.bindings
+        })
+    }
+
+    pub(crate) fn set_layout(id: u32, layout: Vec<String>) {
+        Self::with(|session| session.layouts.insert(id, layout));
+    }
+
+    pub(crate) fn layout(id: u32) -> Option<Vec<String>> {
+        Self::with(|session| session.layouts.get(&id).cloned())
+    }
+
+    /*
+     * Struct the object `id` is laid out in with `fields`, made by `make` the first time
+     */
+    pub(crate) fn object_type(
+        id: u32,
+        fields: Vec<LLVMTypeRef>,
+        make: impl FnOnce() -> LLVMTypeRef,
+    ) -> LLVMTypeRef {
+        if let Some(ty) =
+            Self::with(|session| session.object_types.get(&(id, fields.clone())).copied())
+        {
+            return ty;
+        }
+
+        let ty = make();
+        Self::with(|session| {
+            session.object_types.insert((id, fields), ty);
+            session.object_ids.insert(ty, id);
+        });
+        ty
+    }
+
+    /*
+     * Id of the object laid out in the struct `ty`
+     */
+    pub(crate) fn object_id(ty: LLVMTypeRef) -> Option<u32> {
+        Self::with(|session| session.object_ids.get(&ty).copied())
+    }
+}
+
+/*
+ * A string interned in the running session, like the name of an identifier
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn intern(string: &str) -> Self {
+        Session::with(|session| {
+            if let Some(symbol) = session.interned.get(string) {
+                return Symbol(*symbol);
+            }
+
+            let symbol = session.symbols.len() as u32;
+            session.symbols.push(string.to_string());
+            session.interned.insert(string.to_string(), symbol);
+            Symbol(symbol)
+        })
+    }
+
+    pub fn as_string(&self) -> String {
+        Session::with(|session| session.symbols[self.0 as usize].clone())
+    }
+
+    /*
+     * The interned string, symbols of a session which isn't running are shown by their index
+     */
+    fn name(&self) -> String {
+        Session::try_with(|session| session.symbols.get(self.0 as usize).cloned())
+            .flatten()
+            .unwrap_or_else(|| format!("#{}", self.0))
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.name())
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+/*
+ * A variable, parameter or function bound in the source, bindings sharing a name
+ * get different ids so inner ones don't clobber the ones they shadow
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct BindingId(pub u32);
+
+impl BindingId {
+    /*
+     * `soul`, the object a method is called on
+     */
+    pub const SOUL: Self = BindingId(0);
+
+    pub fn fresh() -> Self {
+        BindingId(Session::fresh_binding())
+    }
+}
@@ -0,0 +1,930 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use crate::{LLVMNode, Node, TypedNode};
+
+/*
+ * A tree whose direct children can be walked in source order, type annotations
+ * of a `Node` are children too
+ */
+pub trait Walk: Sized {
+    fn children(&self) -> Vec<&Self>;
+
+    fn children_mut(&mut self) -> Vec<&mut Self>;
+
+    /*
+     * A node without children, used as a placeholder while folding
+     */
+    fn empty() -> Self;
+}
+
+/*
+ * Read only pass over a tree, override `visit` for the variants you care about
+ * and call `walk` to keep descending into the rest
+ */
+pub trait Visitor<N: Walk> {
+    fn visit(&mut self, node: &N) {
+        walk(self, node);
+    }
+}
+
+/*
+ * Visits every direct child of the node
+ */
+pub fn walk<N: Walk, V: Visitor<N> + ?Sized>(visitor: &mut V, node: &N) {
+    for child in node.children() {
+        visitor.visit(child);
+    }
+}
+
+/*
+ * Pass which edits the tree in place
+ */
+pub trait VisitorMut<N: Walk> {
+    fn visit_mut(&mut self, node: &mut N) {
+        walk_mut(self, node);
+    }
+}
+
+/*
+ * Mutably visits every direct child of the node
+ */
+pub fn walk_mut<N: Walk, V: VisitorMut<N> + ?Sized>(visitor: &mut V, node: &mut N) {
+    for child in node.children_mut() {
+        visitor.visit_mut(child);
+    }
+}
+
+/*
+ * Pass which consumes the tree and rebuilds it, nodes can be replaced with a different variant
+ */
+pub trait Fold<N: Walk> {
+    fn fold(&mut self, node: N) -> N {
+        fold_children(self, node)
+    }
+}
+
+/*
+ * Folds every direct child of the node and puts the result back in its place
+ */
+pub fn fold_children<N: Walk, F: Fold<N> + ?Sized>(folder: &mut F, mut node: N) -> N {
+    for child in node.children_mut() {
+        let old = std::mem::replace(child, N::empty());
+        *child = folder.fold(old);
+    }
+    node
+}
+
+impl Walk for Node {
+    fn children(&self) -> Vec<&Self> {
+        match self {
+            Node::WhileNode {
+                condition_node,
+                body_node,
+                ..
+            } => vec![&**condition_node, &**body_node],
+            Node::VarReassignNode { value, .. } => vec![&**value],
+            Node::VarAssignNode { ty, value, .. } | Node::ConstNode { ty, value, .. } => {
+                let mut children: Vec<_> = (**ty).iter().collect();
+                children.push(&**value);
+                children
+            }
+            Node::UnaryNode { node, .. } => vec![&**node],
+            Node::IfNode {
+                cases, else_case, ..
+            } => {
+                let mut children: Vec<_> = cases
+                    .iter()
+                    .flat_map(|(cond, body)| vec![cond, body])
+                    .collect();
+                children.extend((**else_case).iter());
+                children
+            }
+            Node::FunDef {
+                args,
+                return_type,
+                body_node,
+                ..
+            } => {
+                let mut children: Vec<_> = args.iter().filter_map(|(_, ty)| ty.as_ref()).collect();
+                children.extend((**return_type).iter());
+                children.push(&**body_node);
+                children
+            }
+            Node::ForNode {
+                start_value,
+                end_value,
+                step_value_node,
+                body_node,
+                ..
+            } => vec![
+                &**start_value,
+                &**end_value,
+                &**step_value_node,
+                &**body_node,
+            ],
+            Node::CallNode {
+                node_to_call,
+                type_args,
+                args,
+            } => {
+                let mut children = vec![&**node_to_call];
+                children.extend(type_args.iter());
+                children.extend(args.iter());
+                children
+            }
+            Node::BinaryNode { left, right, .. } => vec![&**left, &**right],
+            Node::ArrayNode { element_nodes, .. } => element_nodes.iter().collect(),
+            Node::ArrayAcess { array, index, .. } => vec![&**array, &**index],
+            Node::Statements { statements, .. } => statements.iter().collect(),
+            Node::ReturnNode { value, .. } => (**value).iter().collect(),
+            Node::ObjectDefNode { properties, .. } => {
+                properties.iter().map(|(_, node)| node).collect()
+            }
+            Node::ObjectPropAccess { object, .. } => vec![&**object],
+            Node::ObjectPropEdit {
+                object, new_val, ..
+            } => vec![&**object, &**new_val],
+            Node::ObjectMethodCall { object, args, .. } => {
+                let mut children = vec![&**object];
+                children.extend(args.iter());
+                children
+            }
+            Node::ClassDefNode {
+                static_members,
+                properties,
+                methods,
+                constructor,
+                base,
+                interfaces,
+                ..
+            } => {
+                let mut children: Vec<_> = (**base).iter().collect();
+                children.extend(interfaces.iter());
+                children.extend(static_members.iter().map(|(_, node)| node));
+                children.extend(properties.iter().map(|(_, node)| node));
+                children.extend(methods.iter().map(|(_, method)| method));
+                children.extend((**constructor).iter());
+                children
+            }
+            Node::ClassInitNode {
+                type_args,
+                constructor_params,
+                ..
+            } => type_args.iter().chain(constructor_params.iter()).collect(),
+            Node::ExternNode {
+                arg_tokens,
+                return_type,
+                ..
+            } => {
+                let mut children: Vec<_> = arg_tokens.iter().collect();
+                children.push(&**return_type);
+                children
+            }
+            Node::CObject { object, .. } => vec![&**object],
+            Node::CToBzxObject {
+                object, bzx_object, ..
+            } => vec![&**object, &**bzx_object],
+            Node::CastNode { node, ty } => vec![&**node, &**ty],
+            Node::ElvisNode { value, default } => vec![&**value, &**default],
+            Node::SafeAccessNode { object, access, .. } => vec![&**object, &**access],
+            Node::MatchNode { value, arms } => {
+                let mut children = vec![&**value];
+                children.extend(arms.iter().flat_map(|(pattern, body)| vec![pattern, body]));
+                children
+            }
+            Node::TupleNode { elements } | Node::TypeTuple { elements } => {
+                elements.iter().collect()
+            }
+            Node::TupleAccess { tuple, .. } => vec![&**tuple],
+            Node::TupleAssignNode { value, .. } => vec![&**value],
+            Node::InterfaceDefNode { methods, .. } => methods
+                .iter()
+                .flat_map(|(_, params, ret)| {
+                    params
+                        .iter()
+                        .filter_map(|(_, ty)| ty.as_ref())
+                        .chain(ret.iter())
+                })
+                .collect(),
+            Node::EnumDefNode { variants, .. } => {
+                variants.iter().flat_map(|(_, payload)| payload).collect()
+            }
+            Node::TypeName { args, .. } => args.iter().collect(),
+            Node::TypeArray { element, .. } | Node::TypeNullable { element } => vec![&**element],
+            Node::VarAccessNode { .. }
+            | Node::StringNode { .. }
+            | Node::NumberNode { .. }
+            | Node::CharNode { .. }
+            | Node::BooleanNode { .. }
+            | Node::TypeKeyword { .. }
+            | Node::SuperNode { .. }
+            | Node::NullNode { .. }
+            | Node::VariantPattern { .. } => vec![],
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Self> {
+        match self {
+            Node::WhileNode {
+                condition_node,
+                body_node,
+                ..
+            } => vec![&mut **condition_node, &mut **body_node],
+            Node::VarReassignNode { value, .. } => vec![&mut **value],
+            Node::VarAssignNode { ty, value, .. } | Node::ConstNode { ty, value, .. } => {
+                let mut children: Vec<_> = (**ty).iter_mut().collect();
+                children.push(&mut **value);
+                children
+            }
+            Node::UnaryNode { node, .. } => vec![&mut **node],
+            Node::IfNode {
+                cases, else_case, ..
+            } => {
+                let mut children: Vec<_> = cases
+                    .iter_mut()
+                    .flat_map(|(cond, body)| vec![cond, body])
+                    .collect();
+                children.extend((**else_case).iter_mut());
+                children
+            }
+            Node::FunDef {
+                args,
+                return_type,
+                body_node,
+                ..
+            } => {
+                let mut children: Vec<_> =
+                    args.iter_mut().filter_map(|(_, ty)| ty.as_mut()).collect();
+                children.extend((**return_type).iter_mut());
+                children.push(&mut **body_node);
+                children
+            }
+            Node::ForNode {
+                start_value,
+                end_value,
+                step_value_node,
+                body_node,
+                ..
+            } => vec![
+                &mut **start_value,
+                &mut **end_value,
+                &mut **step_value_node,
+                &mut **body_node,
+            ],
+            Node::CallNode {
+                node_to_call,
+                type_args,
+                args,
+            } => {
+                let mut children = vec![&mut **node_to_call];
+                children.extend(type_args.iter_mut());
+                children.extend(args.iter_mut());
+                children
+            }
+            Node::BinaryNode { left, right, .. } => vec![&mut **left, &mut **right],
+            Node::ArrayNode { element_nodes, .. } => element_nodes.iter_mut().collect(),
+            Node::ArrayAcess { array, index, .. } => vec![&mut **array, &mut **index],
+            Node::Statements { statements, .. } => statements.iter_mut().collect(),
+            Node::ReturnNode { value, .. } => (**value).iter_mut().collect(),
+            Node::ObjectDefNode { properties, .. } => {
+                properties.iter_mut().map(|(_, node)| node).collect()
+            }
+            Node::ObjectPropAccess { object, .. } => vec![&mut **object],
+            Node::ObjectPropEdit {
+                object, new_val, ..
+            } => vec![&mut **object, &mut **new_val],
+            Node::ObjectMethodCall { object, args, .. } => {
+                let mut children = vec![&mut **object];
+                children.extend(args.iter_mut());
+                children
+            }
+            Node::ClassDefNode {
+                static_members,
+                properties,
+                methods,
+                constructor,
+                base,
+                interfaces,
+                ..
+            } => {
+                let mut children: Vec<_> = (**base).iter_mut().collect();
+                children.extend(interfaces.iter_mut());
+                children.extend(static_members.iter_mut().map(|(_, node)| node));
+                children.extend(properties.iter_mut().map(|(_, node)| node));
+                children.extend(methods.iter_mut().map(|(_, method)| method));
+                children.extend((**constructor).iter_mut());
+                children
+            }
+            Node::ClassInitNode {
+                type_args,
+                constructor_params,
+                ..
+            } => type_args
+                .iter_mut()
+                .chain(constructor_params.iter_mut())
+                .collect(),
+            Node::ExternNode {
+                arg_tokens,
+                return_type,
+                ..
+            } => {
+                let mut children: Vec<_> = arg_tokens.iter_mut().collect();
+                children.push(&mut **return_type);
+                children
+            }
+            Node::CObject { object, .. } => vec![&mut **object],
+            Node::CToBzxObject {
+                object, bzx_object, ..
+            } => vec![&mut **object, &mut **bzx_object],
+            Node::CastNode { node, ty } => vec![&mut **node, &mut **ty],
+            Node::ElvisNode { value, default } => vec![&mut **value, &mut **default],
+            Node::SafeAccessNode { object, access, .. } => vec![&mut **object, &mut **access],
+            Node::MatchNode { value, arms } => {
+                let mut children = vec![&mut **value];
+                children.extend(
+                    arms.iter_mut()
+                        .flat_map(|(pattern, body)| vec![pattern, body]),
+                );
+                children
+            }
+            Node::TupleNode { elements } | Node::TypeTuple { elements } => {
+                elements.iter_mut().collect()
+            }
+            Node::TupleAccess { tuple, .. } => vec![&mut **tuple],
+            Node::TupleAssignNode { value, .. } => vec![&mut **value],
+            Node::InterfaceDefNode { methods, .. } => methods
+                .iter_mut()
+                .flat_map(|(_, params, ret)| {
+                    params
+                        .iter_mut()
+                        .filter_map(|(_, ty)| ty.as_mut())
+                        .chain(ret.iter_mut())
+                })
+                .collect(),
+            Node::EnumDefNode { variants, .. } => variants
+                .iter_mut()
+                .flat_map(|(_, payload)| payload)
+                .collect(),
+            Node::TypeName { args, .. } => args.iter_mut().collect(),
+            Node::TypeArray { element, .. } | Node::TypeNullable { element } => {
+                vec![&mut **element]
+            }
+            Node::VarAccessNode { .. }
+            | Node::StringNode { .. }
+            | Node::NumberNode { .. }
+            | Node::CharNode { .. }
+            | Node::BooleanNode { .. }
+            | Node::TypeKeyword { .. }
+            | Node::SuperNode { .. }
+            | Node::NullNode { .. }
+            | Node::VariantPattern { .. } => vec![],
+        }
+    }
+
+    fn empty() -> Self {
+        Node::Statements { statements: vec![] }
+    }
+}
+
+impl Walk for TypedNode {
+    fn children(&self) -> Vec<&Self> {
+        match self {
+            TypedNode::Statements(stmts) => stmts.iter().collect(),
+            TypedNode::Unary { val, .. }
+            | TypedNode::Cast { val, .. }
+            | TypedNode::Convert { val, .. }
+            | TypedNode::NullCheck { val, .. }
+            | TypedNode::Unwrap { val, .. } => vec![&**val],
+            TypedNode::SafeAccess { object, access, .. } => vec![&**object, &**access],
+            TypedNode::Elvis { val, default, .. } => vec![&**val, &**default],
+            TypedNode::Variant { payload, .. } => payload.iter().collect(),
+            TypedNode::Tuple { elements, .. } => elements.iter().collect(),
+            TypedNode::TupleAccess { tuple, .. } => vec![&**tuple],
+            TypedNode::Match { val, arms, .. } => {
+                let mut children = vec![&**val];
+                children.extend(arms.iter().map(|(_, _, body)| body));
+                children
+            }
+            TypedNode::Binary { left, right, .. } => vec![&**left, &**right],
+            TypedNode::Fun { body, .. } => vec![&**body],
+            TypedNode::Let { val, .. } => vec![&**val],
+            TypedNode::ReLet { val, .. } => vec![&**val],
+            TypedNode::Call { fun, args, .. } => {
+                let mut children = vec![&**fun];
+                children.extend(args.iter());
+                children
+            }
+            TypedNode::Return { val, .. } => vec![&**val],
+            TypedNode::If {
+                cases, else_case, ..
+            } => {
+                let mut children: Vec<_> = cases
+                    .iter()
+                    .flat_map(|(cond, body)| vec![cond, body])
+                    .collect();
+                children.extend(else_case.iter().map(|node| &**node));
+                children
+            }
+            TypedNode::While { cond, body, .. } => vec![&**cond, &**body],
+            TypedNode::For {
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => vec![&**start, &**end, &**step, &**body],
+            TypedNode::Array { elements, .. } => elements.iter().collect(),
+            TypedNode::Index { array, idx, .. } => vec![&**array, &**idx],
+            TypedNode::Object { properties, .. } => properties.values().collect(),
+            TypedNode::CObject { object, .. } => vec![&**object],
+            TypedNode::CToBzxObject { object, .. } => vec![&**object],
+            TypedNode::ObjectAccess { object, .. } => vec![&**object],
+            TypedNode::ObjectEdit {
+                object, new_val, ..
+            } => vec![&**object, &**new_val],
+            TypedNode::ObjectMethodCall { object, args, .. } => {
+                let mut children = vec![&**object];
+                children.extend(args.iter());
+                children
+            }
+            TypedNode::Class {
+                static_obj,
+                properties,
+                methods,
+                constructor,
+                ..
+            } => {
+                let mut children = vec![&**static_obj];
+                children.extend(properties.values());
+                children.extend(methods.values());
+                children.push(&**constructor);
+                children
+            }
+            TypedNode::ClassInit {
+                constructor_params, ..
+            } => constructor_params.iter().collect(),
+            TypedNode::Extern {
+                args, return_type, ..
+            } => {
+                let mut children: Vec<_> = args.iter().collect();
+                children.push(&**return_type);
+                children
+            }
+            TypedNode::Int { .. }
+            | TypedNode::Float { .. }
+            | TypedNode::Boolean { .. }
+            | TypedNode::Char { .. }
+            | TypedNode::String { .. }
+            | TypedNode::Var { .. }
+            | TypedNode::Null { .. }
+            | TypedNode::Super { .. } => vec![],
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Self> {
+        match self {
+            TypedNode::Statements(stmts) => stmts.iter_mut().collect(),
+            TypedNode::Unary { val, .. }
+            | TypedNode::Cast { val, .. }
+            | TypedNode::Convert { val, .. }
+            | TypedNode::NullCheck { val, .. }
+            | TypedNode::Unwrap { val, .. } => vec![&mut **val],
+            TypedNode::SafeAccess { object, access, .. } => vec![&mut **object, &mut **access],
+            TypedNode::Elvis { val, default, .. } => vec![&mut **val, &mut **default],
+            TypedNode::Variant { payload, .. } => payload.iter_mut().collect(),
+            TypedNode::Tuple { elements, .. } => elements.iter_mut().collect(),
+            TypedNode::TupleAccess { tuple, .. } => vec![&mut **tuple],
+            TypedNode::Match { val, arms, .. } => {
+                let mut children = vec![&mut **val];
+                children.extend(arms.iter_mut().map(|(_, _, body)| body));
+                children
+            }
+            TypedNode::Binary { left, right, .. } => vec![&mut **left, &mut **right],
+            TypedNode::Fun { body, .. } => vec![&mut **body],
+            TypedNode::Let { val, .. } => vec![&mut **val],
+            TypedNode::ReLet { val, .. } => vec![&mut **val],
+            TypedNode::Call { fun, args, .. } => {
+                let mut children = vec![&mut **fun];
+                children.extend(args.iter_mut());
+                children
+            }
+            TypedNode::Return { val, .. } => vec![&mut **val],
+            TypedNode::If {
+                cases, else_case, ..
+            } => {
+                let mut children: Vec<_> = cases
+                    .iter_mut()
+                    .flat_map(|(cond, body)| vec![cond, body])
+                    .collect();
+                children.extend(else_case.iter_mut().map(|node| &mut **node));
+                children
+            }
+            TypedNode::While { cond, body, .. } => vec![&mut **cond, &mut **body],
+            TypedNode::For {
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => vec![&mut **start, &mut **end, &mut **step, &mut **body],
+            TypedNode::Array { elements, .. } => elements.iter_mut().collect(),
+            TypedNode::Index { array, idx, .. } => vec![&mut **array, &mut **idx],
+            TypedNode::Object { properties, .. } => properties.values_mut().collect(),
+            TypedNode::CObject { object, .. } => vec![&mut **object],
+            TypedNode::CToBzxObject { object, .. } => vec![&mut **object],
+            TypedNode::ObjectAccess { object, .. } => vec![&mut **object],
+            TypedNode::ObjectEdit {
+                object, new_val, ..
+            } => vec![&mut **object, &mut **new_val],
+            TypedNode::ObjectMethodCall { object, args, .. } => {
+                let mut children = vec![&mut **object];
+                children.extend(args.iter_mut());
+                children
+            }
+            TypedNode::Class {
+                static_obj,
+                properties,
+                methods,
+                constructor,
+                ..
+            } => {
+                let mut children = vec![&mut **static_obj];
+                children.extend(properties.values_mut());
+                children.extend(methods.values_mut());
+                children.push(&mut **constructor);
+                children
+            }
+            TypedNode::ClassInit {
+                constructor_params, ..
+            } => constructor_params.iter_mut().collect(),
+            TypedNode::Extern {
+                args, return_type, ..
+            } => {
+                let mut children: Vec<_> = args.iter_mut().collect();
+                children.push(&mut **return_type);
+                children
+            }
+            TypedNode::Int { .. }
+            | TypedNode::Float { .. }
+            | TypedNode::Boolean { .. }
+            | TypedNode::Char { .. }
+            | TypedNode::String { .. }
+            | TypedNode::Var { .. }
+            | TypedNode::Null { .. }
+            | TypedNode::Super { .. } => vec![],
+        }
+    }
+
+    fn empty() -> Self {
+        TypedNode::Statements(vec![])
+    }
+}
+
+impl Walk for LLVMNode {
+    fn children(&self) -> Vec<&Self> {
+        match self {
+            LLVMNode::Statements(stmts) => stmts.iter().collect(),
+            LLVMNode::Unary { val, .. }
+            | LLVMNode::BoxAny { val, .. }
+            | LLVMNode::UnboxAny { val, .. }
+            | LLVMNode::Cast { val, .. }
+            | LLVMNode::Interface { val, .. }
+            | LLVMNode::NullCheck { val, .. }
+            | LLVMNode::Wrap { val, .. }
+            | LLVMNode::Unwrap { val, .. } => vec![&**val],
+            LLVMNode::SafeAccess { object, access, .. } => vec![&**object, &**access],
+            LLVMNode::Elvis { val, default, .. } => vec![&**val, &**default],
+            LLVMNode::Variant { payload, .. } => payload.iter().collect(),
+            LLVMNode::Tuple { elements, .. } => elements.iter().collect(),
+            LLVMNode::TupleAccess { tuple, .. } => vec![&**tuple],
+            LLVMNode::Match { val, arms, .. } => {
+                let mut children = vec![&**val];
+                children.extend(arms.iter().map(|(_, _, body)| body));
+                children
+            }
+            LLVMNode::Binary { left, right, .. } => vec![&**left, &**right],
+            LLVMNode::Fun { body, .. } => vec![&**body],
+            LLVMNode::Let { val, .. } => vec![&**val],
+            LLVMNode::Call { fun, args, .. } => {
+                let mut children = vec![&**fun];
+                children.extend(args.iter());
+                children
+            }
+            LLVMNode::Return { val, .. } => vec![&**val],
+            LLVMNode::If {
+                cases, else_case, ..
+            } => {
+                let mut children: Vec<_> = cases
+                    .iter()
+                    .flat_map(|(cond, body)| vec![cond, body])
+                    .collect();
+                children.extend(else_case.iter().map(|node| &**node));
+                children
+            }
+            LLVMNode::While { cond, body, .. } => vec![&**cond, &**body],
+            LLVMNode::For {
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => vec![&**start, &**end, &**step, &**body],
+            LLVMNode::Array { elements, .. } => elements.iter().collect(),
+            LLVMNode::Index { array, idx, .. } => vec![&**array, &**idx],
+            LLVMNode::Object { properties, .. } => {
+                properties.iter().map(|(_, node)| node).collect()
+            }
+            LLVMNode::CObject { object, .. } => vec![&**object],
+            LLVMNode::CToBzxObject { object, .. } => vec![&**object],
+            LLVMNode::ObjectAccess { object, .. } => vec![&**object],
+            LLVMNode::ObjectEdit {
+                object, new_val, ..
+            } => vec![&**object, &**new_val],
+            LLVMNode::ObjectMethodCall { object, args, .. }
+            | LLVMNode::InterfaceCall { object, args, .. } => {
+                let mut children = vec![&**object];
+                children.extend(args.iter());
+                children
+            }
+            LLVMNode::Class {
+                static_obj,
+                properties,
+                methods,
+                constructor,
+                ..
+            } => {
+                let mut children = vec![&**static_obj];
+                children.extend(properties.iter().map(|(_, node)| node));
+                children.extend(methods.iter().map(|(_, node)| node));
+                children.push(&**constructor);
+                children
+            }
+            LLVMNode::ClassInit {
+                constructor_params, ..
+            } => constructor_params.iter().collect(),
+            LLVMNode::Extern {
+                args, return_type, ..
+            } => {
+                let mut children: Vec<_> = args.iter().collect();
+                children.push(&**return_type);
+                children
+            }
+            LLVMNode::Int { .. }
+            | LLVMNode::Float { .. }
+            | LLVMNode::Boolean { .. }
+            | LLVMNode::Char { .. }
+            | LLVMNode::String { .. }
+            | LLVMNode::Var { .. }
+            | LLVMNode::Null { .. }
+            | LLVMNode::Super { .. } => vec![],
+        }
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut Self> {
+        match self {
+            LLVMNode::Statements(stmts) => stmts.iter_mut().collect(),
+            LLVMNode::Unary { val, .. }
+            | LLVMNode::BoxAny { val, .. }
+            | LLVMNode::UnboxAny { val, .. }
+            | LLVMNode::Cast { val, .. }
+            | LLVMNode::Interface { val, .. }
+            | LLVMNode::NullCheck { val, .. }
+            | LLVMNode::Wrap { val, .. }
+            | LLVMNode::Unwrap { val, .. } => vec![&mut **val],
+            LLVMNode::SafeAccess { object, access, .. } => vec![&mut **object, &mut **access],
+            LLVMNode::Elvis { val, default, .. } => vec![&mut **val, &mut **default],
+            LLVMNode::Variant { payload, .. } => payload.iter_mut().collect(),
+            LLVMNode::Tuple { elements, .. } => elements.iter_mut().collect(),
+            LLVMNode::TupleAccess { tuple, .. } => vec![&mut **tuple],
+            LLVMNode::Match { val, arms, .. } => {
+                let mut children = vec![&mut **val];
+                children.extend(arms.iter_mut().map(|(_, _, body)| body));
+                children
+            }
+            LLVMNode::Binary { left, right, .. } => vec![&mut **left, &mut **right],
+            LLVMNode::Fun { body, .. } => vec![&mut **body],
+            LLVMNode::Let { val, .. } => vec![&mut **val],
+            LLVMNode::Call { fun, args, .. } => {
+                let mut children = vec![&mut **fun];
+                children.extend(args.iter_mut());
+                children
+            }
+            LLVMNode::Return { val, .. } => vec![&mut **val],
+            LLVMNode::If {
+                cases, else_case, ..
+            } => {
+                let mut children: Vec<_> = cases
+                    .iter_mut()
+                    .flat_map(|(cond, body)| vec![cond, body])
+                    .collect();
+                children.extend(else_case.iter_mut().map(|node| &mut **node));
+                children
+            }
+            LLVMNode::While { cond, body, .. } => vec![&mut **cond, &mut **body],
+            LLVMNode::For {
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => vec![&mut **start, &mut **end, &mut **step, &mut **body],
+            LLVMNode::Array { elements, .. } => elements.iter_mut().collect(),
+            LLVMNode::Index { array, idx, .. } => vec![&mut **array, &mut **idx],
+            LLVMNode::Object { properties, .. } => {
+                properties.iter_mut().map(|(_, node)| node).collect()
+            }
+            LLVMNode::CObject { object, .. } => vec![&mut **object],
+            LLVMNode::CToBzxObject { object, .. } => vec![&mut **object],
+            LLVMNode::ObjectAccess { object, .. } => vec![&mut **object],
+            LLVMNode::ObjectEdit {
+                object, new_val, ..
+            } => vec![&mut **object, &mut **new_val],
+            LLVMNode::ObjectMethodCall { object, args, .. }
+            | LLVMNode::InterfaceCall { object, args, .. } => {
+                let mut children = vec![&mut **object];
+                children.extend(args.iter_mut());
+                children
+            }
+            LLVMNode::Class {
+                static_obj,
+                properties,
+                methods,
+                constructor,
+                ..
+            } => {
+                let mut children = vec![&mut **static_obj];
+                children.extend(properties.iter_mut().map(|(_, node)| node));
+                children.extend(methods.iter_mut().map(|(_, node)| node));
+                children.push(&mut **constructor);
+                children
+            }
+            LLVMNode::ClassInit {
+                constructor_params, ..
+            } => constructor_params.iter_mut().collect(),
+            LLVMNode::Extern {
+                args, return_type, ..
+            } => {
+                let mut children: Vec<_> = args.iter_mut().collect();
+                children.push(&mut **return_type);
+                children
+            }
+            LLVMNode::Int { .. }
+            | LLVMNode::Float { .. }
+            | LLVMNode::Boolean { .. }
+            | LLVMNode::Char { .. }
+            | LLVMNode::String { .. }
+            | LLVMNode::Var { .. }
+            | LLVMNode::Null { .. }
+            | LLVMNode::Super { .. } => vec![],
+        }
+    }
+
+    fn empty() -> Self {
+        LLVMNode::Statements(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Position, Token, Tokens, Type};
+
+    fn token(value: Tokens) -> Token {
+        Token::new(value, Position::proto(), Position::proto())
+    }
+
+    /*
+     * `1 + -(2 + 3)`
+     */
+    fn node() -> Node {
+        let number = |n| Node::NumberNode {
+            token: token(Tokens::Int(n)),
+        };
+        let binary = |left, right| Node::BinaryNode {
+            left: Box::new(left),
+            right: Box::new(right),
+            op_token: token(Tokens::Plus),
+        };
+        Node::Statements {
+            statements: vec![binary(
+                number(1),
+                Node::UnaryNode {
+                    node: Box::new(binary(number(2), number(3))),
+                    op_token: token(Tokens::Minus),
+                },
+            )],
+        }
+    }
+
+    /*
+     * `1 + -(2 + 3)` typed
+     */
+    fn typed_node() -> TypedNode {
+        let pos = (Position::proto(), Position::proto());
+        let int = |val| TypedNode::Int {
+            ty: Type::I32,
+            pos,
+            val,
+        };
+        let binary = |left, right| TypedNode::Binary {
+            ty: Type::I32,
+            pos,
+            left: Box::new(left),
+            right: Box::new(right),
+            op_token: token(Tokens::Plus),
+        };
+        TypedNode::Statements(vec![binary(
+            int(1),
+            TypedNode::Unary {
+                ty: Type::I32,
+                pos,
+                val: Box::new(binary(int(2), int(3))),
+                op_token: token(Tokens::Minus),
+            },
+        )])
+    }
+
+    struct Numbers(Vec<i128>);
+
+    impl Visitor<Node> for Numbers {
+        fn visit(&mut self, node: &Node) {
+            if let Node::NumberNode { token } = node {
+                if let Tokens::Int(n) = token.value {
+                    self.0.push(n);
+                }
+            }
+            walk(self, node);
+        }
+    }
+
+    impl Visitor<TypedNode> for Numbers {
+        fn visit(&mut self, node: &TypedNode) {
+            if let TypedNode::Int { val, .. } = node {
+                self.0.push(*val);
+            }
+            walk(self, node);
+        }
+    }
+
+    struct Double;
+
+    impl VisitorMut<TypedNode> for Double {
+        fn visit_mut(&mut self, node: &mut TypedNode) {
+            if let TypedNode::Int { val, .. } = node {
+                *val *= 2;
+            }
+            walk_mut(self, node);
+        }
+    }
+
+    struct Negate;
+
+    impl Fold<TypedNode> for Negate {
+        fn fold(&mut self, node: TypedNode) -> TypedNode {
+            match fold_children(self, node) {
+                TypedNode::Int { ty, pos, val } => TypedNode::Int { ty, pos, val: -val },
+                node => node,
+            }
+        }
+    }
+
+    #[test]
+    fn visitor() {
+        let mut numbers = Numbers(vec![]);
+        numbers.visit(&node());
+        assert_eq!(numbers.0, vec![1, 2, 3]);
+
+        let mut numbers = Numbers(vec![]);
+        numbers.visit(&typed_node());
+        assert_eq!(numbers.0, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn visitor_mut() {
+        let mut node = typed_node();
+        Double.visit_mut(&mut node);
+
+        let mut numbers = Numbers(vec![]);
+        numbers.visit(&node);
+        assert_eq!(numbers.0, vec![2, 4, 6]);
+    }
+
+    #[test]
+    fn fold() {
+        let node = Negate.fold(typed_node());
+
+        let mut numbers = Numbers(vec![]);
+        numbers.visit(&node);
+        assert_eq!(numbers.0, vec![-1, -2, -3]);
+    }
+}
@@ -12,163 +12,250 @@ use std::collections::BTreeMap;
  * See the License for the specific language governing permissions and
  * limitations under the License.
 */
-use bzxc_shared::{Binder, Node, Tokens, Type, TypedNode};
+use bzxc_shared::{Binder, BindingId, Error, Node, Position, Token, Tokens, Type, TypedNode};
 
-use crate::TypeSystem;
+use crate::generics::ClassDef;
+use crate::numeric::Numeric;
+use crate::{type_error, TypeSystem};
 
 impl TypeSystem {
-    pub(crate) fn annotate(&mut self, node: Node) -> TypedNode {
-        match node.clone() {
-            Node::Statements { statements } => TypedNode::Statements(
-                statements
-                    .iter()
-                    .map(|statement| self.annotate(statement.clone()))
-                    .collect(),
-            ),
+    pub(crate) fn annotate(&mut self, node: Node) -> Result<TypedNode, Error> {
+        let pos = node.get_pos();
+        Ok(match node.clone() {
+            Node::Statements { statements } => {
+                let mut stmts = vec![];
+                for statement in statements {
+                    self.annotate_statement(statement, &mut stmts)?;
+                }
+                TypedNode::Statements(stmts)
+            }
             Node::NumberNode { token } => match token.value {
                 Tokens::Int(i) => TypedNode::Int {
-                    ty: Type::fresh_var(),
+                    ty: self.numeric_var(Numeric::Int),
+                    pos,
                     val: i,
                 },
                 Tokens::Float(f) => TypedNode::Float {
-                    ty: Type::fresh_var(),
+                    ty: self.numeric_var(Numeric::Float),
+                    pos,
                     val: f,
                 },
                 _ => unreachable!(),
             },
             Node::BooleanNode { token } => TypedNode::Boolean {
                 ty: Type::fresh_var(),
+                pos,
                 val: token.value.into_boolean(),
             },
             Node::CharNode { token } => TypedNode::Char {
                 ty: Type::fresh_var(),
+                pos,
                 val: token.value.into_char(),
             },
             Node::StringNode { token } => TypedNode::String {
                 ty: Type::fresh_var(),
+                pos,
                 val: token.value.into_string(),
             },
             Node::UnaryNode { node, op_token } => TypedNode::Unary {
                 ty: Type::fresh_var(),
-                val: box self.annotate(*node),
+                pos,
+                val: box self.annotate(*node)?,
                 op_token,
             },
+            Node::BinaryNode {
+                left,
+                op_token,
+                right,
+            } if Self::null_compared(&left, &right, &op_token).is_some() => TypedNode::NullCheck {
+                ty: Type::Boolean,
+                pos,
+                val: box self.annotate(Self::null_compared(&left, &right, &op_token).unwrap())?,
+                equal: op_token.value == Tokens::DoubleEquals,
+            },
             Node::BinaryNode {
                 left,
                 op_token,
                 right,
             } => TypedNode::Binary {
                 ty: Type::fresh_var(),
-                left: box self.annotate(*left.clone()),
-                right: box self.annotate(*right.clone()),
+                pos,
+                left: box self.annotate(*left.clone())?,
+                right: box self.annotate(*right.clone())?,
                 op_token,
             },
-            Node::VarAccessNode { token } => match self.type_env.get(token.value.into_string()) {
-                Some(ty) => TypedNode::Var {
-                    ty,
+            Node::VarAccessNode { token } => match self.lookup_var(&token)? {
+                Some((binding, ty)) if self.narrowed(&ty).is_some() => {
+                    self.unwrap_var(ty, token.value.into_string(), binding, pos)
+                }
+                Some((binding, ty)) => TypedNode::Var {
+                    ty: self.instantiate(ty),
+                    pos,
                     name: token.value.into_string(),
+                    binding,
                 },
                 None => {
-                    println!("{:?}", self.type_env);
-                    panic!("No var found {}", token.value.into_string());
+                    return Err(type_error(
+                        "Undefined Variable",
+                        pos,
+                        format!("`{}` is not defined", token.value.into_string()),
+                    ))
                 }
             },
-            Node::VarAssignNode { name, value, .. } => {
-                let val = self.annotate(*value);
+            Node::VarAssignNode {
+                name,
+                ty,
+                value,
+                reassignable,
+            } => {
+                let val = self.annotate_value(*ty, *value)?;
                 let ty = val.get_type();
-                self.type_env.set(name.value.into_string(), ty.clone());
+                let binding = if reassignable {
+                    self.bind(name.value.into_string(), ty.clone())
+                } else {
+                    let pos = (name.pos_start, name.pos_end);
+                    self.bind_val(name.value.into_string(), ty.clone(), pos)
+                };
+                if let (TypedNode::Fun { .. }, 0) = (&val, self.class_depth) {
+                    self.generalize(name.value.into_string(), binding, &val)?;
+                }
                 TypedNode::Let {
                     ty,
+                    pos,
                     name: name.value.into_string(),
+                    binding,
                     val: box val,
                 }
             }
+            Node::ConstNode { name, ty, value } => {
+                let val = self.annotate_value(*ty, *value)?;
+                self.annotate_const(name, val, pos)?
+            }
             Node::FunDef {
-                arg_tokens,
-                body_node,
                 name,
+                type_params,
+                args,
+                return_type,
+                body_node,
             } => {
-                let ty = Type::fresh_var();
-
-                let name = if let Some(tok) = name {
-                    self.type_env.set(tok.value.into_string(), ty.clone());
-                    tok.value.into_string()
-                } else {
-                    "%anonymous%".to_string()
-                };
-
-                self.type_env.push_scope();
-                let mut binders = vec![];
-                for arg in arg_tokens {
-                    let ty = Type::fresh_var();
-
-                    self.type_env.set(arg.value.into_string(), ty.clone());
-                    let binder = Binder {
-                        ty,
-                        name: arg.value.into_string(),
-                    };
-                    binders.push(binder);
+                let fun =
+                    self.annotate_fun(name, type_params, args, *return_type, *body_node, pos)?;
+                if let (Some(name), TypedNode::Fun { binding, .. }, 0) =
+                    (name, &fun, self.class_depth)
+                {
+                    self.generalize(name.value.into_string(), *binding, &fun)?;
                 }
-
-                let fun = TypedNode::Fun {
-                    ty,
-                    name,
-                    params: binders,
-                    body: box self.annotate(*body_node),
-                };
-
-                self.type_env.pop_scope();
-
                 fun
             }
-            Node::CallNode { args, node_to_call } => TypedNode::Call {
+            Node::CallNode {
+                args, node_to_call, ..
+            } if matches!(*node_to_call, Node::SuperNode { .. }) => TypedNode::ObjectMethodCall {
                 ty: Type::fresh_var(),
-                fun: box self.annotate(*node_to_call),
-                args: args.iter().map(|x| self.annotate(x.clone())).collect(),
+                pos,
+                object: box self.annotate(*node_to_call)?,
+                property: "constructor".to_string(),
+                args: self.annotate_args(args)?,
             },
+            Node::SuperNode { .. } => self.annotate_super(pos)?,
+            Node::CallNode {
+                args,
+                node_to_call,
+                type_args,
+            } => {
+                let mut callee = None;
+                if let Node::VarAccessNode { token } = &*node_to_call {
+                    if let Some((_, fun)) = self.lookup(&token.value.into_string()) {
+                        self.callee(pos, &fun);
+                        callee = Some((*token, fun));
+                    }
+                }
+
+                let fun = self.annotate(*node_to_call)?;
+                if let (Some((name, declared)), false) = (callee, type_args.is_empty()) {
+                    self.call_type_args(name, &declared, &fun.get_type(), type_args)?;
+                }
+                let args = self.annotate_args(args)?;
+                self.call(pos, &args);
+                TypedNode::Call {
+                    ty: Type::fresh_var(),
+                    pos,
+                    fun: box fun,
+                    args,
+                }
+            }
             Node::ReturnNode { value } => {
                 let val = box if let Some(val) = *value.clone() {
-                    self.annotate(val)
+                    self.annotate(val)?
                 } else {
-                    TypedNode::Null { ty: Type::Null }
+                    TypedNode::Null {
+                        ty: Type::Null,
+                        pos,
+                    }
+                };
+                let val = match self.returns.last() {
+                    Some(Some(ret)) => box self.convert(ret.clone(), *val),
+                    _ => val,
                 };
                 TypedNode::Return {
                     ty: val.get_type(),
+                    pos,
                     val,
                 }
             }
-            Node::IfNode { cases, else_case } => TypedNode::If {
-                ty: Type::fresh_var(),
-                cases: cases
-                    .iter()
-                    .map(|x| {
-                        self.type_env.push_scope();
-                        let val = (self.annotate(x.0.clone()), self.annotate(x.1.clone()));
-                        self.type_env.pop_scope();
-                        val
-                    })
-                    .collect(),
-                else_case: if let Some(n) = *else_case.clone() {
-                    self.type_env.push_scope();
-                    let val = box self.annotate(n);
-                    self.type_env.pop_scope();
-                    Some(val)
-                } else {
-                    None
-                },
-            },
+            Node::IfNode { cases, else_case } => {
+                // a variable checked to be null by the only case isn't null in the else branch
+                let not_null = match cases.as_slice() {
+                    [(cond, _)] => Self::null_checked(cond, true),
+                    _ => None,
+                };
+
+                let mut typed_cases = vec![];
+                for (cond, body) in cases {
+                    self.push_scope();
+                    let narrowed = Self::null_checked(&cond, false);
+                    let val = self.annotate(cond).and_then(|cond| {
+                        if let Some((name, pos)) = narrowed {
+                            self.narrow(&name, pos)?;
+                        }
+                        Ok((cond, self.annotate(body)?))
+                    });
+                    self.pop_scope();
+                    typed_cases.push(val?);
+                }
+
+                TypedNode::If {
+                    ty: Type::fresh_var(),
+                    pos,
+                    cases: typed_cases,
+                    else_case: if let Some(n) = *else_case.clone() {
+                        self.push_scope();
+                        let val = match not_null {
+                            Some((name, pos)) => self.narrow(&name, pos),
+                            None => Ok(()),
+                        }
+                        .and_then(|_| self.annotate(n));
+                        self.pop_scope();
+                        Some(box val?)
+                    } else {
+                        None
+                    },
+                }
+            }
             Node::WhileNode {
                 condition_node,
                 body_node,
             } => {
-                self.type_env.push_scope();
-                let val = TypedNode::While {
-                    ty: Type::fresh_var(),
-                    cond: box self.annotate(*condition_node),
-                    body: box self.annotate(*body_node),
-                };
-                self.type_env.pop_scope();
-                val
+                self.push_scope();
+                let val = self.annotate(*condition_node).and_then(|cond| {
+                    Ok(TypedNode::While {
+                        ty: Type::fresh_var(),
+                        pos,
+                        cond: box cond,
+                        body: box self.annotate(*body_node)?,
+                    })
+                });
+                self.pop_scope();
+                val?
             }
             Node::ForNode {
                 var_name_token,
@@ -177,231 +264,610 @@ impl TypeSystem {
                 step_value_node,
                 body_node,
             } => {
-                self.type_env.push_scope();
-                let val = TypedNode::For {
-                    ty: Type::fresh_var(),
-                    var: var_name_token.value.into_string(),
-                    start: {
-                        let start = box self.annotate(*start_value);
-                        self.type_env
-                            .set(var_name_token.value.into_string(), start.get_type());
-                        start
-                    },
-                    end: box self.annotate(*end_value),
-                    step: box self.annotate(*step_value_node),
-                    body: box self.annotate(*body_node),
-                };
-                self.type_env.pop_scope();
-                val
+                self.push_scope();
+                let val = self.annotate(*start_value).and_then(|start| {
+                    let binding = self.bind(var_name_token.value.into_string(), start.get_type());
+                    Ok(TypedNode::For {
+                        ty: Type::fresh_var(),
+                        pos,
+                        var: var_name_token.value.into_string(),
+                        binding,
+                        start: box start,
+                        end: box self.annotate(*end_value)?,
+                        step: box self.annotate(*step_value_node)?,
+                        body: box self.annotate(*body_node)?,
+                    })
+                });
+                self.pop_scope();
+                val?
             }
-            Node::ArrayNode { element_nodes } => TypedNode::Array {
+            Node::ArrayNode { element_nodes, .. } if element_nodes.is_empty() => TypedNode::Array {
+                ty: self.empty_array(pos),
+                pos,
+                elements: vec![],
+            },
+            Node::ArrayNode { element_nodes, .. } => TypedNode::Array {
                 ty: Type::fresh_var(),
-                elements: element_nodes
-                    .iter()
-                    .map(|x| self.annotate(x.clone()))
-                    .collect(),
+                pos,
+                elements: self.annotate_all(element_nodes)?,
             },
             Node::ArrayAcess { array, index } => TypedNode::Index {
                 ty: Type::fresh_var(),
-                array: box self.annotate(*array),
-                idx: box self.annotate(*index),
+                pos,
+                array: box self.annotate(*array)?,
+                idx: box self.annotate(*index)?,
             },
             Node::VarReassignNode { name, typee, value } => {
-                let name = name.value.into_string();
-                let val = box self.annotate(*value);
-                let prev = self.type_env.get(name.clone()).unwrap().clone();
+                let val = self.annotate(*value)?;
+                let (binding, prev) = match self.lookup_var(&name)? {
+                    Some(bound) => bound,
+                    None => {
+                        return Err(type_error(
+                            "Undefined Variable",
+                            (name.pos_start, name.pos_end),
+                            format!("`{}` is not defined", name.value.into_string()),
+                        ))
+                    }
+                };
+                self.check_reassignable(&name, binding, pos)?;
+
+                // a variable known not to be null can only be given values which aren't
+                let (prev, val) = match self.narrowed(&prev) {
+                    Some(nullable) => (
+                        nullable.clone(),
+                        self.convert(nullable, self.convert(prev, val)),
+                    ),
+                    None => (prev.clone(), self.convert(prev, val)),
+                };
 
                 TypedNode::ReLet {
-                    ty: val.get_type(),
-                    name,
-                    val,
+                    ty: prev.clone(),
+                    pos,
+                    name: name.value.into_string(),
+                    binding,
+                    val: box val,
                     prev,
                 }
             }
             Node::ObjectDefNode { properties } => TypedNode::Object {
                 ty: Type::fresh_var(),
+                pos,
                 properties: {
                     let mut tree = BTreeMap::new();
 
                     for (name, node) in properties {
-                        tree.insert(name.value.into_string(), self.annotate(node.clone()));
+                        tree.insert(name.value.into_string(), self.annotate(node.clone())?);
                     }
                     tree.clone()
                 },
             },
+            Node::ObjectPropAccess { object, property } if self.enum_named(&object).is_some() => {
+                self.variant(self.enum_named(&object).unwrap(), property, vec![], pos)?
+            }
             Node::ObjectPropAccess { object, property } => TypedNode::ObjectAccess {
                 ty: Type::fresh_var(),
-                object: box self.annotate(*object),
+                pos,
+                object: box self.annotate(*object)?,
                 property: property.value.into_string(),
             },
             Node::ObjectPropEdit {
                 object,
                 property,
                 new_val,
-            } => TypedNode::ObjectEdit {
-                ty: Type::fresh_var(),
-                object: box self.annotate(*object),
-                property: property.value.into_string(),
-                new_val: box self.annotate(*new_val),
-            },
+            } => {
+                if let Node::VarAccessNode { token } = &*object {
+                    self.check_frozen(token, pos)?;
+                }
+                TypedNode::ObjectEdit {
+                    ty: Type::fresh_var(),
+                    pos,
+                    object: box self.annotate(*object)?,
+                    property: property.value.into_string(),
+                    new_val: box {
+                        let val = self.annotate(*new_val)?;
+                        self.convert(Type::fresh_var(), val)
+                    },
+                }
+            }
             Node::ObjectMethodCall {
                 object,
                 property,
                 args,
-            } => TypedNode::ObjectMethodCall {
-                ty: Type::fresh_var(),
-                object: box self.annotate(*object),
-                property: property.value.into_string(),
-                args: args.iter().map(|x| self.annotate(x.clone())).collect(),
-            },
+            } if self.enum_named(&object).is_some() => {
+                self.variant(self.enum_named(&object).unwrap(), property, args, pos)?
+            }
+            Node::ObjectMethodCall {
+                object,
+                property,
+                args,
+            } => {
+                let object = self.annotate(*object)?;
+                let args = self.annotate_args(args)?;
+                self.call(pos, &args);
+                TypedNode::ObjectMethodCall {
+                    ty: Type::fresh_var(),
+                    pos,
+                    object: box object,
+                    property: property.value.into_string(),
+                    args,
+                }
+            }
             Node::ClassDefNode {
                 methods: mthds,
                 properties: props,
                 constructor,
                 name,
                 static_members: st_mthds,
+                type_params,
+                base,
+                interfaces,
             } => {
+                if !type_params.is_empty() {
+                    self.class_env.insert(
+                        name.value.into_string(),
+                        ClassDef::Generic {
+                            params: type_params
+                                .iter()
+                                .map(|param| param.value.into_string())
+                                .collect(),
+                            node,
+                            env: self.type_env.clone(),
+                            names: self.resolver.clone(),
+                        },
+                    );
+                    return Ok(TypedNode::Statements(vec![]));
+                }
+
                 let mut properties = BTreeMap::new();
                 let mut methods = BTreeMap::new();
                 let mut static_members = BTreeMap::new();
 
                 for (name, value) in st_mthds {
-                    static_members.insert(name.value.into_string(), self.annotate(value.clone()));
+                    static_members.insert(name.value.into_string(), self.annotate_member(value)?);
                 }
 
                 for (name, node) in props {
-                    properties.insert(name.value.into_string(), self.annotate(node.clone()));
+                    properties.insert(name.value.into_string(), self.annotate_member(node)?);
                 }
 
+                let base = match *base {
+                    Some(base) => Some(self.base_class(base)?),
+                    None => None,
+                };
+                // inherited properties start from the values they have in the base class
+                if let Some((_, base)) = &base {
+                    for (name, val) in self.class_properties[base].clone() {
+                        properties.entry(name).or_insert(val);
+                    }
+                }
+                self.class_properties
+                    .insert(name.value.into_string(), properties.clone());
+                let base = base.map(|(base, _)| base);
+
                 let obj_ty = Type::fresh_var();
                 let ty = Type::Class(box obj_ty.clone());
+                self.define(&ty, (name.pos_start, name.pos_end));
+                self.class_env
+                    .insert(name.value.into_string(), ClassDef::Concrete(ty.clone()));
 
                 let static_obj = TypedNode::Object {
                     ty: Type::fresh_var(),
+                    pos,
                     properties: static_members,
                 };
-                self.type_env
-                    .set(name.value.into_string(), static_obj.get_type());
-                self.type_env.push_scope();
-                self.type_env.set("soul".to_string(), obj_ty.clone());
+                let binding = self.bind_static(name.value.into_string(), static_obj.get_type());
+                self.push_scope();
+                self.resolver.enter_fun();
+                self.resolver.bind_soul();
+                self.type_env.set(BindingId::SOUL, obj_ty.clone());
+                self.supers.push(base.clone());
+                self.class_depth += 1;
 
-                for (name, args, body) in mthds {
-                    methods.insert(
-                        name.value.into_string(),
-                        self.annotate(Node::FunDef {
-                            name: Some(name),
-                            body_node: box body,
-                            arg_tokens: args,
-                        }),
-                    );
-                }
+                let val = self.annotate_class_body(mthds, &mut methods).and_then(|_| {
+                    self.implements(name, interfaces, &methods, base.as_ref())?;
+                    let (args, body_node) = match *constructor {
+                        Some(Node::FunDef {
+                            args, body_node, ..
+                        }) => (args, body_node),
+                        Some(_) => unreachable!(),
+                        None => self.default_constructor(base.as_ref(), name),
+                    };
 
-                self.class_env.insert(name.value.into_string(), ty.clone());
+                    let params = self.annotate_params(args)?;
+                    let params_ty = params.iter().map(|param| param.ty.clone()).collect();
 
-                let mut params = vec![];
-                let mut params_ty = vec![];
-                for arg in constructor.0 {
-                    let ty = Type::fresh_var();
-                    params_ty.push(ty.clone());
-                    self.type_env.set(arg.value.into_string(), ty.clone());
-                    params.push(Binder {
+                    self.returns.push(None);
+                    let body = self.annotate(*body_node);
+                    self.returns.pop();
+                    let body = body?;
+                    Ok(TypedNode::Class {
                         ty,
-                        name: arg.value.into_string(),
-                    });
-                }
-
-                let val = TypedNode::Class {
-                    ty,
-                    name: name.value.into_string(),
-                    properties,
-                    constructor: box TypedNode::Fun {
-                        ty: Type::Fun(params_ty, box Type::Null),
-                        name: "%constructor%".to_string(),
-                        params,
-                        body: box self.annotate(*constructor.1),
-                    },
-                    methods,
-                    static_obj: box static_obj,
-                };
+                        pos,
+                        name: name.value.into_string(),
+                        binding,
+                        properties,
+                        constructor: box TypedNode::Fun {
+                            ty: Type::Fun(params_ty, box body.get_type()),
+                            pos,
+                            name: "%constructor%".to_string(),
+                            binding: BindingId::fresh(),
+                            params,
+                            body: box body,
+                        },
+                        methods,
+                        static_obj: box static_obj,
+                        base,
+                    })
+                });
 
-                self.type_env.pop_scope();
+                self.class_depth -= 1;
+                self.supers.pop();
+                self.resolver.exit_fun();
+                self.pop_scope();
 
-                val
+                val?
+            }
+            Node::InterfaceDefNode { name, methods } => {
+                self.interface_def(name, methods)?;
+                TypedNode::Statements(vec![])
             }
+            Node::EnumDefNode { name, variants } => {
+                self.enum_def(name, variants)?;
+                TypedNode::Statements(vec![])
+            }
+            Node::MatchNode { value, arms } => self.annotate_match(*value, arms, pos)?,
             Node::ClassInitNode {
                 name,
+                type_args,
                 constructor_params,
-            } => TypedNode::ClassInit {
-                ty: Type::fresh_var(),
-                class: self
-                    .class_env
-                    .get(&*name.value.into_string())
-                    .unwrap()
-                    .clone(),
-                constructor_params: constructor_params
-                    .iter()
-                    .map(|x| self.annotate(x.clone()))
-                    .collect(),
-            },
+            } => {
+                let class = self.class_type(name, type_args)?;
+                let constructor_params = self.annotate_all(constructor_params)?;
+                self.callee(pos, &class);
+                self.call(pos, &constructor_params);
+                TypedNode::ClassInit {
+                    ty: Type::fresh_var(),
+                    pos,
+                    class,
+                    constructor_params,
+                }
+            }
             Node::ExternNode {
                 name,
                 arg_tokens,
                 return_type,
                 var_args,
             } => {
-                self.type_env.push_scope();
+                self.push_scope();
+                let name_pos = (name.pos_start, name.pos_end);
                 let name = name.value.into_string();
-                let mut params = vec![];
-                for arg in arg_tokens {
-                    params.push(self.annotate(arg.clone()));
-                }
-
+                let params = self.annotate_all(arg_tokens);
                 let ret = self.annotate(*return_type);
+                self.pop_scope();
 
                 let fun = TypedNode::Extern {
                     ty: Type::fresh_var(),
+                    pos,
                     name: name.clone(),
-                    args: params,
-                    return_type: box ret,
+                    args: params?,
+                    return_type: box ret?,
                     var_args,
                 };
-                self.type_env.pop_scope();
-                self.type_env.set(name, fun.get_type());
+                self.define(&fun.get_type(), name_pos);
+                if let (true, TypedNode::Extern { args, .. }) = (var_args, &fun) {
+                    self.define_variadic(&fun.get_type(), args.len());
+                }
+                self.bind_static(name, fun.get_type());
 
                 fun
             }
             Node::CObject { object } => TypedNode::CObject {
                 ty: Type::fresh_var(),
-                object: box self.annotate(*object),
+                pos,
+                object: box self.annotate(*object)?,
             },
             Node::CToBzxObject { object, bzx_object } => TypedNode::CToBzxObject {
-                ty: self.annotate(*bzx_object).get_type(),
-                object: box self.annotate(*object),
+                ty: self.annotate(*bzx_object)?.get_type(),
+                pos,
+                object: box self.annotate(*object)?,
             },
-            Node::TypeKeyword { token } => match token.value.into_string().as_str() {
-                "int" => TypedNode::Int {
-                    ty: Type::Int,
-                    val: 0,
-                },
-                "float" => TypedNode::Float {
-                    ty: Type::Float,
-                    val: 0.0,
-                },
-                "bool" => TypedNode::Boolean {
+            Node::TypeKeyword { .. } => match self.resolve_type(node)? {
+                ty if ty.is_int() => TypedNode::Int { ty, pos, val: 0 },
+                ty if ty.is_float() => TypedNode::Float { ty, pos, val: 0.0 },
+                Type::Boolean => TypedNode::Boolean {
                     ty: Type::Boolean,
+                    pos,
                     val: false,
                 },
-                "char" => TypedNode::Char {
+                Type::Char => TypedNode::Char {
                     ty: Type::Char,
+                    pos,
                     val: '\0',
                 },
-                "string" => TypedNode::String {
+                Type::String => TypedNode::String {
                     ty: Type::String,
+                    pos,
                     val: String::new(),
                 },
-                "void" => TypedNode::Null { ty: Type::Null },
-                _ => unreachable!(),
+                ty => TypedNode::Null { ty, pos },
+            },
+            Node::CastNode { node, ty } => TypedNode::Cast {
+                ty: self.resolve_type(*ty)?,
+                pos,
+                val: box self.annotate(*node)?,
+            },
+            Node::NullNode { .. } => TypedNode::Null {
+                ty: Type::Nullable(box Type::fresh_var()),
+                pos,
+            },
+            Node::ElvisNode { value, default } => {
+                let ty = Type::fresh_var();
+                TypedNode::Elvis {
+                    ty: ty.clone(),
+                    pos,
+                    val: box self.annotate(*value)?,
+                    default: box {
+                        let default = self.annotate(*default)?;
+                        self.convert(ty, default)
+                    },
+                }
+            }
+            Node::SafeAccessNode {
+                object,
+                name,
+                access,
+            } => {
+                let object = self.annotate(*object)?;
+                self.push_scope();
+                let binding = self.bind(name.value.into_string(), object.get_type());
+                let access = self
+                    .narrow(&name.value.into_string(), object.get_pos())
+                    .and_then(|_| self.annotate(*access));
+                self.pop_scope();
+
+                TypedNode::SafeAccess {
+                    ty: Type::fresh_var(),
+                    pos,
+                    name: name.value.into_string(),
+                    binding,
+                    object: box object,
+                    access: box access?,
+                }
+            }
+            Node::TupleNode { elements } => TypedNode::Tuple {
+                ty: Type::fresh_var(),
+                pos,
+                elements: self.annotate_all(elements)?,
             },
+            Node::TupleAccess { tuple, index } => TypedNode::TupleAccess {
+                ty: Type::fresh_var(),
+                pos,
+                tuple: box self.annotate(*tuple)?,
+                index: match index.value {
+                    Tokens::Int(index) => index as u32,
+                    _ => unreachable!(),
+                },
+            },
+            Node::TupleAssignNode {
+                names,
+                value,
+                reassignable,
+            } => self.destructure(names, *value, reassignable, pos)?,
+            // types of extern parameters and return values, `?` makes them nullable
+            Node::TypeNullable { element } => TypedNode::Null {
+                ty: Type::Nullable(box self.annotate(*element)?.get_type()),
+                pos,
+            },
+            Node::TypeName { .. } | Node::TypeArray { .. } | Node::TypeTuple { .. } => {
+                TypedNode::Null {
+                    ty: self.resolve_type(node)?,
+                    pos,
+                }
+            }
+            // patterns are only annotated along with the arms of their match
+            Node::VariantPattern { .. } => unreachable!(),
+        })
+    }
+
+    /*
+     * Annotates a statement of a block into `stmts`, solving it right away
+     */
+    pub(crate) fn annotate_statement(
+        &mut self,
+        statement: Node,
+        stmts: &mut Vec<TypedNode>,
+    ) -> Result<(), Error> {
+        let stmt = self.annotate(statement)?;
+        /*
+         * Class bodies are solved as a whole so `soul` is bound to the class first
+         */
+        if self.class_depth == 0 {
+            // generic classes instantiated by the statement are defined right before it
+            if self.enclosing.is_empty() {
+                for class in std::mem::take(&mut self.pending_classes) {
+                    self.solve(&class)?;
+                    stmts.push(class);
+                }
+            }
+            self.solve(&stmt)?;
+        }
+        stmts.push(stmt);
+        Ok(())
+    }
+
+    fn annotate_fun(
+        &mut self,
+        name: Option<Token>,
+        type_params: Vec<Token>,
+        args: Vec<(Token, Option<Node>)>,
+        return_type: Option<Node>,
+        body_node: Node,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let declared = name.as_ref().and_then(|tok| self.declared(tok));
+        let ty = match &declared {
+            Some((_, ty)) => ty.clone(),
+            None => Type::fresh_var(),
+        };
+
+        let (name, binding) = if let Some(tok) = name {
+            self.define(&ty, (tok.pos_start, tok.pos_end));
+            let binding = match declared {
+                Some((binding, _)) => self.resolver.rebind(tok.value.into_string(), binding),
+                None => self.bind_static(tok.value.into_string(), ty.clone()),
+            };
+            (tok.value.into_string(), binding)
+        } else {
+            self.define(&ty, pos);
+            ("%anonymous%".to_string(), BindingId::fresh())
+        };
+
+        self.push_scope();
+        self.resolver.enter_fun();
+        self.type_names.push_scope();
+        let params = match &ty {
+            Type::Var(id) => self.type_params.get(id).cloned(),
+            _ => None,
+        }
+        .unwrap_or_else(|| type_params.iter().map(|_| Type::fresh_var()).collect());
+        for (param, var) in type_params.iter().zip(&params) {
+            self.type_names.set(param.value.into_string(), var.clone());
+        }
+        if let (Type::Var(id), false) = (&ty, params.is_empty()) {
+            self.type_params.insert(*id, params);
+        }
+
+        if let Type::Var(id) = ty {
+            self.enclosing.push(id);
+        }
+        let fun = self.annotate_params(args).and_then(|params| {
+            let ret = match return_type {
+                Some(return_type) => Some(self.resolve_type(return_type)?),
+                None => None,
+            };
+
+            self.returns.push(ret.clone());
+            let body = self.annotate(body_node);
+            self.returns.pop();
+
+            let body = match ret {
+                Some(ret) => self.convert(ret, body?),
+                None => body?,
+            };
+            Ok(TypedNode::Fun {
+                ty,
+                pos,
+                name,
+                binding,
+                params,
+                body: box body,
+            })
+        });
+        self.enclosing.pop();
+        self.type_names.pop_scope();
+        self.resolver.exit_fun();
+        self.pop_scope();
+
+        fun
+    }
+
+    /*
+     * Binds the parameters in the current scope, unannotated ones get a fresh type
+     */
+    fn annotate_params(&mut self, args: Vec<(Token, Option<Node>)>) -> Result<Vec<Binder>, Error> {
+        let mut binders = vec![];
+        for (arg, ty) in args {
+            let ty = match ty {
+                Some(ty) => self.resolve_type(ty)?,
+                None => Type::fresh_var(),
+            };
+
+            let binding = self.bind(arg.value.into_string(), ty.clone());
+            binders.push(Binder {
+                ty,
+                name: arg.value.into_string(),
+                binding,
+            });
+        }
+        Ok(binders)
+    }
+
+    /*
+     * Annotates the value of a declaration, checking it against the declared type
+     */
+    fn annotate_value(&mut self, ty: Option<Node>, value: Node) -> Result<TypedNode, Error> {
+        let val = self.annotate(value)?;
+        Ok(match ty {
+            Some(ty) => {
+                let ty = self.resolve_type(ty)?;
+                self.convert(ty, val)
+            }
+            None => val,
+        })
+    }
+
+    /*
+     * Arguments of a call, each one converted to the type of its parameter
+     */
+    fn annotate_args(&mut self, args: Vec<Node>) -> Result<Vec<TypedNode>, Error> {
+        args.into_iter()
+            .map(|arg| {
+                let val = self.annotate(arg)?;
+                Ok(self.convert(Type::fresh_var(), val))
+            })
+            .collect()
+    }
+
+    fn convert(&self, ty: Type, val: TypedNode) -> TypedNode {
+        TypedNode::Convert {
+            ty,
+            pos: val.get_pos(),
+            val: box val,
+        }
+    }
+
+    /*
+     * Annotates a property or static member of a class
+     */
+    fn annotate_member(&mut self, node: Node) -> Result<TypedNode, Error> {
+        match node {
+            Node::VarAssignNode { ty, value, .. } => self.annotate_value(*ty, *value),
+            _ => self.annotate(node),
+        }
+    }
+
+    fn annotate_all(&mut self, nodes: Vec<Node>) -> Result<Vec<TypedNode>, Error> {
+        nodes.into_iter().map(|node| self.annotate(node)).collect()
+    }
+
+    fn annotate_class_body(
+        &mut self,
+        mthds: Vec<(Token, Node)>,
+        methods: &mut BTreeMap<String, TypedNode>,
+    ) -> Result<(), Error> {
+        for (name, method) in mthds {
+            let pos = (name.pos_start, name.pos_end);
+            if let Node::FunDef {
+                type_params,
+                args,
+                return_type,
+                body_node,
+                ..
+            } = method
+            {
+                methods.insert(
+                    name.value.into_string(),
+                    self.annotate_fun(
+                        Some(name),
+                        type_params,
+                        args,
+                        *return_type,
+                        *body_node,
+                        pos,
+                    )?,
+                );
+            }
         }
+        Ok(())
     }
 }
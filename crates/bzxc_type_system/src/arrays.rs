@@ -0,0 +1,83 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::BTreeSet;
+
+use bzxc_shared::{Error, Position, Type};
+
+use crate::generics::free_vars;
+use crate::{type_error, TypeSystem};
+
+impl TypeSystem {
+    /*
+     * `[]` holds elements of a type only its uses tell, the literal is typed as
+     * an array of a fresh type checked once the whole program is solved
+     */
+    pub(crate) fn empty_array(&mut self, pos: (Position, Position)) -> Type {
+        let element = Type::fresh_var();
+        self.empty_arrays.push((element.clone(), pos));
+        Type::Array(box element, 0)
+    }
+
+    /*
+     * Reports the empty arrays nothing gave a type of elements to,
+     * the ones in generic functions are typed by their instantiations
+     */
+    pub(crate) fn check_empty_arrays(&self) -> Result<(), Error> {
+        let generic = self
+            .generics
+            .values()
+            .flat_map(|generic| generic.vars.iter())
+            .map(|var| self.subst.apply_ty(Type::Var(*var)))
+            .collect::<Vec<_>>();
+
+        for (element, pos) in &self.empty_arrays {
+            let mut vars = BTreeSet::new();
+            free_vars(&self.subst.apply_ty(element.clone()), &mut vars);
+            if vars.iter().any(|var| !generic.contains(&Type::Var(*var))) {
+                return Err(type_error(
+                    "Missing Type",
+                    *pos,
+                    "the type of the elements of the array can't be known, annotate it like `[int]`"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /*
+     * Arrays of a length only take arrays of that length, `[T]` takes arrays of any length
+     */
+    pub(crate) fn check_length(
+        &self,
+        expected: &Type,
+        found: &Type,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        match (expected, found) {
+            (Type::Array(_, expected_len), Type::Array(_, found_len))
+                if *expected_len != 0 && *found_len != 0 && expected_len != found_len =>
+            {
+                Err(type_error(
+                    "Mismatched Types",
+                    pos,
+                    format!(
+                        "expected an array of {} elements, found one of {}",
+                        expected_len, found_len
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
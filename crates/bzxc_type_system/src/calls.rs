@@ -0,0 +1,159 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::{HashMap, HashSet};
+
+use bzxc_shared::{Error, Position, Type, TypedNode};
+
+use crate::{type_error, TypeSystem};
+
+type Span = (Position, Position);
+
+/*
+ * Where the things called are defined, so the errors of a call can point at them
+ */
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Calls {
+    // functions, externs and classes by their type variable
+    definitions: HashMap<i32, Span>,
+    // methods and constructors by the id of their class object
+    members: HashMap<(u32, String), Span>,
+    // every call, even the ones whose definition isn't known
+    sites: HashSet<Span>,
+    // the call each argument is passed to
+    arguments: HashMap<Span, Span>,
+    // the definition each call refers to
+    callees: HashMap<Span, Span>,
+    // number of parameters before the `...` of variadic externs
+    variadics: HashMap<i32, usize>,
+}
+
+impl TypeSystem {
+    pub(crate) fn define(&mut self, ty: &Type, pos: Span) {
+        if let Type::Var(id) | Type::Class(box Type::Var(id)) = ty {
+            self.calls.definitions.insert(*id, pos);
+        }
+    }
+
+    /*
+     * Methods are defined at their name, constructors at the name of the class
+     */
+    pub(crate) fn define_member(&mut self, id: u32, name: String, pos: Span) {
+        self.calls.members.insert((id, name), pos);
+    }
+
+    /*
+     * Methods a class doesn't override are defined in its base
+     */
+    pub(crate) fn inherit_members(&mut self, id: u32, base: u32) {
+        let inherited = self
+            .calls
+            .members
+            .iter()
+            .filter(|((class, name), _)| *class == base && name != "constructor")
+            .map(|((_, name), pos)| ((id, name.clone()), *pos))
+            .collect::<Vec<_>>();
+        for (member, pos) in inherited {
+            self.calls.members.entry(member).or_insert(pos);
+        }
+    }
+
+    pub(crate) fn definition(&self, ty: &Type) -> Option<Span> {
+        match ty {
+            Type::Var(id) | Type::Class(box Type::Var(id)) => {
+                self.calls.definitions.get(id).cloned()
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn define_variadic(&mut self, ty: &Type, params: usize) {
+        if let Type::Var(id) = ty {
+            self.calls.variadics.insert(*id, params);
+        }
+    }
+
+    /*
+     * Remembers the arguments passed to the call at `pos`
+     */
+    pub(crate) fn call(&mut self, pos: Span, args: &[TypedNode]) {
+        self.calls.sites.insert(pos);
+        for arg in args {
+            self.calls.arguments.entry(arg.get_pos()).or_insert(pos);
+        }
+    }
+
+    /*
+     * Remembers the call at `pos` refers to the definition of `callee`, if it's known
+     */
+    pub(crate) fn callee(&mut self, pos: Span, callee: &Type) {
+        if let Some(definition) = self.definition(callee) {
+            self.calls.callees.insert(pos, definition);
+        }
+    }
+
+    /*
+     * Same as `callee` for a method, only known once the type of the object is
+     */
+    pub(crate) fn callee_member(&mut self, id: u32, name: &str, pos: Span) {
+        if let Some(definition) = self.calls.members.get(&(id, name.to_string())).cloned() {
+            self.calls.callees.insert(pos, definition);
+        }
+    }
+
+    /*
+     * Arguments matched against the parameters of a variadic extern, the rest aren't checked
+     */
+    pub(crate) fn variadic_args(&self, fun: &Type, mut args: Vec<Type>) -> Vec<Type> {
+        if let Type::Var(id) = fun {
+            if let Some(params) = self.calls.variadics.get(id) {
+                args.truncate(*params);
+            }
+        }
+        args
+    }
+
+    pub(crate) fn check_arity(&self, params: usize, args: usize, pos: Span) -> Result<(), Error> {
+        if params == args {
+            return Ok(());
+        }
+
+        Err(type_error(
+            "Invalid Arguments",
+            pos,
+            format!(
+                "expected {} argument{}, found {}",
+                params,
+                if params == 1 { "" } else { "s" },
+                args
+            ),
+        ))
+    }
+
+    /*
+     * Points an error about a call or one of its arguments at the definition called
+     */
+    pub(crate) fn defined_here(&self, error: Error) -> Error {
+        let pos = (error.pos_start, error.pos_end);
+        // an argument which is a call itself is about what it calls
+        let call = match self.calls.arguments.get(&pos) {
+            Some(call) if !self.calls.sites.contains(&pos) => call,
+            _ => &pos,
+        };
+        match self.calls.callees.get(call) {
+            Some(definition) if error.labels.is_empty() => {
+                error.with_label(definition.0, definition.1, "defined here")
+            }
+            _ => error,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{Error, LLVMNode, Position, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::numeric::Numeric;
+use crate::substitution::Substitution;
+use crate::{type_error, TypeSystem};
+
+/*
+ * Operands of a binary node, typed once the types of both are known
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct Operands {
+    pub ty: Type,
+    pub left: Type,
+    pub right: Type,
+    pub arithmetic: bool,
+    pub pos: (Position, Position),
+}
+
+/*
+ * The operand of a binary node converted to the float type of the other one
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Promote {
+    Left,
+    Right,
+}
+
+impl TypeSystem {
+    /*
+     * Operands of the same type are left as they are, when an integer meets a float
+     * the integer is promoted to the type of the float, anything else is a mismatch
+     */
+    pub(crate) fn solve_operands(&mut self, operands: Operands) -> Result<(), Error> {
+        let left = self.subst.apply_ty(operands.left);
+        let right = self.subst.apply_ty(operands.right);
+
+        let promote = match (self.kind(&left), self.kind(&right)) {
+            (Some(Numeric::Int), Some(Numeric::Float)) => Some(Promote::Left),
+            (Some(Numeric::Float), Some(Numeric::Int)) => Some(Promote::Right),
+            _ => None,
+        };
+
+        let result = match (promote, &operands.ty) {
+            (Some(promote), Type::Var(id)) => {
+                self.promotions.insert(*id, promote);
+                match promote {
+                    Promote::Left => right,
+                    Promote::Right => left,
+                }
+            }
+            _ => {
+                let constraint = Constraint(left.clone(), right, operands.pos);
+                let substitution = self.unify_one(constraint)?;
+                self.subst = self.subst.compose(substitution);
+                left
+            }
+        };
+
+        let result = if operands.arithmetic {
+            result
+        } else {
+            Type::Boolean
+        };
+        let constraint = self.subst.apply(vec![Constraint(operands.ty, result, operands.pos)]);
+        let substitution = self.unify(constraint)?;
+        self.subst = self.subst.compose(substitution);
+        Ok(())
+    }
+
+    /*
+     * Checks the value of an `as` cast can be converted to its type
+     */
+    pub(crate) fn check_cast(&mut self, cast: Constraint) -> Result<(), Error> {
+        let to = cast.0;
+        let from = self.subst.apply_ty(cast.1);
+
+        // values are boxed into `any` and checked to hold the type when unboxed
+        if from == to || from == Type::Any || to == Type::Any {
+            return Ok(());
+        }
+
+        let castable = |ty: &Type| ty.is_int() || ty.is_float() || *ty == Type::Char;
+        match from {
+            Type::Var(_) if self.numeric(&from).is_none() => Err(type_error(
+                "Invalid Cast",
+                cast.2,
+                format!("the type of the value must be known to cast it to `{}`", to),
+            )),
+            _ if (castable(&from) || from == Type::Boolean || self.numeric(&from).is_some())
+                && castable(&to) =>
+            {
+                Ok(())
+            }
+            _ => Err(type_error(
+                "Invalid Cast",
+                cast.2,
+                format!("cannot cast `{}` to `{}`", from, to),
+            )),
+        }
+    }
+
+    fn kind(&self, ty: &Type) -> Option<Numeric> {
+        if ty.is_int() {
+            Some(Numeric::Int)
+        } else if ty.is_float() {
+            Some(Numeric::Float)
+        } else {
+            self.numeric(ty)
+        }
+    }
+
+    /*
+     * Converts `val` to `ty`, values already of that type are left as they are,
+     * the ones from and to `any` are unboxed and boxed
+     */
+    pub(crate) fn gen_cast(&self, subs: Substitution, ty: Type, val: TypedNode) -> LLVMNode {
+        let to = Self::concrete(subs.apply_ty(ty.clone()));
+        let from = Self::concrete(subs.apply_ty(val.get_type()));
+        if from == Type::Any || to == Type::Any {
+            return self.gen_convert(subs, ty, val);
+        }
+        let val = box self.gen(subs.clone(), val);
+
+        if from == to {
+            *val
+        } else {
+            LLVMNode::Cast {
+                ty: unsafe { to.llvm(self.context, subs.0) },
+                val,
+                from_signed: from.is_signed(),
+                to_signed: to.is_signed(),
+            }
+        }
+    }
+
+    /*
+     * Type both operands of the binary node typed `ty` have once the promoted one is converted
+     */
+    pub(crate) fn operand_type(
+        &self,
+        subs: &Substitution,
+        ty: &Type,
+        left: &TypedNode,
+        right: &TypedNode,
+    ) -> Type {
+        let operand = match ty {
+            Type::Var(id) if self.promotions.get(id) == Some(&Promote::Left) => right.get_type(),
+            _ => left.get_type(),
+        };
+        Self::concrete(subs.apply_ty(operand))
+    }
+
+    /*
+     * Operands of the binary node typed `ty`, the promoted one converted
+     */
+    pub(crate) fn gen_operands(
+        &self,
+        subs: Substitution,
+        ty: &Type,
+        left: TypedNode,
+        right: TypedNode,
+    ) -> (LLVMNode, LLVMNode) {
+        let promote = match ty {
+            Type::Var(id) => self.promotions.get(id).cloned(),
+            _ => None,
+        };
+
+        match promote {
+            Some(Promote::Left) => (
+                self.gen_cast(subs.clone(), right.get_type(), left),
+                self.gen(subs, right),
+            ),
+            Some(Promote::Right) => (
+                self.gen(subs.clone(), left.clone()),
+                self.gen_cast(subs, left.get_type(), right),
+            ),
+            None => (self.gen(subs.clone(), left), self.gen(subs, right)),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{Error, LLVMNode, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::substitution::Substitution;
+use crate::TypeSystem;
+
+impl TypeSystem {
+    /*
+     * Checks a value flowing into a place of the expected type,
+     * `any` takes every known type and is taken by every known type,
+     * nullable types take values of the type they wrap
+     */
+    pub(crate) fn coerce(&mut self, coercion: Constraint) -> Result<(), Error> {
+        let expected = self.subst.apply_ty(coercion.0.clone());
+        let found = self.subst.apply_ty(coercion.1.clone());
+
+        // literals flowing through `any` are boxed with their default width
+        let (expected, found) = match (expected, found) {
+            (Type::Any, found) => (Type::Any, self.decide_numeric(found)),
+            (expected, Type::Any) => (self.decide_numeric(expected), Type::Any),
+            types => types,
+        };
+
+        match (&expected, &found) {
+            (Type::Any, Type::Var(_)) | (Type::Var(_), Type::Any) => (),
+            (Type::Any, _) | (_, Type::Any) => return Ok(()),
+            _ => (),
+        }
+
+        match (&expected, &found) {
+            (Type::Nullable(_), Type::Nullable(_)) => (),
+            (Type::Nullable(_), Type::Var(_)) if self.numeric(&found).is_none() => (),
+            (Type::Nullable(ty), _) => {
+                return self.coerce(Constraint(*ty.clone(), found, coercion.2));
+            }
+            _ => (),
+        }
+
+        if let (Type::Interface(interface), Type::Object(..)) = (&expected, &found) {
+            return self.check_implements(interface, &found, coercion.2);
+        }
+        self.check_extends(&expected, &found, coercion.2)?;
+        self.check_length(&expected, &found, coercion.2)?;
+        let substitution = self.unify(vec![Constraint(expected, found, coercion.2)])?;
+        self.subst = self.subst.compose(substitution);
+        Ok(())
+    }
+
+    /*
+     * Boxes values flowing into `any` and unboxes the ones flowing out of it
+     */
+    pub(crate) fn gen_convert(&self, subs: Substitution, ty: Type, val: TypedNode) -> LLVMNode {
+        let to = Self::concrete(subs.apply_ty(ty));
+        let from = Self::concrete(subs.apply_ty(val.get_type()));
+        let val = self.gen(subs.clone(), val);
+        self.convert_value(&subs, to, from, val)
+    }
+
+    fn convert_value(&self, subs: &Substitution, to: Type, from: Type, val: LLVMNode) -> LLVMNode {
+        let llvm = |ty: &Type| unsafe {
+            self.instantiated(subs, ty.clone())
+                .llvm(self.context, subs.0.clone())
+        };
+        let empty = matches!(&val, LLVMNode::Array { elements, .. } if elements.is_empty());
+        let val = box val;
+
+        match (&to, &from) {
+            (Type::Nullable(_), Type::Nullable(_)) => *val,
+            (Type::Nullable(ty), _) => LLVMNode::Wrap {
+                ty: llvm(&to),
+                val: box self.convert_value(subs, *ty.clone(), from, *val),
+            },
+            (Type::Any, Type::Any) => *val,
+            (Type::Any, _) => LLVMNode::BoxAny {
+                ty: llvm(&from),
+                tag: self.type_tag(&from),
+                type_name: self.type_name(&from),
+                val,
+            },
+            (_, Type::Any) => LLVMNode::UnboxAny {
+                ty: llvm(&to),
+                tag: self.type_tag(&to),
+                type_name: self.type_name(&to),
+                val,
+            },
+            (Type::Interface(name), Type::Object(..)) => LLVMNode::Interface {
+                ty: llvm(&to),
+                name: name.clone(),
+                methods: self.interfaces[name].keys().cloned().collect(),
+                val,
+            },
+            // `[]` flowing into an array of a length is allocated with that length
+            (Type::Array(..), Type::Array(..)) if empty => LLVMNode::Array {
+                ty: llvm(&to),
+                elements: vec![],
+            },
+            // arrays of a length flowing into `[T]`
+            (Type::Array(..), Type::Array(..)) if to != from => LLVMNode::Cast {
+                ty: llvm(&to),
+                val,
+                from_signed: false,
+                to_signed: false,
+            },
+            // objects of a class flowing into a place of its base class
+            (Type::Object(..), Type::Object(..)) if to != from => LLVMNode::Cast {
+                ty: llvm(&to),
+                val,
+                from_signed: false,
+                to_signed: false,
+            },
+            _ => *val,
+        }
+    }
+
+    /*
+     * Replaces the type variables inference couldn't decide on with `any`
+     */
+    pub(crate) fn concrete(ty: Type) -> Type {
+        match ty {
+            Type::Var(_) => Type::Any,
+            Type::Fun(params, ret) => Type::Fun(
+                params.into_iter().map(Self::concrete).collect(),
+                box Self::concrete(*ret),
+            ),
+            Type::Array(ty, len) => Type::Array(box Self::concrete(*ty), len),
+            Type::Class(obj) => Type::Class(box Self::concrete(*obj)),
+            Type::Nullable(ty) => Type::Nullable(box Self::concrete(*ty)),
+            Type::Tuple(elements) => {
+                Type::Tuple(elements.into_iter().map(Self::concrete).collect())
+            }
+            Type::Object(id, tree) => Type::Object(
+                id,
+                tree.into_iter()
+                    .map(|(name, ty)| (name, Self::concrete(ty)))
+                    .collect(),
+            ),
+            _ => ty,
+        }
+    }
+
+    /*
+     * Runtime tag of a boxed type, FNV-1a of its name so it is stable across compilations
+     */
+    fn type_tag(&self, ty: &Type) -> u32 {
+        self.type_name(ty).bytes().fold(0x811c9dc5, |hash, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x01000193)
+        })
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::{BTreeMap, HashMap};
 
-use bzxc_shared::{Tokens, Type, TypedNode};
+use bzxc_shared::{Position, Tokens, Type, TypedNode};
 
+use crate::cast::Operands;
+use crate::numeric::Numeric;
+use crate::tuple::Access;
 use crate::TypeSystem;
 
 /*
@@ -16,31 +19,40 @@ use crate::TypeSystem;
  * See the License for the specific language governing permissions and
  * limitations under the License.
 */
+/*
+ * Two types which must be equal, along with the span of the node which demands it
+ */
 #[derive(Debug, Clone)]
-pub struct Constraint(pub Type, pub Type);
+pub struct Constraint(pub Type, pub Type, pub (Position, Position));
 
 impl TypeSystem {
     pub(crate) fn collect(&mut self, node: TypedNode) -> Vec<Constraint> {
+        let pos = node.get_pos();
         match node {
             TypedNode::Statements(stmts) => stmts
                 .iter()
                 .map(|x| self.collect(x.clone()))
                 .collect::<Vec<Vec<Constraint>>>()
                 .concat(),
-            TypedNode::Int { ty, .. } => {
-                vec![Constraint(ty, Type::Int)]
-            }
-            TypedNode::Float { ty, .. } => {
-                vec![Constraint(ty, Type::Float)]
-            }
+            // literals are typed by their numeric variable
+            TypedNode::Int { .. } | TypedNode::Float { .. } => vec![],
             TypedNode::Boolean { ty, .. } => {
-                vec![Constraint(ty, Type::Boolean)]
+                vec![Constraint(ty, Type::Boolean, pos)]
+            }
+            TypedNode::Char { ty, .. } => vec![Constraint(ty, Type::Char, pos)],
+            TypedNode::Var {
+                ty: Type::Var(id), ..
+            } if self.instances.contains_key(&id) => {
+                vec![Constraint(
+                    Type::Var(id),
+                    self.instances[&id].ty.clone(),
+                    pos,
+                )]
             }
-            TypedNode::Char { ty, .. } => vec![Constraint(ty, Type::Char)],
-            TypedNode::String { ty, .. } => vec![Constraint(ty, Type::String)],
+            TypedNode::String { ty, .. } => vec![Constraint(ty, Type::String, pos)],
             TypedNode::Unary { ty, val, .. } => {
                 let mut constr = self.collect(*val.clone());
-                constr.push(Constraint(ty, val.get_type()));
+                constr.push(Constraint(ty, val.get_type(), pos));
                 constr
             }
             TypedNode::Binary {
@@ -48,32 +60,36 @@ impl TypeSystem {
                 left,
                 right,
                 op_token,
+                ..
             } => {
                 let mut constr = self.collect(*left.clone());
                 constr.extend(self.collect(*right.clone()));
-                constr.push(Constraint(left.get_type(), right.get_type()));
-                constr.push(Constraint(
-                    ty.clone(),
-                    match op_token.value {
+                // typed after the operands are known, integers meeting floats are promoted
+                self.operands.push(Operands {
+                    ty,
+                    left: left.get_type(),
+                    right: right.get_type(),
+                    arithmetic: matches!(
+                        op_token.value,
                         Tokens::Plus
-                        | Tokens::Minus
-                        | Tokens::Multiply
-                        | Tokens::Divide
-                        | Tokens::Modulo => left.get_type(),
-                        _ => Type::Boolean,
-                    },
-                ));
+                            | Tokens::Minus
+                            | Tokens::Multiply
+                            | Tokens::Divide
+                            | Tokens::Modulo
+                    ),
+                    pos,
+                });
                 constr
             }
             TypedNode::Let { ty, val, .. } => {
                 let mut constr = self.collect(*val.clone());
-                constr.push(Constraint(ty, val.get_type()));
+                constr.push(Constraint(ty, val.get_type(), pos));
                 constr
             }
             TypedNode::ReLet { ty, prev, val, .. } => {
                 let mut constr = self.collect(*val.clone());
-                constr.push(Constraint(prev.clone(), val.get_type()));
-                constr.push(Constraint(ty, prev));
+                constr.push(Constraint(prev.clone(), val.get_type(), pos));
+                constr.push(Constraint(ty, prev, pos));
                 constr
             }
             TypedNode::Fun {
@@ -86,6 +102,7 @@ impl TypeSystem {
                         params.iter().map(|x| x.ty.clone()).collect(),
                         box body.get_type(),
                     ),
+                    pos,
                 ));
 
                 constr
@@ -109,41 +126,59 @@ impl TypeSystem {
                 constr.push(Constraint(
                     ty,
                     Type::Fun(param_ty, box return_type.get_type().clone()),
+                    pos,
                 ));
 
                 constr
             }
-            TypedNode::Call { ty, fun, args } => {
+            TypedNode::Call { ty, fun, args, .. } => {
                 let mut constr = self.collect(*fun.clone());
                 let mut args_ty = vec![];
                 for arg in args.clone() {
                     constr.extend(self.collect(arg.clone()));
                     args_ty.push(arg.get_type());
                 }
-                constr.push(Constraint(fun.get_type(), Type::Fun(args_ty, box ty)));
+                let args_ty = self.variadic_args(&fun.get_type(), args_ty);
+                constr.push(Constraint(fun.get_type(), Type::Fun(args_ty, box ty), pos));
                 constr
             }
-            TypedNode::Return { ty, val } => {
+            TypedNode::Return { ty, val, .. } => {
                 let mut constr = self.collect(*val.clone());
-                constr.push(Constraint(ty, val.get_type()));
+                constr.push(Constraint(ty, val.get_type(), pos));
                 constr
             }
             TypedNode::If {
                 ty,
                 cases,
                 else_case,
+                ..
             } => {
                 let mut constr = vec![];
+                let mut branches = vec![];
                 for (cond, body) in cases {
                     constr.extend(self.collect(cond.clone()));
-                    constr.push(Constraint(Type::Boolean, cond.get_type()));
+                    constr.push(Constraint(Type::Boolean, cond.get_type(), cond.get_pos()));
                     constr.extend(self.collect(body.clone()));
-                    constr.push(Constraint(ty.clone(), body.get_type()));
+                    branches.push(Constraint(ty.clone(), body.get_type(), body.get_pos()));
                 }
 
                 if let Some(tn) = else_case {
-                    constr.push(Constraint(ty.clone(), tn.get_type()));
+                    branches.push(Constraint(ty.clone(), tn.get_type(), tn.get_pos()));
                     constr.extend(self.collect(*tn));
+
+                    // the other branches of an `any` one are boxed, see `coerce`
+                    let any = branches
+                        .iter()
+                        .any(|branch| self.subst.apply_ty(branch.1.clone()) == Type::Any);
+                    if any {
+                        constr.push(Constraint(ty, Type::Any, pos));
+                        self.coercions.extend(branches);
+                    } else {
+                        constr.extend(branches);
+                    }
+                } else {
+                    // without an else branch there is nothing to produce when no case matches
+                    constr.push(Constraint(ty.clone(), Type::Null, pos));
                 }
 
                 return constr;
@@ -157,76 +192,86 @@ impl TypeSystem {
                 ..
             } => {
                 let mut constr = self.collect(*start.clone());
-                constr.push(Constraint(ty, body.get_type()));
-                constr.push(Constraint(start.get_type(), end.get_type()));
-                constr.push(Constraint(start.get_type(), step.get_type()));
+                constr.push(Constraint(ty, Type::Null, pos));
+                let int = self.numeric_var(Numeric::Int);
+                constr.push(Constraint(int, start.get_type(), start.get_pos()));
+                constr.push(Constraint(start.get_type(), end.get_type(), pos));
+                constr.push(Constraint(start.get_type(), step.get_type(), pos));
                 constr.extend(self.collect(*end));
                 constr.extend(self.collect(*step));
                 constr.extend(self.collect(*body));
                 constr
             }
-            TypedNode::While { ty, cond, body } => {
+            TypedNode::While { ty, cond, body, .. } => {
                 let mut constr = self.collect(*body.clone());
-                constr.push(Constraint(ty, body.get_type()));
+                constr.push(Constraint(ty, Type::Null, pos));
                 constr.extend(self.collect(*cond.clone()));
-                constr.push(Constraint(Type::Boolean, cond.get_type()));
+                constr.push(Constraint(Type::Boolean, cond.get_type(), cond.get_pos()));
                 constr
             }
-            TypedNode::Array { ty, elements } => {
-                let elem_ty = if let Some(elem) = elements.first() {
-                    elem.get_type()
-                } else {
-                    Type::Null
+            TypedNode::Array { ty, elements, .. } => {
+                // empty arrays are typed when they're annotated
+                let elem_ty = match elements.first() {
+                    Some(elem) => elem.get_type(),
+                    None => return vec![],
                 };
                 let mut constr = self.collect(elements.first().unwrap().clone());
 
                 for element in elements.iter().skip(1).collect::<Vec<&TypedNode>>() {
-                    constr.push(Constraint(elem_ty.clone(), element.get_type()));
+                    constr.push(Constraint(
+                        elem_ty.clone(),
+                        element.get_type(),
+                        element.get_pos(),
+                    ));
                     constr.extend(self.collect(element.clone()));
                 }
 
                 constr.push(Constraint(
                     ty.clone(),
                     Type::Array(box elem_ty.clone(), elements.len() as u32),
+                    pos,
                 ));
                 constr
             }
-            TypedNode::Index { ty, array, idx } => {
+            TypedNode::Index { ty, array, idx, .. } => {
                 let mut constr = self.collect(*array.clone());
                 constr.extend(self.collect(*idx.clone()));
-                constr.push(Constraint(idx.get_type(), Type::Int));
-                constr.push(Constraint(array.get_type(), Type::Array(box ty, 0)));
+                let int = self.numeric_var(Numeric::Int);
+                constr.push(Constraint(int, idx.get_type(), idx.get_pos()));
+                constr.push(Constraint(array.get_type(), Type::Array(box ty, 0), pos));
                 constr
             }
-            TypedNode::Object { ty, properties } => {
+            TypedNode::Object { ty, properties, .. } => {
                 let mut constr = vec![];
                 let mut tree = BTreeMap::new();
                 for (name, node) in properties {
                     constr.extend(self.collect(node.clone()));
                     tree.insert(name.clone(), node.get_type());
                 }
-                constr.push(Constraint(ty, Type::create_obj(tree)));
+                constr.push(Constraint(ty, Type::create_obj(tree), pos));
                 constr
             }
-            TypedNode::CObject { ty, object } => {
+            TypedNode::CObject { ty, object, .. } => {
                 let mut constr = self.collect(*object.clone());
-                constr.push(Constraint(ty, object.get_type()));
+                constr.push(Constraint(ty, object.get_type(), pos));
                 constr
             }
-            TypedNode::CToBzxObject { ty, object } => {
+            TypedNode::CToBzxObject { ty, object, .. } => {
                 let mut constr = self.collect(*object.clone());
-                constr.push(Constraint(ty, object.get_type()));
+                constr.push(Constraint(ty, object.get_type(), pos));
                 constr
             }
             TypedNode::ObjectAccess {
                 ty,
                 property,
                 object,
+                ..
             } => {
                 let mut constr = self.collect(*object.clone());
                 constr.push(Constraint(
                     object.get_type(),
-                    Type::Object(BTreeMap::from([(property, ty)])),
+                    Type::Object(None, BTreeMap::from([(property, ty)])),
+                    pos,
                 ));
                 constr
             }
@@ -235,13 +280,15 @@ impl TypeSystem {
                 property,
                 object,
                 new_val,
+                ..
             } => {
                 let mut constr = self.collect(*object.clone());
                 constr.extend(self.collect(*new_val.clone()));
-                constr.push(Constraint(ty.clone(), new_val.get_type()));
+                constr.push(Constraint(ty.clone(), new_val.get_type(), pos));
                 constr.push(Constraint(
                     object.get_type(),
-                    Type::Object(BTreeMap::from([(property, new_val.get_type())])),
+                    Type::Object(None, BTreeMap::from([(property, new_val.get_type())])),
+                    pos,
                 ));
                 constr
             }
@@ -250,6 +297,7 @@ impl TypeSystem {
                 object,
                 args,
                 property,
+                ..
             } => {
                 let mut constr = self.collect(*object.clone());
                 let mut args_ty = vec![];
@@ -259,7 +307,11 @@ impl TypeSystem {
                 }
                 constr.push(Constraint(
                     object.get_type(),
-                    Type::Object(BTreeMap::from([(property, Type::Fun(args_ty, box ty))])),
+                    Type::Object(
+                        None,
+                        BTreeMap::from([(property, Type::Fun(args_ty, box ty))]),
+                    ),
+                    pos,
                 ));
                 constr
             }
@@ -268,33 +320,48 @@ impl TypeSystem {
                 constructor,
                 methods,
                 properties,
-                name: _,
+                name,
                 static_obj,
+                base,
+                ..
             } => {
                 let mut constr = self.collect(*static_obj.clone());
                 let mut tree = BTreeMap::new();
 
-                let mut methods_tree = HashMap::new();
-                methods_tree.insert("constructor".to_string(), constructor.get_type());
-
-                for (name, val) in properties {
+                for (name, val) in &properties {
                     tree.insert(name.clone(), val.get_type());
                     constr.extend(self.collect(val.clone()));
                 }
 
-                let obj = Type::create_obj(tree);
+                let base = base.map(|base| self.subst.apply_ty(base));
+                let mut methods_tree = match &base {
+                    Some(base) => self.inherit(&name, base, &properties, &methods, &mut constr),
+                    None => HashMap::new(),
+                };
+                methods_tree.insert("constructor".to_string(), constructor.get_type());
+
+                let obj = Type::create_class_obj(tree, base.as_ref());
+                let id = obj.id().unwrap();
+                if let Some(definition) = self.definition(&ty) {
+                    self.define_member(id, "constructor".to_string(), definition);
+                }
+                for (name, val) in &methods {
+                    self.define_member(id, name.clone(), val.get_pos());
+                }
+                if let Some(base) = base.as_ref().and_then(Type::id) {
+                    self.inherit_members(id, base);
+                }
 
-                constr.push(Constraint(ty, Type::Class(box obj)));
+                constr.push(Constraint(ty, Type::Class(box obj), pos));
 
                 constr.extend(self.collect(*constructor.clone()));
                 for (name, val) in methods {
                     methods_tree.insert(name.clone(), val.get_type());
                     constr.extend(self.collect(val.clone()));
                 }
-                self.methods.insert(
-                    Type::Array(box Type::Int, Type::last_aligner() as u32),
-                    methods_tree,
-                );
+                constr.extend(self.conform(&name, &methods_tree, pos));
+                self.methods.insert(id, methods_tree);
+                self.class_names.insert(id, name);
 
                 constr
             }
@@ -302,6 +369,7 @@ impl TypeSystem {
                 ty,
                 class,
                 constructor_params,
+                ..
             } => {
                 let mut constr = vec![];
                 let mut params = vec![];
@@ -311,10 +379,92 @@ impl TypeSystem {
                     params.push(param.get_type());
                 }
 
-                constr.push(Constraint(Type::Class(box ty.clone()), class));
+                constr.push(Constraint(Type::Class(box ty.clone()), class.clone(), pos));
+                // the arguments are checked against the constructor found among the methods
+                let constructor = Type::Fun(params, box Type::fresh_var());
+                constr.push(Constraint(
+                    class,
+                    Type::Class(box Type::Object(
+                        None,
+                        BTreeMap::from([("constructor".to_string(), constructor)]),
+                    )),
+                    pos,
+                ));
 
                 constr
             }
+            TypedNode::Cast { ty, val, .. } => {
+                self.casts
+                    .push(Constraint(ty, val.get_type(), val.get_pos()));
+                self.collect(*val)
+            }
+            TypedNode::NullCheck { val, .. } => {
+                let mut constr = self.collect(*val.clone());
+                constr.push(Constraint(
+                    Type::Nullable(box Type::fresh_var()),
+                    val.get_type(),
+                    pos,
+                ));
+                constr
+            }
+            TypedNode::Unwrap { ty, val, .. } => {
+                vec![Constraint(val.get_type(), Type::Nullable(box ty), pos)]
+            }
+            TypedNode::SafeAccess {
+                ty, object, access, ..
+            } => {
+                let mut constr = self.collect(*object);
+                constr.extend(self.collect(*access.clone()));
+                constr.push(Constraint(ty, Type::Nullable(box access.get_type()), pos));
+                constr
+            }
+            TypedNode::Elvis {
+                ty, val, default, ..
+            } => {
+                let mut constr = self.collect(*val.clone());
+                constr.extend(self.collect(*default));
+                constr.push(Constraint(val.get_type(), Type::Nullable(box ty), pos));
+                constr
+            }
+            TypedNode::Variant { payload, .. } => payload
+                .into_iter()
+                .flat_map(|val| self.collect(val))
+                .collect(),
+            TypedNode::Match { ty, val, arms, .. } => {
+                let mut constr = self.collect(*val);
+                for (_, _, body) in arms {
+                    constr.push(Constraint(ty.clone(), body.get_type(), body.get_pos()));
+                    constr.extend(self.collect(body));
+                }
+                constr
+            }
+            TypedNode::Tuple { ty, elements, .. } => {
+                let mut constr = vec![Constraint(
+                    ty,
+                    Type::Tuple(elements.iter().map(|val| val.get_type()).collect()),
+                    pos,
+                )];
+                constr.extend(elements.into_iter().flat_map(|val| self.collect(val)));
+                constr
+            }
+            TypedNode::TupleAccess {
+                ty, tuple, index, ..
+            } => {
+                // typed once the type of the tuple is known, see `solve_accesses`
+                self.tuple_accesses.push(Access {
+                    tuple: tuple.get_type(),
+                    index,
+                    ty,
+                    pos,
+                });
+                self.collect(*tuple)
+            }
+            TypedNode::Convert { ty, val, .. } => {
+                // checked once the types on both ends are known, see `coerce`
+                self.coercions
+                    .push(Constraint(ty, val.get_type(), val.get_pos()));
+                self.collect(*val)
+            }
             _ => vec![],
         }
     }
@@ -0,0 +1,391 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::convert::TryFrom;
+
+use bzxc_shared::{fold_children, Error, Fold, Position, Token, Tokens, Type, TypedNode};
+
+use crate::{type_error, TypeSystem};
+
+/*
+ * Value of an expression known at compile time
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Const {
+    Int(i128),
+    Float(f64),
+    Boolean(bool),
+    Char(char),
+    String(String),
+}
+
+impl Const {
+    /*
+     * Literal of the value, typed `ty`
+     */
+    fn literal(self, ty: Type, pos: (Position, Position)) -> TypedNode {
+        match self {
+            Const::Int(val) => TypedNode::Int { ty, pos, val },
+            Const::Float(val) => TypedNode::Float { ty, pos, val },
+            Const::Boolean(val) => TypedNode::Boolean { ty, pos, val },
+            Const::Char(val) => TypedNode::Char { ty, pos, val },
+            Const::String(val) => TypedNode::String { ty, pos, val },
+        }
+    }
+}
+
+/*
+ * Bits of the integer types, their values wrap around like the ones computed at runtime
+ */
+fn bits(ty: &Type) -> Option<u32> {
+    match ty {
+        Type::I8 | Type::U8 => Some(8),
+        Type::I16 | Type::U16 => Some(16),
+        Type::I32 | Type::U32 => Some(32),
+        Type::I64 | Type::U64 => Some(64),
+        Type::I128 | Type::U128 => Some(128),
+        _ => None,
+    }
+}
+
+impl TypeSystem {
+    /*
+     * `const` binds the value of an expression made of literals and other constants,
+     * uses of the name are replaced with the value
+     */
+    pub(crate) fn annotate_const(
+        &mut self,
+        name: Token,
+        val: TypedNode,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        self.solve(&val)?;
+        let value = match self.eval(&val)? {
+            Some(value) => value,
+            None => {
+                return Err(type_error(
+                    "Not Constant",
+                    val.get_pos(),
+                    "the value of a `const` has to be known at compile time".to_string(),
+                ))
+            }
+        };
+
+        let ty = val.get_type();
+        let name_pos = (name.pos_start, name.pos_end);
+        let binding = self.bind_val(name.value.into_string(), ty.clone(), name_pos);
+        self.consts.insert(binding, value);
+        Ok(TypedNode::Let {
+            ty,
+            pos,
+            name: name.value.into_string(),
+            binding,
+            val: box val,
+        })
+    }
+
+    /*
+     * Length of an array written as a number or the name of a `const`
+     */
+    pub(crate) fn array_len(&self, len: Token) -> Result<u32, Error> {
+        let pos = (len.pos_start, len.pos_end);
+        let value = match len.value {
+            Tokens::Int(value) => value,
+            _ => {
+                let name = len.value.into_string();
+                match self
+                    .lookup(&name)
+                    .and_then(|(binding, _)| self.consts.get(&binding))
+                {
+                    Some(Const::Int(value)) => *value,
+                    Some(_) => {
+                        return Err(type_error(
+                            "Mismatched Types",
+                            pos,
+                            format!("the length of an array is an integer, `{}` isn't", name),
+                        ))
+                    }
+                    None => {
+                        return Err(type_error(
+                            "Not Constant",
+                            pos,
+                            format!(
+                                "`{}` isn't a `const`, array lengths are known at compile time",
+                                name
+                            ),
+                        ))
+                    }
+                }
+            }
+        };
+
+        u32::try_from(value).map_err(|_| {
+            type_error(
+                "Invalid Length",
+                pos,
+                format!("an array can't hold {} elements", value),
+            )
+        })
+    }
+
+    /*
+     * Value of `node` when it is made of literals and constants only
+     */
+    pub(crate) fn eval(&self, node: &TypedNode) -> Result<Option<Const>, Error> {
+        Ok(match node {
+            TypedNode::Int { val, .. } => Some(Const::Int(*val)),
+            TypedNode::Float { val, .. } => Some(Const::Float(*val)),
+            TypedNode::Boolean { val, .. } => Some(Const::Boolean(*val)),
+            TypedNode::Char { val, .. } => Some(Const::Char(*val)),
+            TypedNode::String { val, .. } => Some(Const::String(val.clone())),
+            TypedNode::Var { binding, .. } => self.consts.get(binding).cloned(),
+            // boxing into `any` or wrapping into a nullable isn't done at compile time
+            TypedNode::Convert { ty, val, .. }
+                if self.subst.apply_ty(ty.clone()) == self.subst.apply_ty(val.get_type()) =>
+            {
+                self.eval(val)?
+            }
+            TypedNode::Unary {
+                ty, val, op_token, ..
+            } => match self.eval(val)? {
+                Some(val) => self.eval_unary(op_token, val, ty),
+                None => None,
+            },
+            TypedNode::Binary {
+                ty,
+                pos,
+                left,
+                right,
+                op_token,
+            } => match (self.eval(left)?, self.eval(right)?) {
+                (Some(left), Some(right)) => self.eval_binary(op_token, left, right, ty, *pos)?,
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    fn eval_unary(&self, op: &Token, val: Const, ty: &Type) -> Option<Const> {
+        match (&op.value, val) {
+            (Tokens::Plus, val) => Some(val),
+            (Tokens::Minus, Const::Int(val)) => self.wrap(ty, val.wrapping_neg()),
+            (Tokens::Minus, Const::Float(val)) => self.round(ty, -val),
+            (Tokens::Keyword("not"), Const::Boolean(val)) => Some(Const::Boolean(!val)),
+            (Tokens::Keyword("not"), Const::Int(val)) => self.wrap(ty, !val),
+            _ => None,
+        }
+    }
+
+    fn eval_binary(
+        &self,
+        op: &Token,
+        left: Const,
+        right: Const,
+        ty: &Type,
+        pos: (Position, Position),
+    ) -> Result<Option<Const>, Error> {
+        // integers mixed with floats, or typed as floats, are computed as floats
+        let float = self.subst.apply_ty(ty.clone()).is_float();
+        let (left, right) = match (left, right) {
+            (Const::Int(left), Const::Int(right)) if float => {
+                (Const::Float(left as f64), Const::Float(right as f64))
+            }
+            (Const::Int(left), Const::Float(right)) => {
+                (Const::Float(left as f64), Const::Float(right))
+            }
+            (Const::Float(left), Const::Int(right)) => {
+                (Const::Float(left), Const::Float(right as f64))
+            }
+            operands => operands,
+        };
+
+        Ok(match (left, right) {
+            (Const::Int(left), Const::Int(right)) => match op.value {
+                Tokens::Divide | Tokens::Modulo if right == 0 => {
+                    return Err(type_error(
+                        "Division By Zero",
+                        pos,
+                        "this divides by zero".to_string(),
+                    ))
+                }
+                Tokens::Plus => self.wrap_int(ty, pos, i128::overflowing_add, left, right)?,
+                Tokens::Minus => self.wrap_int(ty, pos, i128::overflowing_sub, left, right)?,
+                Tokens::Multiply => self.wrap_int(ty, pos, i128::overflowing_mul, left, right)?,
+                Tokens::Divide => self.wrap_int(ty, pos, i128::overflowing_div, left, right)?,
+                Tokens::Modulo => self.wrap_int(ty, pos, i128::overflowing_rem, left, right)?,
+                _ => compare(&op.value, left, right),
+            },
+            (Const::Float(left), Const::Float(right)) => match op.value {
+                Tokens::Plus => self.round(ty, left + right),
+                Tokens::Minus => self.round(ty, left - right),
+                Tokens::Multiply => self.round(ty, left * right),
+                Tokens::Divide => self.round(ty, left / right),
+                Tokens::Modulo => self.round(ty, left % right),
+                _ => compare(&op.value, left, right),
+            },
+            (Const::Char(left), Const::Char(right)) => compare(&op.value, left, right),
+            (Const::Boolean(left), Const::Boolean(right)) => match op.value {
+                Tokens::Keyword("and") => Some(Const::Boolean(left && right)),
+                Tokens::Keyword("or") => Some(Const::Boolean(left || right)),
+                Tokens::DoubleEquals => Some(Const::Boolean(left == right)),
+                Tokens::NotEquals => Some(Const::Boolean(left != right)),
+                _ => None,
+            },
+            (Const::String(left), Const::String(right)) if op.value == Tokens::Plus => {
+                Some(Const::String(left + &right))
+            }
+            _ => None,
+        })
+    }
+
+    /*
+     * Wraps an integer around the width of `ty`, literals whose width isn't decided yet
+     * are kept as they are, the ones of generic functions aren't computed
+     */
+    fn wrap(&self, ty: &Type, val: i128) -> Option<Const> {
+        let ty = self.subst.apply_ty(ty.clone());
+        let bits = match bits(&ty) {
+            Some(bits) => bits,
+            None if ty.is_float() => return self.round(&ty, val as f64),
+            None if self.numeric(&ty).is_some() => return Some(Const::Int(val)),
+            None => return None,
+        };
+        if bits == 128 {
+            return Some(Const::Int(val));
+        }
+
+        let shift = 128 - bits;
+        Some(Const::Int(if ty.is_signed() {
+            (val << shift) >> shift
+        } else {
+            ((val as u128) << shift >> shift) as i128
+        }))
+    }
+
+    /*
+     * Result of an integer operation wrapped to `ty`,
+     * with checked arithmetic one which doesn't fit in `ty` is an error instead
+     */
+    fn wrap_int(
+        &self,
+        ty: &Type,
+        pos: (Position, Position),
+        op: fn(i128, i128) -> (i128, bool),
+        left: i128,
+        right: i128,
+    ) -> Result<Option<Const>, Error> {
+        let (val, overflowed) = op(left, right);
+        let wrapped = self.wrap(ty, val);
+        let fits = match wrapped {
+            Some(Const::Int(wrapped)) => !overflowed && wrapped == val,
+            _ => true,
+        };
+
+        if self.checked_arith && !fits {
+            Err(type_error(
+                "Arithmetic Overflow",
+                pos,
+                format!("this overflows `{}`", self.subst.apply_ty(ty.clone())),
+            ))
+        } else {
+            Ok(wrapped)
+        }
+    }
+
+    /*
+     * Rounds a float to the precision of `ty`
+     */
+    fn round(&self, ty: &Type, val: f64) -> Option<Const> {
+        match self.subst.apply_ty(ty.clone()) {
+            Type::F32 => Some(Const::Float(val as f32 as f64)),
+            Type::F64 => Some(Const::Float(val)),
+            ty if self.numeric(&ty).is_some() => Some(Const::Float(val)),
+            _ => None,
+        }
+    }
+
+    /*
+     * Replaces the expressions made of literals and constants with their values
+     */
+    pub(crate) fn fold_consts(&self, node: TypedNode) -> Result<TypedNode, Error> {
+        let mut folder = Folder {
+            type_system: self,
+            error: None,
+        };
+        let node = folder.fold(node);
+        match folder.error {
+            Some(error) => Err(error),
+            None => Ok(node),
+        }
+    }
+}
+
+/*
+ * Result of comparing two values, `None` for the other operators
+ */
+fn compare<T: PartialOrd>(op: &Tokens, left: T, right: T) -> Option<Const> {
+    Some(Const::Boolean(match op {
+        Tokens::LessThan => left < right,
+        Tokens::GreaterThan => left > right,
+        Tokens::LessThanEquals => left <= right,
+        Tokens::GreaterThanEquals => left >= right,
+        Tokens::DoubleEquals => left == right,
+        Tokens::NotEquals => left != right,
+        _ => return None,
+    }))
+}
+
+/*
+ * `+` on two strings
+ */
+fn is_concat(node: &TypedNode, ty: &Type) -> bool {
+    matches!(node, TypedNode::Binary { op_token, .. } if op_token.value == Tokens::Plus)
+        && *ty == Type::String
+}
+
+/*
+ * Folds the tree from its leaves up, keeping the first error met
+ */
+struct Folder<'a> {
+    type_system: &'a TypeSystem,
+    error: Option<Error>,
+}
+
+impl Fold<TypedNode> for Folder<'_> {
+    fn fold(&mut self, node: TypedNode) -> TypedNode {
+        let node = fold_children(self, node);
+        let (ty, pos) = match &node {
+            TypedNode::Var { ty, pos, .. }
+            | TypedNode::Unary { ty, pos, .. }
+            | TypedNode::Binary { ty, pos, .. } => (ty.clone(), *pos),
+            _ => return node,
+        };
+
+        match self.type_system.eval(&node) {
+            Ok(Some(value)) => value.literal(ty, pos),
+            // strings are only joined at compile time
+            Ok(None) if is_concat(&node, &self.type_system.subst.apply_ty(ty)) => {
+                self.error.get_or_insert(type_error(
+                    "Not Constant",
+                    pos,
+                    "strings are joined at compile time, both of them have to be known".to_string(),
+                ));
+                node
+            }
+            Ok(None) => node,
+            Err(error) => {
+                self.error.get_or_insert(error);
+                node
+            }
+        }
+    }
+}
@@ -0,0 +1,360 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::BTreeSet;
+
+use bzxc_shared::{
+    Binder, BindingId, Error, Node, Position, Symbol, Token, Tokens, Type, TypedNode,
+};
+
+use crate::{type_error, TypeSystem};
+
+impl TypeSystem {
+    /*
+     * Records the variants of an enum, numbered in the order they are declared
+     */
+    pub(crate) fn enum_def(
+        &mut self,
+        name: Token,
+        variants: Vec<(Token, Vec<Node>)>,
+    ) -> Result<(), Error> {
+        // registered first so variants can hold values of the enum itself
+        self.enums.insert(name.value.into_string(), vec![]);
+
+        let mut resolved: Vec<(String, Vec<Type>)> = vec![];
+        for (variant, payload) in variants {
+            if resolved
+                .iter()
+                .any(|(other, _)| *other == variant.value.into_string())
+            {
+                return Err(type_error(
+                    "Duplicate Variant",
+                    (variant.pos_start, variant.pos_end),
+                    format!(
+                        "`{}` already has a variant `{}`",
+                        name.value.into_string(),
+                        variant.value.into_string()
+                    ),
+                ));
+            }
+
+            let payload = payload
+                .into_iter()
+                .map(|ty| self.resolve_type(ty))
+                .collect::<Result<Vec<_>, Error>>()?;
+            resolved.push((variant.value.into_string(), payload));
+        }
+
+        self.enums.insert(name.value.into_string(), resolved);
+        Ok(())
+    }
+
+    /*
+     * Name of the enum `node` refers to, variables shadow enums
+     */
+    pub(crate) fn enum_named(&self, node: &Node) -> Option<String> {
+        match node {
+            Node::VarAccessNode { token } => {
+                let name = token.value.into_string();
+                if self.enums.contains_key(&name) && self.lookup(&name).is_none() {
+                    Some(name)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /*
+     * Value of `variant` of the enum `name`, its payload is converted to the declared types
+     */
+    pub(crate) fn variant(
+        &mut self,
+        name: String,
+        variant: Token,
+        args: Vec<Node>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let (tag, payload) = self.variant_of(&name, variant)?;
+        if payload.len() != args.len() {
+            return Err(type_error(
+                "Mismatched Payload",
+                pos,
+                format!(
+                    "`{}.{}` holds {}, found {}",
+                    name,
+                    variant.value.into_string(),
+                    values(payload.len()),
+                    args.len()
+                ),
+            ));
+        }
+
+        let mut vals = vec![];
+        for (ty, arg) in payload.into_iter().zip(args) {
+            let val = self.annotate(arg)?;
+            vals.push(TypedNode::Convert {
+                ty,
+                pos: val.get_pos(),
+                val: box val,
+            });
+        }
+
+        Ok(TypedNode::Variant {
+            ty: Type::Enum(name),
+            pos,
+            tag,
+            payload: vals,
+        })
+    }
+
+    /*
+     * Annotates a match, every variant of the enum has to be matched by one of the arms
+     * and the payload of a variant is bound to the names its pattern lists
+     */
+    pub(crate) fn annotate_match(
+        &mut self,
+        value: Node,
+        arms: Vec<(Node, Node)>,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let val = self.annotate(value)?;
+        let name = self.matched_enum(&val, &arms, pos)?;
+        let variants = self.enums[&name].clone();
+
+        let mut matched = BTreeSet::new();
+        let mut wildcard = false;
+        let mut typed_arms = vec![];
+        for (pattern, body) in arms {
+            let pattern_pos = pattern.get_pos();
+            let (enum_name, variant, bindings) = match pattern {
+                Node::VariantPattern {
+                    enum_name,
+                    variant,
+                    bindings,
+                } => (enum_name, variant, bindings),
+                _ => unreachable!(),
+            };
+
+            if wildcard {
+                return Err(type_error(
+                    "Unreachable Pattern",
+                    pattern_pos,
+                    "arms after `_` are never reached".to_string(),
+                ));
+            }
+
+            let is_wildcard =
+                enum_name.is_none() && variant.value == Tokens::Identifier(Symbol::intern("_"));
+            let (tag, payload) = if is_wildcard && bindings.is_empty() {
+                wildcard = true;
+                (None, vec![])
+            } else {
+                if let Some(enum_name) = enum_name {
+                    if enum_name.value.into_string() != name {
+                        return Err(type_error(
+                            "Mismatched Types",
+                            pattern_pos,
+                            format!(
+                                "expected a variant of `{}`, found one of `{}`",
+                                name,
+                                enum_name.value.into_string()
+                            ),
+                        ));
+                    }
+                }
+
+                let (tag, payload) = self.variant_of(&name, variant)?;
+                if !matched.insert(tag) {
+                    return Err(type_error(
+                        "Unreachable Pattern",
+                        pattern_pos,
+                        format!(
+                            "`{}.{}` is already matched",
+                            name,
+                            variant.value.into_string()
+                        ),
+                    ));
+                }
+                if !bindings.is_empty() && bindings.len() != payload.len() {
+                    return Err(type_error(
+                        "Mismatched Payload",
+                        pattern_pos,
+                        format!(
+                            "`{}.{}` holds {}, found {}",
+                            name,
+                            variant.value.into_string(),
+                            values(payload.len()),
+                            bindings.len()
+                        ),
+                    ));
+                }
+                (Some(tag), payload)
+            };
+
+            self.push_scope();
+            let binders = bindings
+                .iter()
+                .zip(payload)
+                .map(|(binding, ty)| {
+                    let name = binding.value.into_string();
+                    Binder {
+                        binding: match name.as_str() {
+                            "_" => BindingId::fresh(),
+                            _ => self.bind(name.clone(), ty.clone()),
+                        },
+                        ty,
+                        name,
+                    }
+                })
+                .collect::<Vec<_>>();
+            let body = self.annotate(body);
+            self.pop_scope();
+            typed_arms.push((tag, binders, body?));
+        }
+
+        let missing = variants
+            .iter()
+            .enumerate()
+            .filter(|(tag, _)| !matched.contains(&(*tag as u32)))
+            .map(|(_, (variant, _))| format!("`{}.{}`", name, variant))
+            .collect::<Vec<_>>();
+        if !wildcard && !missing.is_empty() {
+            return Err(type_error(
+                "Non-exhaustive Match",
+                pos,
+                format!("{} not matched, add the arms or `_`", missing.join(", ")),
+            ));
+        }
+
+        Ok(TypedNode::Match {
+            ty: Type::fresh_var(),
+            pos,
+            val: box TypedNode::Convert {
+                ty: Type::Enum(name),
+                pos: val.get_pos(),
+                val: box val,
+            },
+            arms: typed_arms,
+        })
+    }
+
+    /*
+     * The enum matched on, known from the value when it's already typed
+     * and from the variants the patterns name otherwise
+     */
+    fn matched_enum(
+        &self,
+        val: &TypedNode,
+        arms: &[(Node, Node)],
+        pos: (Position, Position),
+    ) -> Result<String, Error> {
+        if let Type::Enum(name) = self.subst.apply_ty(val.get_type()) {
+            return Ok(name);
+        }
+
+        for (pattern, _) in arms {
+            match pattern {
+                Node::VariantPattern {
+                    enum_name: Some(enum_name),
+                    ..
+                } => {
+                    let name = enum_name.value.into_string();
+                    if self.enums.contains_key(&name) {
+                        return Ok(name);
+                    }
+                    return Err(type_error(
+                        "Undefined Type",
+                        (enum_name.pos_start, enum_name.pos_end),
+                        format!("enum `{}` is not defined", name),
+                    ));
+                }
+                Node::VariantPattern { variant, .. }
+                    if variant.value != Tokens::Identifier(Symbol::intern("_")) =>
+                {
+                    let mut candidates = self
+                        .enums
+                        .iter()
+                        .filter(|(_, variants)| {
+                            variants
+                                .iter()
+                                .any(|(name, _)| *name == variant.value.into_string())
+                        })
+                        .map(|(name, _)| name.clone())
+                        .collect::<Vec<_>>();
+                    candidates.sort();
+
+                    return match candidates.as_slice() {
+                        [name] => Ok(name.clone()),
+                        [] => Err(type_error(
+                            "Unknown Variant",
+                            (variant.pos_start, variant.pos_end),
+                            format!("no enum has a variant `{}`", variant.value.into_string()),
+                        )),
+                        _ => Err(type_error(
+                            "Ambiguous Variant",
+                            (variant.pos_start, variant.pos_end),
+                            format!(
+                                "`{}` is a variant of {}, write `{}.{}`",
+                                variant.value.into_string(),
+                                candidates
+                                    .iter()
+                                    .map(|name| format!("`{}`", name))
+                                    .collect::<Vec<_>>()
+                                    .join(" and "),
+                                candidates[0],
+                                variant.value.into_string()
+                            ),
+                        )),
+                    };
+                }
+                _ => (),
+            }
+        }
+
+        Err(type_error(
+            "Missing Type",
+            pos,
+            "the enum matched on isn't known here, name one of its variants".to_string(),
+        ))
+    }
+
+    /*
+     * Tag and payload types of `variant` of the enum `name`
+     */
+    fn variant_of(&self, name: &str, variant: Token) -> Result<(u32, Vec<Type>), Error> {
+        self.enums[name]
+            .iter()
+            .enumerate()
+            .find(|(_, (other, _))| *other == variant.value.into_string())
+            .map(|(tag, (_, payload))| (tag as u32, payload.clone()))
+            .ok_or_else(|| {
+                type_error(
+                    "Unknown Variant",
+                    (variant.pos_start, variant.pos_end),
+                    format!(
+                        "`{}` has no variant `{}`",
+                        name,
+                        variant.value.into_string()
+                    ),
+                )
+            })
+    }
+}
+
+fn values(count: usize) -> String {
+    match count {
+        1 => "1 value".to_string(),
+        _ => format!("{} values", count),
+    }
+}
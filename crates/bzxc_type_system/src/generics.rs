@@ -0,0 +1,913 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::{BTreeMap, BTreeSet};
+
+use bzxc_shared::{BindingId, Error, Node, Symbol, Token, Tokens, Type, TypedNode, Walk};
+
+use crate::constraint::Constraint;
+use crate::resolver::Resolver;
+use crate::substitution::Substitution;
+use crate::type_env::TypeEnv;
+use crate::{type_error, TypeSystem};
+
+/*
+ * A function binding generalized over the type variables its definition doesn't fix
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct Generic {
+    pub name: String,
+    pub vars: Vec<i32>,
+    pub ty: Type,
+}
+
+/*
+ * A use of a generic binding, `args` are the fresh variables its `vars` were replaced with
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct Instance {
+    pub generic: i32,
+    pub args: Vec<Type>,
+    pub ty: Type,
+    pub enclosing: Vec<i32>,
+}
+
+/*
+ * A generic class used with type variables among its arguments, `ty` is the class
+ * of the copy it's typed with
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct ClassUse {
+    pub name: Token,
+    pub args: Vec<Type>,
+    pub ty: Type,
+}
+
+/*
+ * A class known to `class_env`, generic ones are kept as their definition
+ * and instantiated for every list of type arguments they are used with
+ */
+#[derive(Debug, Clone)]
+pub(crate) enum ClassDef {
+    Concrete(Type),
+    Generic {
+        params: Vec<String>,
+        node: Node,
+        env: TypeEnv,
+        names: Resolver,
+    },
+}
+
+impl TypeSystem {
+    /*
+     * Solves the constraints of an annotated statement into the running substitution,
+     * errors about a call also point at what it calls
+     */
+    pub(crate) fn solve(&mut self, node: &TypedNode) -> Result<(), Error> {
+        self.solve_constraints(node)
+            .map_err(|error| self.defined_here(error))
+    }
+
+    fn solve_constraints(&mut self, node: &TypedNode) -> Result<(), Error> {
+        let constraints = self.collect(node.clone());
+        let substitution = self.unify(self.subst.apply(constraints))?;
+        self.subst = self.subst.compose(substitution);
+        let accesses = std::mem::take(&mut self.tuple_accesses);
+        let accesses = self.solve_accesses(accesses, false)?;
+        for operands in std::mem::take(&mut self.operands) {
+            self.solve_operands(operands)?;
+        }
+        for cast in std::mem::take(&mut self.casts) {
+            self.check_cast(cast)?;
+        }
+        for coercion in std::mem::take(&mut self.coercions) {
+            self.coerce(coercion)?;
+        }
+        // the tuples passed to parameters without a type are known after the coercions
+        self.solve_accesses(accesses, true)?;
+        Ok(())
+    }
+
+    /*
+     * Type written in a type annotation
+     */
+    pub(crate) fn resolve_type(&mut self, node: Node) -> Result<Type, Error> {
+        let pos = node.get_pos();
+        match node {
+            Node::TypeKeyword { token } => Ok(Type::keyword(&token.value.into_string()).unwrap()),
+            Node::TypeArray { element, len } => {
+                let len = match len {
+                    Some(len) => self.array_len(len)?,
+                    None => 0,
+                };
+                Ok(Type::Array(box self.resolve_type(*element)?, len))
+            }
+            Node::TypeNullable { element } => Ok(Type::Nullable(box self.resolve_type(*element)?)),
+            Node::TypeTuple { elements } => Ok(Type::Tuple(
+                elements
+                    .into_iter()
+                    .map(|element| self.resolve_type(element))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Node::TypeName { name, args } => {
+                if args.is_empty() {
+                    if let Some(ty) = self.type_names.get(name.value.into_string()) {
+                        return Ok(ty);
+                    }
+                    if self.interfaces.contains_key(&name.value.into_string()) {
+                        return Ok(Type::Interface(name.value.into_string()));
+                    }
+                    if self.enums.contains_key(&name.value.into_string()) {
+                        return Ok(Type::Enum(name.value.into_string()));
+                    }
+                }
+
+                if !self.class_env.contains_key(&name.value.into_string()) {
+                    return Err(type_error(
+                        "Undefined Type",
+                        pos,
+                        format!("type `{}` is not defined", name.value.into_string()),
+                    ));
+                }
+
+                match self.class_type(name, args)? {
+                    Type::Class(obj) => Ok(*obj),
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /*
+     * Class type of `name<args>`, instantiating generic classes on their first use
+     */
+    pub(crate) fn class_type(&mut self, name: Token, args: Vec<Node>) -> Result<Type, Error> {
+        let pos = (name.pos_start, name.pos_end);
+        let class_name = name.value.into_string();
+        let args = args
+            .into_iter()
+            .map(|arg| {
+                let ty = self.resolve_type(arg)?;
+                Ok(self.subst.apply_ty(ty))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.class_of(name, args)
+    }
+
+    /*
+     * Class type of `name` with the type arguments `args`
+     */
+    fn class_of(&mut self, name: Token, args: Vec<Type>) -> Result<Type, Error> {
+        let pos = (name.pos_start, name.pos_end);
+        let class_name = name.value.into_string();
+        let (params, node, env, names) = match self.class_env.get(&class_name).cloned() {
+            Some(ClassDef::Concrete(ty)) if args.is_empty() => return Ok(ty),
+            Some(ClassDef::Concrete(_)) => {
+                return Err(type_error(
+                    "Invalid Type Arguments",
+                    pos,
+                    format!("class `{}` takes no type arguments", class_name),
+                ))
+            }
+            Some(ClassDef::Generic {
+                params,
+                node,
+                env,
+                names,
+            }) => (params, node, env, names),
+            None => {
+                return Err(type_error(
+                    "Undefined Class",
+                    pos,
+                    format!("class `{}` is not defined", class_name),
+                ))
+            }
+        };
+
+        if params.len() != args.len() {
+            return Err(type_error(
+                "Invalid Type Arguments",
+                pos,
+                format!(
+                    "class `{}` takes {} type arguments, found {}",
+                    class_name,
+                    params.len(),
+                    args.len()
+                ),
+            ));
+        }
+
+        // the type parameters of a generic function are only known once it's monomorphized
+        let mut vars = BTreeSet::new();
+        args.iter().for_each(|arg| free_vars(arg, &mut vars));
+        if !vars.is_empty() {
+            return self.class_use(name, params, node, env, names, args);
+        }
+
+        let instance = self.mangled(&class_name, &args);
+        if let Some(ClassDef::Concrete(ty)) = self.class_env.get(&instance) {
+            return Ok(ty.clone());
+        }
+
+        let class = self.instantiate_class(&instance, params, node, env, names, args)?;
+        self.pending_classes.push(class);
+        match &self.class_env[&instance] {
+            ClassDef::Concrete(ty) => Ok(self.subst.apply_ty(ty.clone())),
+            _ => unreachable!(),
+        }
+    }
+
+    /*
+     * Use of a generic class whose type arguments mention type variables, it's typed with
+     * a copy of the class that isn't emitted, the copies for the arguments the variables
+     * end up with are made by `instantiate_uses`
+     */
+    fn class_use(
+        &mut self,
+        name: Token,
+        params: Vec<String>,
+        node: Node,
+        env: TypeEnv,
+        names: Resolver,
+        args: Vec<Type>,
+    ) -> Result<Type, Error> {
+        let class_name = name.value.into_string();
+        let known = self.class_uses.values().find(|class_use| {
+            class_use.name.value == name.value
+                && class_use
+                    .args
+                    .iter()
+                    .map(|arg| self.subst.apply_ty(arg.clone()))
+                    .eq(args.iter().cloned())
+        });
+        if let Some(class_use) = known {
+            return Ok(self.subst.apply_ty(class_use.ty.clone()));
+        }
+
+        // a method of the class can use it with the same arguments while it's being annotated
+        let instance = self.mangled(&class_name, &args);
+        if let Some(ClassDef::Concrete(ty)) = self.class_env.get(&instance) {
+            return Ok(ty.clone());
+        }
+
+        self.instantiate_class(&instance, params, node, env, names, args.clone())?;
+        let ty = match self.class_env.remove(&instance) {
+            Some(ClassDef::Concrete(ty)) => self.subst.apply_ty(ty),
+            _ => unreachable!(),
+        };
+        let args = args
+            .into_iter()
+            .map(|arg| self.subst.apply_ty(arg))
+            .collect::<Vec<_>>();
+
+        let id = ty.id().unwrap();
+        let mut fields = BTreeSet::new();
+        free_vars(&ty, &mut fields);
+        if args.iter().any(|arg| {
+            let mut vars = BTreeSet::new();
+            free_vars(arg, &mut vars);
+            !vars.is_subset(&fields)
+        }) {
+            return Err(type_error(
+                "Invalid Type Arguments",
+                (name.pos_start, name.pos_end),
+                format!(
+                    "type arguments of `{}` must be known here, found `{}`",
+                    class_name, instance
+                ),
+            ));
+        }
+
+        self.class_uses.insert(
+            id,
+            ClassUse {
+                name,
+                args,
+                ty: ty.clone(),
+            },
+        );
+        Ok(ty)
+    }
+
+    /*
+     * Annotates and solves the copy of a generic class for `args`, named `instance`
+     */
+    fn instantiate_class(
+        &mut self,
+        instance: &str,
+        params: Vec<String>,
+        node: Node,
+        env: TypeEnv,
+        names: Resolver,
+        args: Vec<Type>,
+    ) -> Result<TypedNode, Error> {
+        let node = match node {
+            Node::ClassDefNode {
+                methods,
+                properties,
+                constructor,
+                static_members,
+                name,
+                base,
+                interfaces,
+                ..
+            } => Node::ClassDefNode {
+                methods,
+                properties,
+                constructor,
+                static_members,
+                name: Token::new(
+                    Tokens::Identifier(Symbol::intern(instance)),
+                    name.pos_start,
+                    name.pos_end,
+                ),
+                type_params: vec![],
+                base,
+                interfaces,
+            },
+            _ => unreachable!(),
+        };
+
+        /*
+         * The class body is annotated where the class was defined, not where it's used,
+         * and solved on its own so its type is known to the arguments of other classes
+         */
+        let type_env = std::mem::replace(&mut self.type_env, env);
+        let resolver = std::mem::replace(&mut self.resolver, names);
+        let enclosing = std::mem::take(&mut self.enclosing);
+        self.type_names.push_scope();
+        for (param, arg) in params.into_iter().zip(args) {
+            self.type_names.set(param, arg);
+        }
+
+        let class = self.annotate(node).and_then(|class| {
+            self.solve(&class)?;
+            Ok(class)
+        });
+
+        self.type_names.pop_scope();
+        self.enclosing = enclosing;
+        self.resolver = resolver;
+        self.type_env = type_env;
+        class
+    }
+
+    /*
+     * Generalizes the function `name` bound to `binding` over every variable
+     * the environment doesn't mention
+     */
+    pub(crate) fn generalize(
+        &mut self,
+        name: String,
+        binding: BindingId,
+        fun: &TypedNode,
+    ) -> Result<(), Error> {
+        let id = match fun.get_type() {
+            Type::Var(id) => id,
+            _ => return Ok(()),
+        };
+        // functions called above their definitions are already typed by those calls
+        if !matches!(self.subst.apply_ty(Type::Var(id)), Type::Var(_)) {
+            return Ok(());
+        }
+
+        self.solve(fun)?;
+        let ty = self.subst.apply_ty(Type::Var(id));
+
+        let mut env = BTreeSet::new();
+        for (bound, ty) in self.type_env.iter() {
+            if *bound != binding {
+                free_vars(&self.subst.apply_ty(ty.clone()), &mut env);
+            }
+        }
+        // fields of classes are shared by every use of the class
+        for class in self.class_env.values() {
+            if let ClassDef::Concrete(ty) = class {
+                free_vars(&self.subst.apply_ty(ty.clone()), &mut env);
+            }
+        }
+
+        let mut vars = BTreeSet::new();
+        free_vars(&ty, &mut vars);
+        let vars = vars.difference(&env).cloned().collect::<Vec<_>>();
+
+        if !vars.is_empty() {
+            self.generics.insert(id, Generic { name, vars, ty });
+        }
+        Ok(())
+    }
+
+    /*
+     * Gives every use of a generic binding its own copy of the generalized variables
+     */
+    pub(crate) fn instantiate(&mut self, ty: Type) -> Type {
+        let generic = match &ty {
+            Type::Var(id) => match self.generics.get(id) {
+                Some(generic) => generic.clone(),
+                None => return ty,
+            },
+            _ => return ty,
+        };
+
+        let args = generic
+            .vars
+            .iter()
+            .map(|var| match self.numeric(&Type::Var(*var)) {
+                Some(numeric) => self.numeric_var(numeric),
+                None => Type::fresh_var(),
+            })
+            .collect::<Vec<_>>();
+        let fresh = Substitution(
+            generic
+                .vars
+                .iter()
+                .map(|var| Type::Var(*var))
+                .zip(args.iter().cloned())
+                .collect(),
+        );
+
+        let use_ty = Type::fresh_var();
+        if let (Type::Var(use_id), Type::Var(generic_id)) = (&use_ty, ty) {
+            self.instances.insert(
+                *use_id,
+                Instance {
+                    generic: generic_id,
+                    args,
+                    ty: fresh.apply_ty(generic.ty),
+                    enclosing: self.enclosing.clone(),
+                },
+            );
+        }
+        use_ty
+    }
+
+    /*
+     * Type arguments written out at a call like `max<float>(a, b)`, `declared` is the type
+     * the function `name` is bound to and `used` the one it has at the call
+     */
+    pub(crate) fn call_type_args(
+        &mut self,
+        name: Token,
+        declared: &Type,
+        used: &Type,
+        args: Vec<Node>,
+    ) -> Result<(), Error> {
+        let pos = (name.pos_start, name.pos_end);
+        let fun_name = name.value.into_string();
+        let params = match declared {
+            Type::Var(id) => self.type_params.get(id).cloned().unwrap_or_default(),
+            _ => vec![],
+        };
+
+        if params.is_empty() {
+            return Err(type_error(
+                "Invalid Type Arguments",
+                pos,
+                format!("function `{}` takes no type arguments", fun_name),
+            ));
+        }
+        if params.len() != args.len() {
+            return Err(type_error(
+                "Invalid Type Arguments",
+                pos,
+                format!(
+                    "function `{}` takes {} type arguments, found {}",
+                    fun_name,
+                    params.len(),
+                    args.len()
+                ),
+            ));
+        }
+
+        // a use of a generic function has its own copies of the type parameters
+        let instance = match used {
+            Type::Var(id) => self.instances.get(id).cloned(),
+            _ => None,
+        };
+        let mut constraints = vec![];
+        for (param, arg) in params.into_iter().zip(args) {
+            let arg_pos = arg.get_pos();
+            let param = match (self.subst.apply_ty(param), &instance) {
+                (Type::Var(var), Some(instance)) => self.generics[&instance.generic]
+                    .vars
+                    .iter()
+                    .position(|generic| *generic == var)
+                    .map_or(Type::Var(var), |index| instance.args[index].clone()),
+                (param, _) => param,
+            };
+            constraints.push(Constraint(param, self.resolve_type(arg)?, arg_pos));
+        }
+
+        let constraints = self.subst.apply(constraints);
+        let substitution = self.unify(constraints)?;
+        self.subst = self.subst.compose(substitution);
+        Ok(())
+    }
+
+    /*
+     * Finds every concrete instantiation of the generic bindings, uses inside
+     * a generic body are resolved once for each instantiation of that body
+     */
+    pub(crate) fn monomorphize(&mut self, subs: &Substitution) {
+        let mut mono: BTreeMap<i32, Vec<Vec<Type>>> = BTreeMap::new();
+
+        loop {
+            let mut changed = false;
+
+            for instance in self.instances.values() {
+                let mut candidates = vec![subs.clone()];
+                for enclosing in &instance.enclosing {
+                    for args in mono.get(enclosing).cloned().unwrap_or_default() {
+                        candidates.push(self.instance_subst(subs, *enclosing, &args));
+                    }
+                }
+
+                for candidate in candidates {
+                    let args = instance
+                        .args
+                        .iter()
+                        .map(|arg| candidate.apply_ty(arg.clone()))
+                        .collect::<Vec<_>>();
+
+                    let mut vars = BTreeSet::new();
+                    args.iter().for_each(|arg| free_vars(arg, &mut vars));
+
+                    let known = mono.entry(instance.generic).or_default();
+                    if vars.is_empty() && !known.contains(&args) {
+                        known.push(args);
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        self.mono = mono;
+    }
+
+    /*
+     * The substitution a copy of generic `id` is generated with
+     */
+    pub(crate) fn instance_subst(
+        &self,
+        subs: &Substitution,
+        id: i32,
+        args: &[Type],
+    ) -> Substitution {
+        let mut tree = BTreeMap::new();
+        for (var, arg) in self.generics[&id].vars.iter().zip(args) {
+            if let Type::Var(rep) = subs.apply_ty(Type::Var(*var)) {
+                tree.insert(Type::Var(rep), arg.clone());
+            }
+        }
+        subs.clone().compose(Substitution(tree))
+    }
+
+    /*
+     * Name of the copy of generic `id`, eg. `max<int>`
+     */
+    pub(crate) fn mangle(&self, id: i32, args: &[Type]) -> String {
+        self.mangled(&self.generics[&id].name, args)
+    }
+
+    fn mangled(&self, name: &str, args: &[Type]) -> String {
+        format!(
+            "{}<{}>",
+            name,
+            args.iter()
+                .map(|arg| self.type_name(arg))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    /*
+     * Types are displayed as usual, except objects of a class are named after it
+     */
+    pub(crate) fn type_name(&self, ty: &Type) -> String {
+        match ty {
+            Type::Object(Some(id), _) if self.class_names.contains_key(id) => {
+                self.class_names[id].clone()
+            }
+            Type::Array(ty, 0) => format!("[{}]", self.type_name(ty)),
+            Type::Array(ty, len) => format!("[{}; {}]", self.type_name(ty), len),
+            Type::Nullable(ty) => format!("{}?", self.type_name(ty)),
+            Type::Tuple(elements) => format!(
+                "({})",
+                elements
+                    .iter()
+                    .map(|element| self.type_name(element))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Type::Fun(params, ret) => format!(
+                "fun({}) -> {}",
+                params
+                    .iter()
+                    .map(|param| self.type_name(param))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.type_name(ret)
+            ),
+            _ => ty.to_string(),
+        }
+    }
+
+    /*
+     * Name a use of a generic binding refers to
+     */
+    pub(crate) fn instance_name(&self, subs: &Substitution, use_ty: &Type) -> Option<String> {
+        let instance = match use_ty {
+            Type::Var(id) => self.instances.get(id)?,
+            _ => return None,
+        };
+
+        let args = instance
+            .args
+            .iter()
+            .map(|arg| subs.apply_ty(arg.clone()))
+            .collect::<Vec<_>>();
+        Some(self.mangle(instance.generic, &args))
+    }
+    /*
+     * What the variables of the class use `id` stand for in `obj`, an object of the use
+     * whose fields may have other types, eg. after instantiating a generic function
+     */
+    pub(crate) fn use_subst(&self, id: u32, obj: &Type) -> Option<Substitution> {
+        let template = match self.subst.apply_ty(self.class_uses.get(&id)?.ty.clone()) {
+            Type::Class(template) => *template,
+            _ => unreachable!(),
+        };
+        let mut tree = BTreeMap::new();
+        bind_vars(&template, obj, &mut tree);
+        Some(Substitution(tree))
+    }
+
+    /*
+     * Type arguments of the class use `id` in its object `obj`
+     */
+    fn use_args(&self, id: u32, obj: &Type) -> Vec<Type> {
+        let subs = self.use_subst(id, obj).unwrap();
+        self.class_uses[&id]
+            .args
+            .iter()
+            .map(|arg| subs.apply_ty(self.subst.apply_ty(arg.clone())))
+            .collect()
+    }
+
+    /*
+     * `ty` with the objects of class uses replaced by the ones of the classes
+     * made for their arguments by `instantiate_uses`
+     */
+    pub(crate) fn instantiated(&self, subs: &Substitution, ty: Type) -> Type {
+        self.instantiated_ty(subs.apply_ty(ty))
+    }
+
+    fn instantiated_ty(&self, ty: Type) -> Type {
+        match ty {
+            Type::Object(Some(id), _) if self.class_uses.contains_key(&id) => {
+                let args = self
+                    .use_args(id, &ty)
+                    .into_iter()
+                    .map(|arg| self.instantiated_ty(arg))
+                    .collect::<Vec<_>>();
+                let name = self.mangled(&self.class_uses[&id].name.value.into_string(), &args);
+                match self.class_env.get(&name) {
+                    Some(ClassDef::Concrete(class)) => match self.subst.apply_ty(class.clone()) {
+                        Type::Class(obj) => *obj,
+                        _ => unreachable!(),
+                    },
+                    _ => ty,
+                }
+            }
+            Type::Object(id, tree) => Type::Object(
+                id,
+                tree.into_iter()
+                    .map(|(name, ty)| (name, self.instantiated_ty(ty)))
+                    .collect(),
+            ),
+            Type::Fun(params, ret) => Type::Fun(
+                params
+                    .into_iter()
+                    .map(|param| self.instantiated_ty(param))
+                    .collect(),
+                box self.instantiated_ty(*ret),
+            ),
+            Type::Array(ty, len) => Type::Array(box self.instantiated_ty(*ty), len),
+            Type::Class(ty) => Type::Class(box self.instantiated_ty(*ty)),
+            Type::Nullable(ty) => Type::Nullable(box self.instantiated_ty(*ty)),
+            Type::Tuple(elements) => Type::Tuple(
+                elements
+                    .into_iter()
+                    .map(|element| self.instantiated_ty(element))
+                    .collect(),
+            ),
+            ty => ty,
+        }
+    }
+
+    /*
+     * Makes the copies of generic classes their uses stand for once the generic functions
+     * are monomorphized, returns the classes to emit along with the program
+     */
+    pub(crate) fn instantiate_uses(&mut self, node: &TypedNode) -> Result<Vec<TypedNode>, Error> {
+        let mut classes = vec![];
+        loop {
+            let subs = self.subst.clone();
+            self.monomorphize(&subs);
+
+            let mut uses = vec![];
+            self.find_uses(&subs, node, &mut uses);
+            let mut made = false;
+            for (name, args) in uses {
+                let instance = self.mangled(&name.value.into_string(), &args);
+                if self.class_env.contains_key(&instance) {
+                    continue;
+                }
+
+                let mut vars = BTreeSet::new();
+                args.iter().for_each(|arg| free_vars(arg, &mut vars));
+                if !vars.is_empty() {
+                    return Err(type_error(
+                        "Invalid Type Arguments",
+                        (name.pos_start, name.pos_end),
+                        format!(
+                            "type arguments of `{}` must be known here, found `{}`",
+                            name.value.into_string(),
+                            instance
+                        ),
+                    ));
+                }
+                self.class_of(name, args)?;
+                made = true;
+            }
+
+            classes.extend(std::mem::take(&mut self.pending_classes));
+            if !made {
+                return Ok(classes);
+            }
+        }
+    }
+
+    /*
+     * Class uses in the types of `node`, walked the way it's generated
+     */
+    fn find_uses(&self, subs: &Substitution, node: &TypedNode, uses: &mut Vec<(Token, Vec<Type>)>) {
+        let id = match self.generic_id(node) {
+            Some(id) => id,
+            None => return self.find_node_uses(subs, node, uses),
+        };
+        let fun = match node {
+            TypedNode::Let { val, .. } => val,
+            fun => fun,
+        };
+        for args in self.mono.get(&id).cloned().unwrap_or_default() {
+            self.find_node_uses(&self.instance_subst(subs, id, &args), fun, uses);
+        }
+    }
+
+    fn find_node_uses(
+        &self,
+        subs: &Substitution,
+        node: &TypedNode,
+        uses: &mut Vec<(Token, Vec<Type>)>,
+    ) {
+        self.find_type_uses(subs.apply_ty(node.get_type()), uses);
+        if let TypedNode::Fun { params, .. } = node {
+            for param in params {
+                self.find_type_uses(subs.apply_ty(param.ty.clone()), uses);
+            }
+        }
+        for child in node.children() {
+            self.find_uses(subs, child, uses);
+        }
+    }
+
+    fn find_type_uses(&self, ty: Type, uses: &mut Vec<(Token, Vec<Type>)>) {
+        match &ty {
+            Type::Object(Some(id), _) if self.class_uses.contains_key(id) => {
+                let args = self.use_args(*id, &ty);
+                args.iter()
+                    .for_each(|arg| self.find_type_uses(arg.clone(), uses));
+                // uses among the arguments are made first
+                let args = args
+                    .into_iter()
+                    .map(|arg| self.instantiated_ty(arg))
+                    .collect::<Vec<_>>();
+                if !args.iter().any(|arg| self.has_uses(arg)) {
+                    uses.push((self.class_uses[id].name, args));
+                }
+            }
+            Type::Object(_, tree) => tree
+                .values()
+                .for_each(|ty| self.find_type_uses(ty.clone(), uses)),
+            Type::Fun(params, ret) => {
+                params
+                    .iter()
+                    .for_each(|param| self.find_type_uses(param.clone(), uses));
+                self.find_type_uses(*ret.clone(), uses);
+            }
+            Type::Array(ty, _) | Type::Class(ty) | Type::Nullable(ty) => {
+                self.find_type_uses(*ty.clone(), uses)
+            }
+            Type::Tuple(elements) => elements
+                .iter()
+                .for_each(|element| self.find_type_uses(element.clone(), uses)),
+            _ => (),
+        }
+    }
+
+    fn has_uses(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Object(Some(id), _) if self.class_uses.contains_key(id) => true,
+            Type::Object(_, tree) => tree.values().any(|ty| self.has_uses(ty)),
+            Type::Fun(params, ret) => {
+                params.iter().any(|param| self.has_uses(param)) || self.has_uses(ret)
+            }
+            Type::Array(ty, _) | Type::Class(ty) | Type::Nullable(ty) => self.has_uses(ty),
+            Type::Tuple(elements) => elements.iter().any(|element| self.has_uses(element)),
+            _ => false,
+        }
+    }
+
+    /*
+     * Id of the generic function `node` defines, if it does
+     */
+    pub(crate) fn generic_id(&self, node: &TypedNode) -> Option<i32> {
+        match node {
+            TypedNode::Fun {
+                ty: Type::Var(id), ..
+            } if self.generics.contains_key(id) => Some(*id),
+            TypedNode::Let {
+                ty: Type::Var(id),
+                val,
+                ..
+            } if self.generics.contains_key(id) && matches!(**val, TypedNode::Fun { .. }) => {
+                Some(*id)
+            }
+            _ => None,
+        }
+    }
+}
+
+/*
+ * Binds the variables of `template` to the types at the same places in `ty`
+ */
+fn bind_vars(template: &Type, ty: &Type, tree: &mut BTreeMap<Type, Type>) {
+    match (template, ty) {
+        (Type::Var(var), ty) => {
+            tree.entry(Type::Var(*var)).or_insert_with(|| ty.clone());
+        }
+        (Type::Fun(params1, ret1), Type::Fun(params2, ret2)) => {
+            for (param1, param2) in params1.iter().zip(params2) {
+                bind_vars(param1, param2, tree);
+            }
+            bind_vars(ret1, ret2, tree);
+        }
+        (Type::Array(ty1, _), Type::Array(ty2, _))
+        | (Type::Class(ty1), Type::Class(ty2))
+        | (Type::Nullable(ty1), Type::Nullable(ty2)) => bind_vars(ty1, ty2, tree),
+        (Type::Tuple(elements1), Type::Tuple(elements2)) => {
+            for (element1, element2) in elements1.iter().zip(elements2) {
+                bind_vars(element1, element2, tree);
+            }
+        }
+        (Type::Object(_, tree1), Type::Object(_, tree2)) => {
+            for (name, ty1) in tree1 {
+                if let Some(ty2) = tree2.get(name) {
+                    bind_vars(ty1, ty2, tree);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+pub(crate) fn free_vars(ty: &Type, vars: &mut BTreeSet<i32>) {
+    match ty {
+        Type::Var(var) => {
+            vars.insert(*var);
+        }
+        Type::Fun(params, ret) => {
+            params.iter().for_each(|param| free_vars(param, vars));
+            free_vars(ret, vars);
+        }
+        Type::Array(ty, _) | Type::Class(ty) | Type::Nullable(ty) => free_vars(ty, vars),
+        Type::Object(_, tree) => tree.values().for_each(|ty| free_vars(ty, vars)),
+        Type::Tuple(elements) => elements.iter().for_each(|ty| free_vars(ty, vars)),
+        _ => (),
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{BindingId, Error, Node, Token, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::TypeSystem;
+
+impl TypeSystem {
+    /*
+     * Annotates the whole program. The top-level functions are bound before anything else,
+     * then externs, enums, interfaces and classes are annotated in the order they're written,
+     * so all of them can be used above their definitions and functions can call each other.
+     * Functions are typed by their signatures when those are fully annotated,
+     * their bodies are annotated where they are
+     */
+    pub(crate) fn annotate_program(&mut self, node: Node) -> Result<TypedNode, Error> {
+        let statements = match node {
+            Node::Statements { statements } => statements,
+            node => return self.annotate(node),
+        };
+
+        for statement in &statements {
+            if let Node::FunDef {
+                name: Some(name),
+                type_params,
+                ..
+            } = statement
+            {
+                self.declare(name, type_params);
+            }
+        }
+
+        let (declarations, rest): (Vec<_>, Vec<_>) =
+            statements.into_iter().partition(|statement| {
+                matches!(
+                    statement,
+                    Node::ExternNode { .. }
+                        | Node::EnumDefNode { .. }
+                        | Node::InterfaceDefNode { .. }
+                        | Node::ClassDefNode { .. }
+                )
+            });
+
+        let mut stmts = vec![];
+        for statement in declarations {
+            self.annotate_statement(statement, &mut stmts)?;
+        }
+        for statement in &rest {
+            if let Node::FunDef {
+                name: Some(name),
+                type_params,
+                args,
+                return_type,
+                ..
+            } = statement
+            {
+                if type_params.is_empty() {
+                    self.sign(name, args, return_type)?;
+                }
+            }
+        }
+        // the generic classes instantiated below are defined along with the declarations,
+        // so functions written above the first use of one can use it too
+        let mut body = vec![];
+        for statement in rest {
+            let mut annotated = vec![];
+            self.annotate_statement(statement, &mut annotated)?;
+            body.push(annotated.pop().unwrap());
+            stmts.append(&mut annotated);
+        }
+        stmts.extend(body);
+        Ok(TypedNode::Statements(stmts))
+    }
+
+    /*
+     * Binds the function `name` to a type known only once its definition is annotated,
+     * its type parameters can already be given at calls above it
+     */
+    fn declare(&mut self, name: &Token, type_params: &[Token]) {
+        let ty = Type::fresh_var();
+        self.define(&ty, (name.pos_start, name.pos_end));
+        if let (Type::Var(id), false) = (&ty, type_params.is_empty()) {
+            let params = type_params.iter().map(|_| Type::fresh_var()).collect();
+            self.type_params.insert(*id, params);
+        }
+        let binding = self.bind_static(name.value.into_string(), ty.clone());
+        self.declared.insert(name.pos_start, (binding, ty));
+    }
+
+    /*
+     * Types the declared function `name` by its signature, unless a type is left out
+     */
+    fn sign(
+        &mut self,
+        name: &Token,
+        args: &[(Token, Option<Node>)],
+        return_type: &Option<Node>,
+    ) -> Result<(), Error> {
+        let (ret, ty) = match (return_type, self.declared.get(&name.pos_start)) {
+            (Some(ret), Some((_, ty))) => (ret.clone(), ty.clone()),
+            _ => return Ok(()),
+        };
+
+        let mut params = vec![];
+        for (_, param) in args {
+            match param {
+                Some(param) => params.push(self.resolve_type(param.clone())?),
+                None => return Ok(()),
+            }
+        }
+        let signature = Type::Fun(params, box self.resolve_type(ret)?);
+
+        let pos = (name.pos_start, name.pos_end);
+        let constraints = self.subst.apply(vec![Constraint(ty, signature, pos)]);
+        let substitution = self.unify(constraints)?;
+        self.subst = self.subst.compose(substitution);
+        Ok(())
+    }
+
+    /*
+     * Binding and type of the function `name` if it was declared before its definition
+     */
+    pub(crate) fn declared(&mut self, name: &Token) -> Option<(BindingId, Type)> {
+        self.declared.remove(&name.pos_start)
+    }
+}
@@ -0,0 +1,192 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::{BTreeMap, HashMap};
+
+use bzxc_shared::{Error, Node, Position, Symbol, Token, Tokens, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::{type_error, TypeSystem};
+
+impl TypeSystem {
+    /*
+     * Object type and name of the class named by an `extends` clause
+     */
+    pub(crate) fn base_class(&mut self, node: Node) -> Result<(Type, String), Error> {
+        let pos = node.get_pos();
+        let ty = self.resolve_type(node)?;
+
+        // generic classes instantiated by the clause are solved before the class extending them
+        if let Type::Var(_) = self.subst.apply_ty(ty.clone()) {
+            for class in self.pending_classes.clone() {
+                self.solve(&class)?;
+            }
+        }
+
+        let base = self.subst.apply_ty(ty);
+        match base.id().and_then(|id| self.class_names.get(&id)) {
+            Some(name) => Ok((base.clone(), name.clone())),
+            None => Err(type_error(
+                "Invalid Base Class",
+                pos,
+                format!("`{}` is not a class", base),
+            )),
+        }
+    }
+
+    /*
+     * Constructor of a class which doesn't define one, classes extending another
+     * take the parameters of the constructor of their base class and pass them on to it
+     */
+    pub(crate) fn default_constructor(
+        &self,
+        base: Option<&Type>,
+        class: Token,
+    ) -> (Vec<(Token, Option<Node>)>, Box<Node>) {
+        let token = |name: &str| {
+            Token::new(
+                Tokens::Identifier(Symbol::intern(name)),
+                class.pos_start,
+                class.pos_end,
+            )
+        };
+        let mut statements = vec![];
+        let mut args = vec![];
+
+        if let Some(base) = base {
+            let id = base.id().unwrap();
+            let params = match self
+                .subst
+                .apply_ty(self.methods[&id]["constructor"].clone())
+            {
+                Type::Fun(params, _) => params.len(),
+                _ => 0,
+            };
+
+            for i in 0..params {
+                args.push((token(&format!("%arg{}%", i)), None));
+            }
+            statements.push(Node::CallNode {
+                node_to_call: box Node::SuperNode {
+                    token: token("super"),
+                },
+                type_args: vec![],
+                args: args
+                    .iter()
+                    .map(|(token, _)| Node::VarAccessNode { token: *token })
+                    .collect(),
+            });
+        }
+
+        statements.push(Node::ReturnNode {
+            value: box Some(Node::VarAccessNode {
+                token: token("soul"),
+            }),
+        });
+        (args, box Node::Statements { statements })
+    }
+
+    /*
+     * `soul` seen as the base class of the class being defined
+     */
+    pub(crate) fn annotate_super(&self, pos: (Position, Position)) -> Result<TypedNode, Error> {
+        match self.supers.last() {
+            Some(Some(base)) => Ok(TypedNode::Super {
+                ty: base.clone(),
+                pos,
+            }),
+            _ => Err(type_error(
+                "Invalid Super",
+                pos,
+                "`super` can only be used inside a class extending another".to_string(),
+            )),
+        }
+    }
+
+    /*
+     * Methods `class` inherits from `base`, the properties and methods it redefines
+     * must keep the types they have in the base class
+     */
+    pub(crate) fn inherit(
+        &mut self,
+        class: &str,
+        base: &Type,
+        properties: &BTreeMap<String, TypedNode>,
+        methods: &BTreeMap<String, TypedNode>,
+        constr: &mut Vec<Constraint>,
+    ) -> HashMap<String, Type> {
+        for (name, ty) in base.fields() {
+            let property = &properties[&name];
+            constr.push(Constraint(ty, property.get_type(), property.get_pos()));
+        }
+
+        let id = base.id().unwrap();
+        let mut inherited = self.methods[&id].clone();
+        inherited.remove("constructor");
+
+        for (name, method) in methods {
+            if let Some(ty) = inherited.get(name) {
+                constr.push(Constraint(ty.clone(), method.get_type(), method.get_pos()));
+            }
+        }
+
+        let base = self.class_names[&id].clone();
+        self.bases.insert(class.to_string(), base);
+        inherited
+    }
+
+    /*
+     * Objects of a class can be used where ones of the classes it extends are expected
+     */
+    pub(crate) fn check_extends(
+        &self,
+        expected: &Type,
+        found: &Type,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        let name = |ty: &Type| ty.id().and_then(|id| self.class_names.get(&id));
+        let (expected_ty, found_ty) = (expected, found);
+        let (expected, found) = match (name(expected), name(found)) {
+            (Some(expected), Some(found)) => (expected, found),
+            _ => return Ok(()),
+        };
+
+        // uses of a generic class take any copy of it, their arguments are unified after
+        let is_use = |ty: &Type| {
+            ty.id()
+                .map_or(false, |id| self.class_uses.contains_key(&id))
+        };
+        if is_use(expected_ty) || is_use(found_ty) {
+            let generic = |name: &String| name.split('<').next().unwrap().to_string();
+            if generic(expected) == generic(found) {
+                return Ok(());
+            }
+        }
+
+        let mut class = Some(found);
+        while let Some(name) = class {
+            if name == expected {
+                return Ok(());
+            }
+            class = self.bases.get(name);
+        }
+
+        Err(type_error(
+            "Mismatched Types",
+            pos,
+            format!(
+                "expected `{}`, found `{}` which doesn't extend it",
+                expected, found
+            ),
+        ))
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::{BTreeMap, HashMap};
+
+use bzxc_shared::{Error, Node, Position, Token, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::substitution::Substitution;
+use crate::{type_error, TypeSystem};
+
+impl TypeSystem {
+    /*
+     * Records the signatures of the methods of an interface, ordered by name
+     */
+    pub(crate) fn interface_def(
+        &mut self,
+        name: Token,
+        methods: Vec<(Token, Vec<(Token, Option<Node>)>, Option<Node>)>,
+    ) -> Result<(), Error> {
+        let mut signatures = BTreeMap::new();
+        for (method, args, return_type) in methods {
+            let mut params = vec![];
+            for (arg, ty) in args {
+                match ty {
+                    Some(ty) => params.push(self.resolve_type(ty)?),
+                    None => {
+                        return Err(type_error(
+                            "Missing Type",
+                            (arg.pos_start, arg.pos_end),
+                            format!(
+                                "parameter `{}` of an interface method needs a type",
+                                arg.value.into_string()
+                            ),
+                        ))
+                    }
+                }
+            }
+
+            let ret = match return_type {
+                Some(ty) => self.resolve_type(ty)?,
+                None => Type::Null,
+            };
+            signatures.insert(method.value.into_string(), Type::Fun(params, box ret));
+        }
+
+        self.interfaces.insert(name.value.into_string(), signatures);
+        Ok(())
+    }
+
+    /*
+     * Checks the class `class` has every method of the interfaces it implements,
+     * inherited ones included
+     */
+    pub(crate) fn implements(
+        &mut self,
+        class: Token,
+        interfaces: Vec<Node>,
+        methods: &BTreeMap<String, TypedNode>,
+        base: Option<&Type>,
+    ) -> Result<(), Error> {
+        let inherited = match base.and_then(|base| base.id()) {
+            Some(id) => self.methods[&id].clone(),
+            None => HashMap::new(),
+        };
+
+        let mut names = vec![];
+        for node in interfaces {
+            let pos = node.get_pos();
+            let name = match self.resolve_type(node)? {
+                Type::Interface(name) => name,
+                _ => {
+                    return Err(type_error(
+                        "Invalid Interface",
+                        pos,
+                        "only interfaces can be implemented".to_string(),
+                    ))
+                }
+            };
+
+            for method in self.interfaces[&name].keys() {
+                if !methods.contains_key(method) && !inherited.contains_key(method) {
+                    return Err(type_error(
+                        "Missing Method",
+                        (class.pos_start, class.pos_end),
+                        format!(
+                            "`{}` doesn't implement `{}` of `{}`",
+                            class.value.into_string(),
+                            method,
+                            name
+                        ),
+                    ));
+                }
+            }
+            names.push(name);
+        }
+
+        self.implementations
+            .insert(class.value.into_string(), names);
+        Ok(())
+    }
+
+    /*
+     * Methods of a class have the signatures of the interfaces it implements,
+     * the ones returning `void` there may return anything, it's left unused
+     */
+    pub(crate) fn conform(
+        &self,
+        class: &str,
+        methods: &HashMap<String, Type>,
+        pos: (Position, Position),
+    ) -> Vec<Constraint> {
+        let mut constr = vec![];
+        for interface in self.implementations.get(class).cloned().unwrap_or_default() {
+            for (name, signature) in &self.interfaces[&interface] {
+                let signature = match signature {
+                    Type::Fun(params, ret) if **ret == Type::Null => {
+                        Type::Fun(params.clone(), box Type::fresh_var())
+                    }
+                    _ => signature.clone(),
+                };
+                constr.push(Constraint(signature, methods[name].clone(), pos));
+            }
+        }
+        constr
+    }
+
+    /*
+     * Whether objects of the type `found` can be used as `interface`,
+     * their class or one it extends has to implement it
+     */
+    pub(crate) fn check_implements(
+        &self,
+        interface: &str,
+        found: &Type,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        let mut class = found.id().and_then(|id| self.class_names.get(&id));
+        while let Some(name) = class {
+            if let Some(interfaces) = self.implementations.get(name) {
+                if interfaces
+                    .iter()
+                    .any(|implemented| implemented == interface)
+                {
+                    return Ok(());
+                }
+            }
+            class = self.bases.get(name);
+        }
+
+        Err(type_error(
+            "Mismatched Types",
+            pos,
+            format!(
+                "`{}` doesn't implement `{}`",
+                self.type_name(found),
+                interface
+            ),
+        ))
+    }
+
+    /*
+     * Slot of `method` in the dispatch table of values of the interface `ty`
+     */
+    pub(crate) fn interface_slot(
+        &self,
+        subs: &Substitution,
+        ty: Type,
+        method: &str,
+    ) -> Option<usize> {
+        match subs.apply_ty(ty) {
+            Type::Interface(name) => self.interfaces[&name]
+                .keys()
+                .position(|name| name == method),
+            _ => None,
+        }
+    }
+}
@@ -14,23 +14,79 @@
 #![allow(unused_variables)]
 
 use llvm_sys::prelude::LLVMContextRef;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use bzxc_shared::{LLVMNode, Node, Type};
+use bzxc_shared::{BindingId, Error, LLVMNode, Node, Position, Type, TypedNode};
+use calls::Calls;
+use cast::{Operands, Promote};
+use constraint::Constraint;
+use consts::Const;
+use generics::{ClassDef, ClassUse, Generic, Instance};
+use numeric::Numeric;
+use resolver::Resolver;
+use substitution::Substitution;
+use tuple::Access;
 use type_env::TypeEnv;
 
 mod annotate;
+mod arrays;
+mod calls;
+mod cast;
+mod coercion;
 mod constraint;
+mod consts;
+mod enums;
+mod generics;
+mod hoist;
+mod inheritance;
+mod interface;
 mod llvm_node;
+mod nullable;
+mod numeric;
+mod resolver;
 mod substitution;
+mod tuple;
 mod type_env;
 mod unifier;
+mod vals;
 
 pub struct TypeSystem {
     node: Node,
-    methods: HashMap<Type, HashMap<String, Type>>,
+    methods: HashMap<u32, HashMap<String, Type>>,
+    resolver: Resolver,
     type_env: TypeEnv,
-    class_env: HashMap<String, Type>,
+    class_env: HashMap<String, ClassDef>,
+    class_names: HashMap<u32, String>,
+    class_uses: HashMap<u32, ClassUse>,
+    class_properties: HashMap<String, BTreeMap<String, TypedNode>>,
+    bases: HashMap<String, String>,
+    supers: Vec<Option<Type>>,
+    interfaces: HashMap<String, BTreeMap<String, Type>>,
+    implementations: HashMap<String, Vec<String>>,
+    enums: HashMap<String, Vec<(String, Vec<Type>)>>,
+    pending_classes: Vec<TypedNode>,
+    type_names: TypeEnv<String>,
+    subst: Substitution,
+    operands: Vec<Operands>,
+    promotions: HashMap<i32, Promote>,
+    casts: Vec<Constraint>,
+    coercions: Vec<Constraint>,
+    tuple_accesses: Vec<Access>,
+    empty_arrays: Vec<(Type, (Position, Position))>,
+    calls: Calls,
+    numerics: HashMap<i32, Numeric>,
+    returns: Vec<Option<Type>>,
+    generics: HashMap<i32, Generic>,
+    type_params: HashMap<i32, Vec<Type>>,
+    instances: HashMap<i32, Instance>,
+    mono: BTreeMap<i32, Vec<Vec<Type>>>,
+    enclosing: Vec<i32>,
+    class_depth: usize,
+    narrowings: HashMap<i32, Type>,
+    declared: HashMap<Position, (BindingId, Type)>,
+    consts: HashMap<BindingId, Const>,
+    pub freeze_vals: bool,
+    pub checked_arith: bool,
     pub context: LLVMContextRef,
 }
 
@@ -39,16 +95,88 @@ impl TypeSystem {
         TypeSystem {
             node,
             methods: HashMap::new(),
+            resolver: Resolver::new(),
             type_env: TypeEnv::new(),
             class_env: HashMap::new(),
+            class_names: HashMap::new(),
+            class_uses: HashMap::new(),
+            class_properties: HashMap::new(),
+            bases: HashMap::new(),
+            supers: vec![],
+            interfaces: HashMap::new(),
+            implementations: HashMap::new(),
+            enums: HashMap::new(),
+            pending_classes: vec![],
+            type_names: TypeEnv::new(),
+            subst: Substitution::empty(),
+            operands: vec![],
+            promotions: HashMap::new(),
+            casts: vec![],
+            coercions: vec![],
+            tuple_accesses: vec![],
+            empty_arrays: vec![],
+            calls: Calls::default(),
+            numerics: HashMap::new(),
+            returns: vec![],
+            generics: HashMap::new(),
+            type_params: HashMap::new(),
+            instances: HashMap::new(),
+            mono: BTreeMap::new(),
+            enclosing: vec![],
+            class_depth: 0,
+            narrowings: HashMap::new(),
+            declared: HashMap::new(),
+            consts: HashMap::new(),
+            freeze_vals: false,
+            checked_arith: false,
             context,
         }
     }
 
-    pub fn llvm_node(&mut self) -> LLVMNode {
-        let annotation = self.annotate(self.node.clone());
-        let constraints = self.collect(annotation.clone());
-        let substitution = self.unify(constraints.clone());
-        self.gen(substitution, annotation)
+    pub fn llvm_node(&mut self) -> Result<LLVMNode, Error> {
+        let annotation = self.annotate_program(self.node.clone())?;
+        self.solve(&annotation)?;
+        self.default_numerics();
+        self.check_empty_arrays()?;
+        let annotation = self.fold_consts(annotation)?;
+        self.check_literals(&annotation)?;
+        let classes = self.instantiate_uses(&annotation)?;
+        // classes instantiated for generic functions are defined after the declarations
+        let annotation = match annotation {
+            TypedNode::Statements(mut stmts) => {
+                let at = stmts
+                    .iter()
+                    .rposition(|stmt| {
+                        matches!(stmt, TypedNode::Class { .. } | TypedNode::Extern { .. })
+                    })
+                    .map_or(0, |at| at + 1);
+                stmts.splice(at..at, classes);
+                TypedNode::Statements(stmts)
+            }
+            annotation => annotation,
+        };
+        let substitution = self.subst.clone();
+        Ok(self.gen(substitution, annotation))
     }
+
+    pub fn typed_node(&mut self) -> Result<TypedNode, Error> {
+        let annotation = self.annotate_program(self.node.clone())?;
+        self.solve(&annotation)?;
+        self.default_numerics();
+        self.check_empty_arrays()?;
+        let annotation = self.fold_consts(annotation)?;
+        self.check_literals(&annotation)?;
+        Ok(self.subst.apply_node(annotation))
+    }
+}
+
+/*
+ * Creates an error spanning `pos`, the description is formatted with the types involved
+ */
+pub(crate) fn type_error(
+    name: &'static str,
+    pos: (Position, Position),
+    description: String,
+) -> Error {
+    Error::new(name, pos.0, pos.1, description)
 }
@@ -10,14 +10,66 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
 */
-use bzxc_shared::{LLVMNode, Type, TypedNode};
+use bzxc_shared::{BindingId, LLVMNode, Type, TypedNode};
 
 use crate::substitution::Substitution;
 use crate::TypeSystem;
 
 impl TypeSystem {
     pub(crate) fn gen(&self, subs: Substitution, node: TypedNode) -> LLVMNode {
-        let llvm = |ty: Type| ty.llvm(self.context, subs.0.clone());
+        let id = match self.generic_id(&node) {
+            Some(id) => id,
+            None => return self.gen_node(subs, node),
+        };
+
+        /*
+         * Generic functions are emitted once for every instantiation, unused ones are dropped.
+         * Instances are named after their type arguments and looked up by that name,
+         * each of them gets a binding of its own
+         */
+        LLVMNode::Statements(
+            self.mono
+                .get(&id)
+                .cloned()
+                .unwrap_or_default()
+                .iter()
+                .map(|args| {
+                    let subs = self.instance_subst(&subs, id, args);
+                    let name = self.mangle(id, args);
+                    let fun = match node.clone() {
+                        TypedNode::Let { val, .. } => *val,
+                        fun => fun,
+                    };
+                    match fun {
+                        TypedNode::Fun {
+                            ty,
+                            pos,
+                            params,
+                            body,
+                            ..
+                        } => self.gen_node(
+                            subs,
+                            TypedNode::Fun {
+                                ty,
+                                pos,
+                                name,
+                                binding: BindingId::fresh(),
+                                params,
+                                body,
+                            },
+                        ),
+                        _ => unreachable!(),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn gen_node(&self, subs: Substitution, node: TypedNode) -> LLVMNode {
+        let llvm = |ty: Type| unsafe {
+            self.instantiated(&subs, ty)
+                .llvm(self.context, subs.0.clone())
+        };
         match node {
             TypedNode::Statements(stmts) => LLVMNode::Statements(
                 stmts
@@ -25,12 +77,14 @@ impl TypeSystem {
                     .map(|x| self.gen(subs.clone(), x.clone()))
                     .collect(),
             ),
-            TypedNode::Int { ty, val } => LLVMNode::Int { ty: llvm(ty), val },
-            TypedNode::Float { ty, val } => LLVMNode::Float { ty: llvm(ty), val },
-            TypedNode::Boolean { ty, val } => LLVMNode::Boolean { ty: llvm(ty), val },
-            TypedNode::Char { ty, val } => LLVMNode::Char { ty: llvm(ty), val },
-            TypedNode::String { ty, val } => LLVMNode::String { ty: llvm(ty), val },
-            TypedNode::Unary { ty, val, op_token } => LLVMNode::Unary {
+            TypedNode::Int { ty, val, .. } => LLVMNode::Int { ty: llvm(ty), val },
+            TypedNode::Float { ty, val, .. } => LLVMNode::Float { ty: llvm(ty), val },
+            TypedNode::Boolean { ty, val, .. } => LLVMNode::Boolean { ty: llvm(ty), val },
+            TypedNode::Char { ty, val, .. } => LLVMNode::Char { ty: llvm(ty), val },
+            TypedNode::String { ty, val, .. } => LLVMNode::String { ty: llvm(ty), val },
+            TypedNode::Unary {
+                ty, val, op_token, ..
+            } => LLVMNode::Unary {
                 ty: llvm(ty),
                 op_token,
                 val: box self.gen(subs, *val),
@@ -40,36 +94,70 @@ impl TypeSystem {
                 left,
                 right,
                 op_token,
-            } => LLVMNode::Binary {
-                ty: llvm(ty),
-                left: box self.gen(subs.clone(), *left),
-                right: box self.gen(subs, *right),
-                op_token,
-            },
+                ..
+            } => {
+                let signed = self.operand_type(&subs, &ty, &left, &right).is_signed();
+                let (left, right) = self.gen_operands(subs.clone(), &ty, *left, *right);
+                LLVMNode::Binary {
+                    ty: llvm(ty),
+                    left: box left,
+                    right: box right,
+                    op_token,
+                    signed,
+                }
+            }
             TypedNode::Fun {
                 ty,
                 name,
+                binding,
                 params,
                 body,
+                ..
             } => LLVMNode::Fun {
                 body: box self.gen(subs.clone(), *body),
                 ty: llvm(ty),
                 name,
+                binding,
                 params: params
                     .iter()
-                    .map(|x| (x.name.clone(), llvm(x.ty.clone())))
+                    .map(|x| (x.binding, x.name.clone(), llvm(x.ty.clone())))
                     .collect(),
             },
-            TypedNode::Let { ty, name, val } | TypedNode::ReLet { ty, name, val, .. } => {
-                LLVMNode::Let {
-                    ty: llvm(ty),
-                    name,
-                    val: box self.gen(subs, *val),
-                }
+            TypedNode::Let {
+                ty,
+                name,
+                binding,
+                val,
+                ..
             }
+            | TypedNode::ReLet {
+                ty,
+                name,
+                binding,
+                val,
+                ..
+            } => LLVMNode::Let {
+                ty: llvm(ty),
+                name,
+                binding,
+                val: box self.gen(subs, *val),
+            },
 
-            TypedNode::Var { ty, name } => LLVMNode::Var { ty: llvm(ty), name },
-            TypedNode::Call { ty, fun, args } => LLVMNode::Call {
+            TypedNode::Var {
+                ty, name, binding, ..
+            } => match self.instance_name(&subs, &ty) {
+                Some(instance) => LLVMNode::Var {
+                    name: instance,
+                    binding: None,
+                    ty: llvm(ty),
+                },
+                None => LLVMNode::Var {
+                    name,
+                    binding: Some(binding),
+                    ty: llvm(ty),
+                },
+            },
+            TypedNode::Call { ty, fun, args, .. } => LLVMNode::Call {
                 ty: llvm(ty),
                 args: args
                     .iter()
@@ -77,33 +165,38 @@ impl TypeSystem {
                     .collect(),
                 fun: box self.gen(subs, *fun),
             },
-            TypedNode::Return { ty, val } => LLVMNode::Return {
+            TypedNode::Return { ty, val, .. } => LLVMNode::Return {
                 ty: llvm(ty),
                 val: box self.gen(subs, *val),
             },
-            TypedNode::Null { ty } => LLVMNode::Null { ty: llvm(ty) },
+            TypedNode::Null { ty, .. } => LLVMNode::Null { ty: llvm(ty) },
             TypedNode::If {
                 ty,
                 cases,
                 else_case,
-            } => LLVMNode::If {
-                ty: llvm(ty),
-                cases: cases
-                    .iter()
-                    .map(|(cond, body)| {
-                        (
-                            self.gen(subs.clone(), cond.clone()),
-                            self.gen(subs.clone(), body.clone()),
-                        )
-                    })
-                    .collect(),
-                else_case: if let Some(els) = else_case {
-                    Some(box self.gen(subs, *els))
-                } else {
-                    None
-                },
-            },
-            TypedNode::While { ty, cond, body } => LLVMNode::While {
+                ..
+            } => {
+                // branches of an `if` which is `any` are boxed when they aren't
+                let any = subs.apply_ty(ty.clone()) == Type::Any;
+                let branch = |body: TypedNode| {
+                    if any {
+                        self.gen_convert(subs.clone(), Type::Any, body)
+                    } else {
+                        self.gen(subs.clone(), body)
+                    }
+                };
+                LLVMNode::If {
+                    ty: llvm(ty),
+                    cases: cases
+                        .iter()
+                        .map(|(cond, body)| {
+                            (self.gen(subs.clone(), cond.clone()), branch(body.clone()))
+                        })
+                        .collect(),
+                    else_case: else_case.map(|els| box branch(*els)),
+                }
+            }
+            TypedNode::While { ty, cond, body, .. } => LLVMNode::While {
                 ty: llvm(ty),
                 cond: box self.gen(subs.clone(), *cond),
                 body: box self.gen(subs, *body),
@@ -111,42 +204,45 @@ impl TypeSystem {
             TypedNode::For {
                 ty,
                 var,
+                binding,
                 start,
                 end,
                 step,
                 body,
+                ..
             } => LLVMNode::For {
                 ty: llvm(ty),
                 body: box self.gen(subs.clone(), *body),
                 var,
+                binding,
                 end: box self.gen(subs.clone(), *end),
                 start: box self.gen(subs.clone(), *start),
                 step: box self.gen(subs.clone(), *step),
             },
-            TypedNode::Array { ty, elements } => LLVMNode::Array {
+            TypedNode::Array { ty, elements, .. } => LLVMNode::Array {
                 ty: llvm(ty),
                 elements: elements
                     .iter()
                     .map(|elem| self.gen(subs.clone(), elem.clone()))
                     .collect(),
             },
-            TypedNode::Index { ty, array, idx } => LLVMNode::Index {
+            TypedNode::Index { ty, array, idx, .. } => LLVMNode::Index {
                 ty: llvm(ty),
                 array: box self.gen(subs.clone(), *array),
                 idx: box self.gen(subs.clone(), *idx),
             },
-            TypedNode::Object { ty, properties } => LLVMNode::Object {
+            TypedNode::Object { ty, properties, .. } => LLVMNode::Object {
                 ty: llvm(ty),
                 properties: properties
                     .iter()
                     .map(|(name, node)| (name.clone(), self.gen(subs.clone(), node.clone())))
                     .collect(),
             },
-            TypedNode::CObject { ty, object } => LLVMNode::CObject {
+            TypedNode::CObject { ty, object, .. } => LLVMNode::CObject {
                 ty: llvm(ty),
                 object: box self.gen(subs.clone(), *object),
             },
-            TypedNode::CToBzxObject { ty, object } => LLVMNode::CToBzxObject {
+            TypedNode::CToBzxObject { ty, object, .. } => LLVMNode::CToBzxObject {
                 ty: llvm(ty),
                 object: box self.gen(subs.clone(), *object),
             },
@@ -154,6 +250,7 @@ impl TypeSystem {
                 ty,
                 object,
                 property,
+                ..
             } => LLVMNode::ObjectAccess {
                 ty: llvm(ty),
                 object: box self.gen(subs, *object),
@@ -164,6 +261,7 @@ impl TypeSystem {
                 property,
                 object,
                 new_val,
+                ..
             } => LLVMNode::ObjectEdit {
                 ty: llvm(ty),
                 new_val: box self.gen(subs.clone(), *new_val),
@@ -175,6 +273,29 @@ impl TypeSystem {
                 property,
                 object,
                 args,
+                ..
+            } if self
+                .interface_slot(&subs, object.get_type(), &property)
+                .is_some() =>
+            {
+                LLVMNode::InterfaceCall {
+                    ty: llvm(ty),
+                    slot: self
+                        .interface_slot(&subs, object.get_type(), &property)
+                        .unwrap(),
+                    args: args
+                        .iter()
+                        .map(|x| self.gen(subs.clone(), x.clone()))
+                        .collect(),
+                    object: box self.gen(subs, *object),
+                }
+            }
+            TypedNode::ObjectMethodCall {
+                ty,
+                property,
+                object,
+                args,
+                ..
             } => LLVMNode::ObjectMethodCall {
                 ty: llvm(ty),
                 property,
@@ -190,25 +311,38 @@ impl TypeSystem {
                 methods,
                 constructor,
                 name,
+                binding,
                 static_obj,
+                base,
+                ..
             } => LLVMNode::Class {
+                // properties are stored in the order of the fields of the object
+                properties: match subs.apply_ty(ty.clone()) {
+                    Type::Class(obj) => obj.fields(),
+                    _ => unreachable!(),
+                }
+                .into_iter()
+                .map(|(name, _)| {
+                    let node = properties[&name].clone();
+                    (name, self.gen(subs.clone(), node))
+                })
+                .collect(),
                 ty: llvm(ty),
                 name,
+                binding,
                 constructor: box self.gen(subs.clone(), *constructor),
-                properties: properties
-                    .iter()
-                    .map(|(name, node)| (name.clone(), self.gen(subs.clone(), node.clone())))
-                    .collect(),
                 methods: methods
                     .iter()
                     .map(|(name, node)| (name.clone(), self.gen(subs.clone(), node.clone())))
                     .collect(),
                 static_obj: box self.gen(subs.clone(), *static_obj),
+                base: base.map(llvm),
             },
             TypedNode::ClassInit {
                 ty,
                 class,
                 constructor_params,
+                ..
             } => LLVMNode::ClassInit {
                 ty: llvm(ty),
                 class: llvm(class),
@@ -223,6 +357,7 @@ impl TypeSystem {
                 args,
                 ty,
                 var_args,
+                ..
             } => LLVMNode::Extern {
                 ty: llvm(ty),
                 return_type: box self.gen(subs.clone(), *return_type),
@@ -233,6 +368,80 @@ impl TypeSystem {
                     .collect(),
                 var_args,
             },
+            TypedNode::Cast { ty, val, .. } => self.gen_cast(subs, ty, *val),
+            TypedNode::Convert { ty, val, .. } => self.gen_convert(subs, ty, *val),
+            TypedNode::Super { ty, .. } => LLVMNode::Super { ty: llvm(ty) },
+            TypedNode::NullCheck { ty, val, equal, .. } => LLVMNode::NullCheck {
+                ty: llvm(ty),
+                val: box self.gen(subs, *val),
+                equal,
+            },
+            TypedNode::Unwrap { ty, val, .. } => LLVMNode::Unwrap {
+                ty: llvm(ty),
+                val: box self.gen(subs, *val),
+            },
+            TypedNode::SafeAccess {
+                ty,
+                name,
+                binding,
+                object,
+                access,
+                ..
+            } => LLVMNode::SafeAccess {
+                ty: llvm(ty),
+                name,
+                binding,
+                object: box self.gen(subs.clone(), *object),
+                access: box self.gen(subs, *access),
+            },
+            TypedNode::Elvis {
+                ty, val, default, ..
+            } => LLVMNode::Elvis {
+                ty: llvm(ty),
+                val: box self.gen(subs.clone(), *val),
+                default: box self.gen(subs, *default),
+            },
+            TypedNode::Variant {
+                ty, tag, payload, ..
+            } => LLVMNode::Variant {
+                ty: llvm(ty),
+                tag,
+                payload: payload
+                    .into_iter()
+                    .map(|val| self.gen(subs.clone(), val))
+                    .collect(),
+            },
+            TypedNode::Match { ty, val, arms, .. } => LLVMNode::Match {
+                ty: llvm(ty),
+                val: box self.gen(subs.clone(), *val),
+                arms: arms
+                    .into_iter()
+                    .map(|(tag, bindings, body)| {
+                        (
+                            tag,
+                            bindings
+                                .into_iter()
+                                .map(|binder| (binder.binding, binder.name, llvm(binder.ty)))
+                                .collect(),
+                            self.gen(subs.clone(), body),
+                        )
+                    })
+                    .collect(),
+            },
+            TypedNode::Tuple { ty, elements, .. } => LLVMNode::Tuple {
+                ty: llvm(ty),
+                elements: elements
+                    .into_iter()
+                    .map(|val| self.gen(subs.clone(), val))
+                    .collect(),
+            },
+            TypedNode::TupleAccess {
+                ty, tuple, index, ..
+            } => LLVMNode::TupleAccess {
+                ty: llvm(ty),
+                tuple: box self.gen(subs.clone(), *tuple),
+                index,
+            },
         }
     }
 }
@@ -0,0 +1,117 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{BindingId, Error, Node, Position, Token, Tokens, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::TypeSystem;
+
+impl TypeSystem {
+    /*
+     * Variable compared to `null` by a condition, with `==` when `equal` is set and `!=` otherwise
+     */
+    pub(crate) fn null_checked(cond: &Node, equal: bool) -> Option<(String, (Position, Position))> {
+        let op = if equal {
+            Tokens::DoubleEquals
+        } else {
+            Tokens::NotEquals
+        };
+
+        match cond {
+            Node::BinaryNode {
+                left,
+                right,
+                op_token,
+            } if op_token.value == op => match (&**left, &**right) {
+                (Node::VarAccessNode { token }, Node::NullNode { .. })
+                | (Node::NullNode { .. }, Node::VarAccessNode { token }) => {
+                    Some((token.value.into_string(), cond.get_pos()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /*
+     * Value compared to `null` by a `==` or `!=`
+     */
+    pub(crate) fn null_compared(left: &Node, right: &Node, op_token: &Token) -> Option<Node> {
+        if ![Tokens::DoubleEquals, Tokens::NotEquals].contains(&op_token.value) {
+            return None;
+        }
+
+        match (left, right) {
+            (Node::NullNode { .. }, value) | (value, Node::NullNode { .. }) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /*
+     * Inside the current scope the nullable variable `name` is known not to be null,
+     * it's read as the type it wraps
+     */
+    pub(crate) fn narrow(&mut self, name: &str, pos: (Position, Position)) -> Result<(), Error> {
+        let (binding, ty) = match self.lookup(name) {
+            Some((_, Type::Var(id))) if self.narrowings.contains_key(&id) => return Ok(()),
+            Some(bound) => bound,
+            None => return Ok(()),
+        };
+
+        let inner = Type::fresh_var();
+        let constraint = Constraint(
+            self.subst.apply_ty(ty.clone()),
+            Type::Nullable(box inner.clone()),
+            pos,
+        );
+        let substitution = self.unify(vec![constraint])?;
+        self.subst = self.subst.compose(substitution);
+
+        if let Type::Var(id) = inner {
+            self.narrowings.insert(id, ty);
+        }
+        self.type_env.set(binding, inner);
+        Ok(())
+    }
+
+    /*
+     * Type of the nullable variable the narrowed type `ty` was made for
+     */
+    pub(crate) fn narrowed(&self, ty: &Type) -> Option<Type> {
+        match ty {
+            Type::Var(id) => self.narrowings.get(id).cloned(),
+            _ => None,
+        }
+    }
+
+    /*
+     * Reads a variable of the narrowed type `ty`
+     */
+    pub(crate) fn unwrap_var(
+        &self,
+        ty: Type,
+        name: String,
+        binding: BindingId,
+        pos: (Position, Position),
+    ) -> TypedNode {
+        TypedNode::Unwrap {
+            val: box TypedNode::Var {
+                ty: self.narrowed(&ty).unwrap(),
+                pos,
+                name,
+                binding,
+            },
+            ty,
+            pos,
+        }
+    }
+}
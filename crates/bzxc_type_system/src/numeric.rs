@@ -0,0 +1,225 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::BTreeMap;
+
+use bzxc_shared::{walk, Error, Position, Type, TypedNode, Visitor};
+
+use crate::substitution::Substitution;
+use crate::{type_error, TypeSystem};
+
+/*
+ * What an unsuffixed literal may become
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Numeric {
+    Int,
+    Float,
+}
+
+impl Numeric {
+    fn accepts(self, ty: &Type) -> bool {
+        match self {
+            Numeric::Int => ty.is_int(),
+            Numeric::Float => ty.is_float(),
+        }
+    }
+
+    /*
+     * Width the literal gets when nothing else decides it, the ones of C
+     */
+    fn default(self) -> Type {
+        match self {
+            Numeric::Int => Type::I32,
+            Numeric::Float => Type::F64,
+        }
+    }
+}
+
+impl TypeSystem {
+    /*
+     * A type variable which can only be bound to a type of the `numeric` kind
+     */
+    pub(crate) fn numeric_var(&mut self, numeric: Numeric) -> Type {
+        let ty = Type::fresh_var();
+        if let Type::Var(id) = ty {
+            self.numerics.insert(id, numeric);
+        }
+        ty
+    }
+
+    pub(crate) fn numeric(&self, ty: &Type) -> Option<Numeric> {
+        match ty {
+            Type::Var(id) => self.numerics.get(id).cloned(),
+            _ => None,
+        }
+    }
+
+    /*
+     * Checks the variable of `expected` or `found` can be bound to the other type,
+     * variables bound together share their kind
+     */
+    pub(crate) fn check_numeric(
+        &mut self,
+        expected: &Type,
+        found: &Type,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        let (tvar, ty) = match (expected, found) {
+            (Type::Var(tvar), ty) | (ty, Type::Var(tvar)) => (*tvar, ty),
+            _ => return Ok(()),
+        };
+        let numeric = match self.numerics.get(&tvar) {
+            Some(numeric) => *numeric,
+            None => return Ok(()),
+        };
+
+        let accepted = match ty {
+            Type::Var(id) => match self.numerics.get(id) {
+                Some(other) => *other == numeric,
+                None => {
+                    self.numerics.insert(*id, numeric);
+                    true
+                }
+            },
+            _ => numeric.accepts(ty),
+        };
+
+        if accepted {
+            Ok(())
+        } else {
+            Err(type_error(
+                "Mismatched Types",
+                pos,
+                format!(
+                    "expected {}, found {}",
+                    self.describe(expected),
+                    self.describe(found)
+                ),
+            ))
+        }
+    }
+
+    /*
+     * Type as shown in errors, literals which aren't decided yet are shown by their kind
+     */
+    pub(crate) fn describe(&self, ty: &Type) -> String {
+        match self.numeric(ty) {
+            Some(Numeric::Int) => "`{integer}`".to_string(),
+            Some(Numeric::Float) => "`{float}`".to_string(),
+            None => format!("`{}`", ty),
+        }
+    }
+
+    /*
+     * Integer literals have to fit in the type they're given once it's known,
+     * constants are folded first so `-128` is a single `i8` literal
+     */
+    pub(crate) fn check_literals(&self, node: &TypedNode) -> Result<(), Error> {
+        let mut literals = Literals {
+            type_system: self,
+            error: None,
+        };
+        literals.visit(node);
+        match literals.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /*
+     * Settles a literal on its default width right away, other types are returned as they are
+     */
+    pub(crate) fn decide_numeric(&mut self, ty: Type) -> Type {
+        match self.numeric(&ty) {
+            Some(numeric) => {
+                let default = Substitution(BTreeMap::from([(ty, numeric.default())]));
+                self.subst = self.subst.compose(default);
+                numeric.default()
+            }
+            None => ty,
+        }
+    }
+
+    /*
+     * Gives every literal nothing decided the type of its default width,
+     * variables of generic bindings are left to their instantiations
+     */
+    pub(crate) fn default_numerics(&mut self) {
+        let mut numerics = self.numerics.clone().into_iter().collect::<Vec<_>>();
+        numerics.sort_by_key(|(id, _)| *id);
+
+        for (id, _) in numerics {
+            let ty = self.subst.apply_ty(Type::Var(id));
+            let generic = self.generics.values().any(|generic| {
+                generic
+                    .vars
+                    .iter()
+                    .any(|var| self.subst.apply_ty(Type::Var(*var)) == ty)
+            });
+
+            if !generic {
+                self.decide_numeric(ty);
+            }
+        }
+    }
+}
+
+/*
+ * Smallest and largest values of an integer type
+ */
+fn range(ty: &Type) -> Option<(i128, i128)> {
+    let bits = match ty {
+        Type::I8 | Type::U8 => 8,
+        Type::I16 | Type::U16 => 16,
+        Type::I32 | Type::U32 => 32,
+        Type::I64 | Type::U64 => 64,
+        Type::I128 => return Some((i128::MIN, i128::MAX)),
+        Type::U128 => return Some((0, i128::MAX)),
+        _ => return None,
+    };
+
+    Some(if ty.is_signed() {
+        (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+    } else {
+        (0, (1 << bits) - 1)
+    })
+}
+
+/*
+ * Finds the first integer literal which doesn't fit in its type
+ */
+struct Literals<'a> {
+    type_system: &'a TypeSystem,
+    error: Option<Error>,
+}
+
+impl Visitor<TypedNode> for Literals<'_> {
+    fn visit(&mut self, node: &TypedNode) {
+        match node {
+            TypedNode::Int { ty, pos, val } if self.error.is_none() => {
+                let ty = self.type_system.subst.apply_ty(ty.clone());
+                match range(&ty) {
+                    Some((min, max)) if *val < min || *val > max => {
+                        self.error = Some(type_error(
+                            "Out Of Range",
+                            *pos,
+                            format!("literal out of range for `{}`", ty),
+                        ))
+                    }
+                    _ => (),
+                }
+            }
+            _ => walk(self, node),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use std::collections::HashMap;
+
+use bzxc_shared::{BindingId, Error, Position, Token, Type};
+
+use crate::{type_error, TypeSystem};
+
+/*
+ * Names in scope and the bindings they resolve to. Every binding gets an id of its own,
+ * uses carry the id of the binding they resolve to so the type system and codegen
+ * never mix up bindings sharing a name
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct Resolver {
+    scopes: Vec<HashMap<String, BindingId>>,
+    // where the `val`s are bound, they can't be reassigned
+    vals: HashMap<BindingId, (Position, Position)>,
+    // how many functions the variables are bound in, every function has a frame of its own
+    depths: HashMap<BindingId, usize>,
+    depth: usize,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            vals: HashMap::new(),
+            depths: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    /*
+     * Binds `name` in the innermost scope, hiding the bindings of it in the outer ones
+     */
+    pub fn bind(&mut self, name: String) -> BindingId {
+        let binding = self.bind_static(name);
+        self.depths.insert(binding, self.depth);
+        binding
+    }
+
+    /*
+     * Binds `name` to what isn't kept in the frame of a function, like a function or a class,
+     * every function can use it
+     */
+    pub fn bind_static(&mut self, name: String) -> BindingId {
+        let binding = BindingId::fresh();
+        self.scopes.last_mut().unwrap().insert(name, binding);
+        binding
+    }
+
+    /*
+     * Binds `name` to an existing binding in the innermost scope
+     */
+    pub fn rebind(&mut self, name: String, binding: BindingId) -> BindingId {
+        self.scopes.last_mut().unwrap().insert(name, binding);
+        binding
+    }
+
+    /*
+     * Binds `soul` in the innermost scope, it's the same binding in every class
+     */
+    pub fn bind_soul(&mut self) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert("soul".to_string(), BindingId::SOUL);
+    }
+
+    pub fn resolve(&self, name: &str) -> Option<BindingId> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    /*
+     * Where `binding` is bound if it's a `val`
+     */
+    pub fn val(&self, binding: BindingId) -> Option<(Position, Position)> {
+        self.vals.get(&binding).copied()
+    }
+
+    /*
+     * Whether `binding` is a variable of a function the current one is nested in
+     */
+    pub fn captured(&self, binding: BindingId) -> bool {
+        matches!(self.depths.get(&binding), Some(depth) if *depth < self.depth)
+    }
+
+    pub fn enter_fun(&mut self) {
+        self.depth += 1;
+    }
+
+    pub fn exit_fun(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+}
+
+impl TypeSystem {
+    /*
+     * Binds `name` to a new binding of type `ty` in the current scope
+     */
+    pub(crate) fn bind(&mut self, name: String, ty: Type) -> BindingId {
+        let binding = self.resolver.bind(name);
+        self.type_env.set(binding, ty);
+        binding
+    }
+
+    /*
+     * Binds a function, an extern or a class, which any function can use
+     */
+    pub(crate) fn bind_static(&mut self, name: String, ty: Type) -> BindingId {
+        let binding = self.resolver.bind_static(name);
+        self.type_env.set(binding, ty);
+        binding
+    }
+
+    /*
+     * Binds a `val`, `pos` is where it's bound
+     */
+    pub(crate) fn bind_val(
+        &mut self,
+        name: String,
+        ty: Type,
+        pos: (Position, Position),
+    ) -> BindingId {
+        let binding = self.bind(name, ty);
+        self.resolver.vals.insert(binding, pos);
+        binding
+    }
+
+    /*
+     * Binding `name` resolves to and its type
+     */
+    pub(crate) fn lookup(&self, name: &str) -> Option<(BindingId, Type)> {
+        let binding = self.resolver.resolve(name)?;
+        Some((binding, self.type_env.get(binding)?))
+    }
+
+    /*
+     * Binding the variable `name` resolves to, functions are compiled on their own so they
+     * can't use the variables of the ones they're nested in, `const`s are folded into their uses
+     */
+    pub(crate) fn lookup_var(&self, name: &Token) -> Result<Option<(BindingId, Type)>, Error> {
+        let bound = self.lookup(&name.value.into_string());
+        match bound {
+            Some((binding, _))
+                if self.resolver.captured(binding) && !self.consts.contains_key(&binding) =>
+            {
+                Err(type_error(
+                    "Captured Variable",
+                    (name.pos_start, name.pos_end),
+                    format!("functions can't capture `{}`", name.value.into_string()),
+                ))
+            }
+            _ => Ok(bound),
+        }
+    }
+
+    pub(crate) fn push_scope(&mut self) {
+        self.resolver.push_scope();
+        self.type_env.push_scope();
+    }
+
+    pub(crate) fn pop_scope(&mut self) {
+        self.resolver.pop_scope();
+        self.type_env.pop_scope();
+    }
+}
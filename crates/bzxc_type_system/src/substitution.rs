@@ -12,7 +12,7 @@
 */
 use std::collections::BTreeMap;
 
-use bzxc_shared::Type;
+use bzxc_shared::{Binder, Type, TypedNode};
 
 use crate::constraint::Constraint;
 
@@ -30,6 +30,7 @@ impl Substitution {
                 Constraint(
                     self.apply_ty(constraint.0.clone()),
                     self.apply_ty(constraint.1.clone()),
+                    constraint.2,
                 )
             })
             .collect()
@@ -56,7 +57,8 @@ impl Substitution {
                 box self.substitute_tvar(*ret.clone(), tvar, sol_ty.clone()),
             ),
             Type::Array(ty, size) => Type::Array(box self.substitute_tvar(*ty, tvar, sol_ty), size),
-            Type::Object(ty) => Type::Object(
+            Type::Object(id, ty) => Type::Object(
+                id,
                 ty.iter()
                     .map(|(name, ty)| {
                         (
@@ -67,6 +69,13 @@ impl Substitution {
                     .collect(),
             ),
             Type::Class(obj) => Type::Class(box self.substitute_tvar(*obj, tvar, sol_ty)),
+            Type::Nullable(ty) => Type::Nullable(box self.substitute_tvar(*ty, tvar, sol_ty)),
+            Type::Tuple(elements) => Type::Tuple(
+                elements
+                    .into_iter()
+                    .map(|ty| self.substitute_tvar(ty, tvar, sol_ty.clone()))
+                    .collect(),
+            ),
             Type::Var(tvar2) => {
                 if tvar == tvar2 {
                     sol_ty
@@ -88,4 +97,432 @@ impl Substitution {
         self_substituded.extend(other.0);
         Substitution(self_substituded)
     }
+
+    /*
+     * Applies the substitution to every type inside a typed node
+     */
+    pub fn apply_node(&self, node: TypedNode) -> TypedNode {
+        let ty = |ty: Type| self.apply_ty(ty);
+        let node_box = |node: Box<TypedNode>| box self.apply_node(*node);
+        let nodes = |nodes: Vec<TypedNode>| {
+            nodes
+                .into_iter()
+                .map(|node| self.apply_node(node))
+                .collect::<Vec<_>>()
+        };
+        let tree = |tree: BTreeMap<String, TypedNode>| {
+            tree.into_iter()
+                .map(|(name, node)| (name, self.apply_node(node)))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        match node {
+            TypedNode::Statements(stmts) => TypedNode::Statements(nodes(stmts)),
+            TypedNode::Int { ty: t, pos, val } => TypedNode::Int {
+                ty: ty(t),
+                pos,
+                val,
+            },
+            TypedNode::Float { ty: t, pos, val } => TypedNode::Float {
+                ty: ty(t),
+                pos,
+                val,
+            },
+            TypedNode::Boolean { ty: t, pos, val } => TypedNode::Boolean {
+                ty: ty(t),
+                pos,
+                val,
+            },
+            TypedNode::Char { ty: t, pos, val } => TypedNode::Char {
+                ty: ty(t),
+                pos,
+                val,
+            },
+            TypedNode::String { ty: t, pos, val } => TypedNode::String {
+                ty: ty(t),
+                pos,
+                val,
+            },
+            TypedNode::Unary {
+                ty: t,
+                pos,
+                val,
+                op_token,
+            } => TypedNode::Unary {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+                op_token,
+            },
+            TypedNode::Binary {
+                ty: t,
+                pos,
+                left,
+                right,
+                op_token,
+            } => TypedNode::Binary {
+                ty: ty(t),
+                pos,
+                left: node_box(left),
+                right: node_box(right),
+                op_token,
+            },
+            TypedNode::Fun {
+                ty: t,
+                pos,
+                name,
+                binding,
+                params,
+                body,
+            } => TypedNode::Fun {
+                ty: ty(t),
+                pos,
+                name,
+                binding,
+                params: params
+                    .into_iter()
+                    .map(|param| Binder {
+                        ty: ty(param.ty),
+                        name: param.name,
+                        binding: param.binding,
+                    })
+                    .collect(),
+                body: node_box(body),
+            },
+            TypedNode::Let {
+                ty: t,
+                pos,
+                name,
+                binding,
+                val,
+            } => TypedNode::Let {
+                ty: ty(t),
+                pos,
+                name,
+                binding,
+                val: node_box(val),
+            },
+            TypedNode::ReLet {
+                ty: t,
+                pos,
+                prev,
+                name,
+                binding,
+                val,
+            } => TypedNode::ReLet {
+                ty: ty(t),
+                pos,
+                prev: ty(prev),
+                name,
+                binding,
+                val: node_box(val),
+            },
+            TypedNode::Var {
+                ty: t,
+                pos,
+                name,
+                binding,
+            } => TypedNode::Var {
+                ty: ty(t),
+                pos,
+                name,
+                binding,
+            },
+            TypedNode::Call {
+                ty: t,
+                pos,
+                fun,
+                args,
+            } => TypedNode::Call {
+                ty: ty(t),
+                pos,
+                fun: node_box(fun),
+                args: nodes(args),
+            },
+            TypedNode::Return { ty: t, pos, val } => TypedNode::Return {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+            },
+            TypedNode::Null { ty: t, pos } => TypedNode::Null { ty: ty(t), pos },
+            TypedNode::If {
+                ty: t,
+                pos,
+                cases,
+                else_case,
+            } => TypedNode::If {
+                ty: ty(t),
+                pos,
+                cases: cases
+                    .into_iter()
+                    .map(|(cond, body)| (self.apply_node(cond), self.apply_node(body)))
+                    .collect(),
+                else_case: else_case.map(node_box),
+            },
+            TypedNode::While {
+                ty: t,
+                pos,
+                cond,
+                body,
+            } => TypedNode::While {
+                ty: ty(t),
+                pos,
+                cond: node_box(cond),
+                body: node_box(body),
+            },
+            TypedNode::For {
+                ty: t,
+                pos,
+                var,
+                binding,
+                start,
+                end,
+                step,
+                body,
+            } => TypedNode::For {
+                ty: ty(t),
+                pos,
+                var,
+                binding,
+                start: node_box(start),
+                end: node_box(end),
+                step: node_box(step),
+                body: node_box(body),
+            },
+            TypedNode::Array {
+                ty: t,
+                pos,
+                elements,
+            } => TypedNode::Array {
+                ty: ty(t),
+                pos,
+                elements: nodes(elements),
+            },
+            TypedNode::Index {
+                ty: t,
+                pos,
+                array,
+                idx,
+            } => TypedNode::Index {
+                ty: ty(t),
+                pos,
+                array: node_box(array),
+                idx: node_box(idx),
+            },
+            TypedNode::Object {
+                ty: t,
+                pos,
+                properties,
+            } => TypedNode::Object {
+                ty: ty(t),
+                pos,
+                properties: tree(properties),
+            },
+            TypedNode::CObject { ty: t, pos, object } => TypedNode::CObject {
+                ty: ty(t),
+                pos,
+                object: node_box(object),
+            },
+            TypedNode::CToBzxObject { ty: t, pos, object } => TypedNode::CToBzxObject {
+                ty: ty(t),
+                pos,
+                object: node_box(object),
+            },
+            TypedNode::ObjectAccess {
+                ty: t,
+                pos,
+                object,
+                property,
+            } => TypedNode::ObjectAccess {
+                ty: ty(t),
+                pos,
+                object: node_box(object),
+                property,
+            },
+            TypedNode::ObjectEdit {
+                ty: t,
+                pos,
+                object,
+                new_val,
+                property,
+            } => TypedNode::ObjectEdit {
+                ty: ty(t),
+                pos,
+                object: node_box(object),
+                new_val: node_box(new_val),
+                property,
+            },
+            TypedNode::ObjectMethodCall {
+                ty: t,
+                pos,
+                object,
+                property,
+                args,
+            } => TypedNode::ObjectMethodCall {
+                ty: ty(t),
+                pos,
+                object: node_box(object),
+                property,
+                args: nodes(args),
+            },
+            TypedNode::Class {
+                name,
+                binding,
+                ty: t,
+                pos,
+                properties,
+                methods,
+                constructor,
+                static_obj,
+                base,
+            } => TypedNode::Class {
+                name,
+                binding,
+                ty: ty(t),
+                pos,
+                properties: tree(properties),
+                methods: tree(methods),
+                constructor: node_box(constructor),
+                static_obj: node_box(static_obj),
+                base: base.map(ty),
+            },
+            TypedNode::ClassInit {
+                ty: t,
+                pos,
+                class,
+                constructor_params,
+            } => TypedNode::ClassInit {
+                ty: ty(t),
+                pos,
+                class: ty(class),
+                constructor_params: nodes(constructor_params),
+            },
+            TypedNode::Extern {
+                ty: t,
+                pos,
+                name,
+                return_type,
+                args,
+                var_args,
+            } => TypedNode::Extern {
+                ty: ty(t),
+                pos,
+                name,
+                return_type: node_box(return_type),
+                args: nodes(args),
+                var_args,
+            },
+            TypedNode::Cast { ty: t, pos, val } => TypedNode::Cast {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+            },
+            TypedNode::Convert { ty: t, pos, val } => TypedNode::Convert {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+            },
+            TypedNode::Super { ty: t, pos } => TypedNode::Super { ty: ty(t), pos },
+            TypedNode::NullCheck {
+                ty: t,
+                pos,
+                val,
+                equal,
+            } => TypedNode::NullCheck {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+                equal,
+            },
+            TypedNode::Unwrap { ty: t, pos, val } => TypedNode::Unwrap {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+            },
+            TypedNode::SafeAccess {
+                ty: t,
+                pos,
+                name,
+                binding,
+                object,
+                access,
+            } => TypedNode::SafeAccess {
+                ty: ty(t),
+                pos,
+                name,
+                binding,
+                object: node_box(object),
+                access: node_box(access),
+            },
+            TypedNode::Elvis {
+                ty: t,
+                pos,
+                val,
+                default,
+            } => TypedNode::Elvis {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+                default: node_box(default),
+            },
+            TypedNode::Variant {
+                ty: t,
+                pos,
+                tag,
+                payload,
+            } => TypedNode::Variant {
+                ty: ty(t),
+                pos,
+                tag,
+                payload: nodes(payload),
+            },
+            TypedNode::Match {
+                ty: t,
+                pos,
+                val,
+                arms,
+            } => TypedNode::Match {
+                ty: ty(t),
+                pos,
+                val: node_box(val),
+                arms: arms
+                    .into_iter()
+                    .map(|(tag, bindings, body)| {
+                        (
+                            tag,
+                            bindings
+                                .into_iter()
+                                .map(|binder| Binder {
+                                    ty: ty(binder.ty),
+                                    name: binder.name,
+                                    binding: binder.binding,
+                                })
+                                .collect(),
+                            self.apply_node(body),
+                        )
+                    })
+                    .collect(),
+            },
+            TypedNode::Tuple {
+                ty: t,
+                pos,
+                elements,
+            } => TypedNode::Tuple {
+                ty: ty(t),
+                pos,
+                elements: nodes(elements),
+            },
+            TypedNode::TupleAccess {
+                ty: t,
+                pos,
+                tuple,
+                index,
+            } => TypedNode::TupleAccess {
+                ty: ty(t),
+                pos,
+                tuple: node_box(tuple),
+                index,
+            },
+        }
+    }
 }
@@ -0,0 +1,149 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{BindingId, Error, Node, Position, Token, Type, TypedNode};
+
+use crate::constraint::Constraint;
+use crate::{type_error, TypeSystem};
+
+/*
+ * Access of the element `index` of a tuple, typed once the type of the tuple is known
+ */
+#[derive(Debug, Clone)]
+pub(crate) struct Access {
+    pub tuple: Type,
+    pub index: u32,
+    pub ty: Type,
+    pub pos: (Position, Position),
+}
+
+impl TypeSystem {
+    /*
+     * `var (a, b) = value` binds the tuple to a hidden variable, every name but `_`
+     * is declared as an access of it
+     */
+    pub(crate) fn destructure(
+        &mut self,
+        names: Vec<Token>,
+        value: Node,
+        reassignable: bool,
+        pos: (Position, Position),
+    ) -> Result<TypedNode, Error> {
+        let val = self.annotate(value)?;
+        let hidden = format!("%tuple{}%", pos.0.index);
+        let hidden_binding = BindingId::fresh();
+        let tuple = Type::Tuple(names.iter().map(|_| Type::fresh_var()).collect());
+
+        let mut stmts = vec![TypedNode::Let {
+            ty: tuple.clone(),
+            pos,
+            name: hidden.clone(),
+            binding: hidden_binding,
+            val: box val,
+        }];
+        for (index, name) in names.into_iter().enumerate() {
+            if name.value.into_string() == "_" {
+                continue;
+            }
+
+            let name_pos = (name.pos_start, name.pos_end);
+            let ty = Type::fresh_var();
+            let binding = if reassignable {
+                self.bind(name.value.into_string(), ty.clone())
+            } else {
+                self.bind_val(name.value.into_string(), ty.clone(), name_pos)
+            };
+            stmts.push(TypedNode::Let {
+                ty: ty.clone(),
+                pos: name_pos,
+                name: name.value.into_string(),
+                binding,
+                val: box TypedNode::TupleAccess {
+                    ty,
+                    pos: name_pos,
+                    tuple: box TypedNode::Var {
+                        ty: tuple.clone(),
+                        pos: name_pos,
+                        name: hidden.clone(),
+                        binding: hidden_binding,
+                    },
+                    index: index as u32,
+                },
+            });
+        }
+        Ok(TypedNode::Statements(stmts))
+    }
+
+    /*
+     * Types the accesses of tuple elements, an element may itself be a tuple
+     * so they are retried until none of them makes progress, the ones left are returned
+     * or reported when `last` is set
+     */
+    pub(crate) fn solve_accesses(
+        &mut self,
+        mut accesses: Vec<Access>,
+        last: bool,
+    ) -> Result<Vec<Access>, Error> {
+        loop {
+            let (known, unknown): (Vec<_>, Vec<_>) = accesses.into_iter().partition(|access| {
+                let tuple = self.subst.apply_ty(access.tuple.clone());
+                !matches!(tuple, Type::Var(_)) || self.numeric(&tuple).is_some()
+            });
+
+            if known.is_empty() {
+                return match unknown.first() {
+                    Some(access) if last => Err(type_error(
+                        "Missing Type",
+                        access.pos,
+                        "the type of the tuple has to be known, annotate it".to_string(),
+                    )),
+                    _ => Ok(unknown),
+                };
+            }
+
+            for access in known {
+                let element = self.element(&access)?;
+                let constraints = self
+                    .subst
+                    .apply(vec![Constraint(access.ty, element, access.pos)]);
+                let substitution = self.unify(constraints)?;
+                self.subst = self.subst.compose(substitution);
+            }
+            accesses = unknown;
+        }
+    }
+
+    /*
+     * Type of the element an access reads
+     */
+    fn element(&self, access: &Access) -> Result<Type, Error> {
+        match self.subst.apply_ty(access.tuple.clone()) {
+            Type::Tuple(elements) => match elements.get(access.index as usize) {
+                Some(element) => Ok(element.clone()),
+                None => Err(type_error(
+                    "Unknown Property",
+                    access.pos,
+                    format!(
+                        "the tuple has no element {}, it holds {}",
+                        access.index,
+                        elements.len()
+                    ),
+                )),
+            },
+            ty => Err(type_error(
+                "Mismatched Types",
+                access.pos,
+                format!("{} is not a tuple", self.describe(&ty)),
+            )),
+        }
+    }
+}
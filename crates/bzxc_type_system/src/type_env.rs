@@ -11,22 +11,27 @@
  * limitations under the License.
 */
 use std::collections::HashMap;
+use std::hash::Hash;
 
-use bzxc_shared::Type;
+use bzxc_shared::{BindingId, Type};
 
+/*
+ * Types of the bindings in scope, variables are keyed by the binding the resolver gave them
+ * and the names of types by the names themselves
+ */
 #[derive(Debug, Clone)]
-pub struct TypeEnv(Vec<HashMap<String, Type>>);
+pub struct TypeEnv<K = BindingId>(Vec<HashMap<K, Type>>);
 
-impl TypeEnv {
+impl<K: Eq + Hash> TypeEnv<K> {
     pub fn new() -> Self {
         Self(vec![HashMap::new()])
     }
 
-    pub fn set(&mut self, k: String, v: Type) {
+    pub fn set(&mut self, k: K, v: Type) {
         self.0.last_mut().unwrap().insert(k, v);
     }
 
-    pub fn get(&self, k: String) -> Option<Type> {
+    pub fn get(&self, k: K) -> Option<Type> {
         for map in self.0.iter().rev() {
             if let Some(v) = map.get(&k) {
                 return Some(v.clone());
@@ -36,6 +41,13 @@ impl TypeEnv {
         None
     }
 
+    /*
+     * Every binding in scope, shadowed ones included
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &Type)> {
+        self.0.iter().flat_map(|map| map.iter())
+    }
+
     pub fn push_scope(&mut self) {
         self.0.push(HashMap::new());
     }
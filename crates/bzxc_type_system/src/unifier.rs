@@ -12,100 +12,221 @@
 */
 use std::collections::BTreeMap;
 
-use bzxc_shared::Type;
+use bzxc_shared::{Error, Position, Type};
 
-use crate::{constraint::Constraint, substitution::Substitution, TypeSystem};
+use crate::{constraint::Constraint, substitution::Substitution, type_error, TypeSystem};
 
 impl TypeSystem {
-    pub(crate) fn unify(&mut self, constraints: Vec<Constraint>) -> Substitution {
+    pub(crate) fn unify(&mut self, constraints: Vec<Constraint>) -> Result<Substitution, Error> {
         if constraints.is_empty() {
-            Substitution::empty()
+            Ok(Substitution::empty())
         } else {
             let mut it = constraints.into_iter();
-            let mut subst = self.unify_one(it.next().unwrap());
+            let mut subst = self.unify_one(it.next().unwrap())?;
             let subst_tail = subst.apply(it.collect());
-            let subst_tail: Substitution = self.unify(subst_tail);
-            subst.compose(subst_tail)
+            let subst_tail: Substitution = self.unify(subst_tail)?;
+            Ok(subst.compose(subst_tail))
         }
     }
 
-    pub fn unify_one(&mut self, constraint: Constraint) -> Substitution {
+    pub fn unify_one(&mut self, constraint: Constraint) -> Result<Substitution, Error> {
+        let pos = constraint.2;
         match (constraint.0, constraint.1) {
             (Type::Fun(params, ret1), Type::Fun(args, ret2)) => {
+                self.check_arity(params.len(), args.len(), pos)?;
                 let mut constraints = vec![];
 
-                for i in 0..params.len() {
-                    constraints.push(Constraint(
-                        params.get(i).unwrap().clone(),
-                        args.get(i).unwrap().clone(),
-                    ));
+                for (param, arg) in params.iter().zip(args.iter()) {
+                    constraints.push(Constraint(param.clone(), arg.clone(), pos));
                 }
 
-                constraints.push(Constraint(*ret1.clone(), *ret2.clone()));
+                constraints.push(Constraint(*ret1.clone(), *ret2.clone(), pos));
 
                 self.unify(constraints)
             }
-            (Type::Class(ty1), Type::Class(ty2)) => self.unify_one(Constraint(*ty1, *ty2)),
-            (Type::Array(ty1, _), Type::Array(ty2, _)) => self.unify_one(Constraint(*ty1, *ty2)),
-            (Type::Object(tree1), Type::Object(tree2)) => {
-                let main_tree;
-                let other_tree;
+            (Type::Class(ty1), Type::Class(ty2)) => self.unify_one(Constraint(*ty1, *ty2, pos)),
+            (Type::Nullable(ty1), Type::Nullable(ty2)) => {
+                self.unify_one(Constraint(*ty1, *ty2, pos))
+            }
+            (Type::Array(ty1, _), Type::Array(ty2, _)) => {
+                self.unify_one(Constraint(*ty1, *ty2, pos))
+            }
+            (Type::Tuple(elements1), Type::Tuple(elements2))
+                if elements1.len() == elements2.len() =>
+            {
+                self.unify(
+                    elements1
+                        .into_iter()
+                        .zip(elements2)
+                        .map(|(ty1, ty2)| Constraint(ty1, ty2, pos))
+                        .collect(),
+                )
+            }
+            (Type::Object(id1, tree1), Type::Object(id2, tree2)) => {
+                // objects of two classes only unify when one of them extends the other
+                if id1.is_some() && id2.is_some() && id1 != id2 {
+                    let ty1 = Type::Object(id1, tree1.clone());
+                    let ty2 = Type::Object(id2, tree2.clone());
+                    self.check_extends(&ty1, &ty2, pos)
+                        .or_else(|_| self.check_extends(&ty2, &ty1, pos))?;
+                }
 
-                if tree1.len() > tree2.len() {
-                    main_tree = tree1;
-                    other_tree = tree2;
-                } else {
-                    main_tree = tree2;
-                    other_tree = tree1;
+                // an object is checked against the shape demanded of it
+                let (main_tree, other_tree) = match (id1, id2) {
+                    (Some(_), None) => (tree1, tree2),
+                    (None, Some(_)) => (tree2, tree1),
+                    _ if tree1.len() > tree2.len() => (tree1, tree2),
+                    _ => (tree2, tree1),
                 };
 
-                let a = main_tree.get("%alignment%");
-                let b = other_tree.get("%alignment%");
-
                 let mut constr = vec![];
                 for (name, ty1) in &other_tree {
-                    if name.as_str() == "%alignment%" {
+                    // the property or method is what's used, so its type comes first
+                    if let Some(ty2) = main_tree.get(name) {
+                        constr.push(Constraint(ty2.clone(), ty1.clone(), pos));
                         continue;
                     }
-                    let mut ty2 = main_tree.get(name);
 
-                    if ty2.is_none() {
-                        ty2 = self
-                            .methods
-                            .get(a.unwrap_or_else(|| b.unwrap()))
-                            .unwrap_or_else(|| self.methods.get(b.unwrap()).unwrap())
-                            .get(name);
+                    let method = [id1, id2].iter().flatten().find_map(|id| {
+                        let method = self.methods.get(id)?.get(name)?;
+                        Some((*id, self.subst.apply_ty(method.clone())))
+                    });
+                    match method {
+                        Some((id, ty2)) => {
+                            self.callee_member(id, name, pos);
+                            constr.push(Constraint(ty2, ty1.clone(), pos))
+                        }
+                        None => {
+                            // a class is named, and its definition pointed at
+                            let class = [id1, id2]
+                                .iter()
+                                .flatten()
+                                .copied()
+                                .find(|id| self.class_names.contains_key(id));
+                            let object = match class {
+                                Some(id) => {
+                                    self.callee_member(id, "constructor", pos);
+                                    self.class_names[&id].clone()
+                                }
+                                None => Type::Object(None, main_tree.clone()).to_string(),
+                            };
+                            return Err(type_error(
+                                "Unknown Property",
+                                pos,
+                                format!("`{}` has no property `{}`", object, name),
+                            ));
+                        }
                     }
+                }
 
-                    constr.push(Constraint(ty1.clone(), ty2.unwrap().clone()));
+                self.unify(constr)
+            }
+            (Type::Interface(name), Type::Object(id, tree))
+            | (Type::Object(id, tree), Type::Interface(name)) => {
+                let methods = &self.interfaces[&name];
+                let mut constr = vec![];
+                for (property, ty) in &tree {
+                    match methods.get(property) {
+                        Some(method) if id.is_none() => {
+                            constr.push(Constraint(method.clone(), ty.clone(), pos))
+                        }
+                        Some(_) => {
+                            return Err(type_error(
+                                "Mismatched Types",
+                                pos,
+                                format!("expected `{}`, found `{}`", name, Type::Object(id, tree)),
+                            ))
+                        }
+                        None => {
+                            return Err(type_error(
+                                "Unknown Property",
+                                pos,
+                                format!("`{}` has no method `{}`", name, property),
+                            ))
+                        }
+                    }
                 }
 
                 self.unify(constr)
             }
-            (Type::Var(tvar), ty) => self.unify_var(tvar, ty),
-            (ty, Type::Var(tvar)) => self.unify_var(tvar, ty),
+            (Type::Tuple(elements1), Type::Tuple(elements2)) => Err(type_error(
+                "Mismatched Types",
+                pos,
+                format!(
+                    "expected a tuple of {} elements, found one of {}",
+                    elements1.len(),
+                    elements2.len()
+                ),
+            )),
+            // properties are looked up on a value which has none
+            (Type::Object(None, tree), other) | (other, Type::Object(None, tree))
+                if self.numeric(&other).is_some()
+                    || !matches!(
+                        other,
+                        Type::Var(_) | Type::Object(..) | Type::Class(_) | Type::Nullable(_)
+                    ) =>
+            {
+                let property = tree.keys().next().cloned().unwrap_or_default();
+                Err(type_error(
+                    "Unknown Property",
+                    pos,
+                    format!("{} has no property `{}`", self.describe(&other), property),
+                ))
+            }
+            (Type::Var(tvar), ty) => {
+                self.check_numeric(&Type::Var(tvar), &ty, pos)?;
+                self.unify_var(tvar, ty, pos)
+            }
+            (ty, Type::Var(tvar)) => {
+                self.check_numeric(&ty, &Type::Var(tvar), pos)?;
+                self.unify_var(tvar, ty, pos)
+            }
+            (Type::Nullable(ty), other) | (other, Type::Nullable(ty)) => Err(type_error(
+                "Mismatched Types",
+                pos,
+                match *ty {
+                    Type::Var(_) => format!("`{}` can't be null", self.type_name(&other)),
+                    _ => format!(
+                        "`{}?` may be null, check it against `null` first",
+                        self.type_name(&ty)
+                    ),
+                },
+            )),
             (a, b) => {
                 if a == b {
-                    Substitution::empty()
+                    Ok(Substitution::empty())
                 } else {
-                    panic!("Cannot unify {:#?} with {:#?}", a, b)
+                    Err(type_error(
+                        "Mismatched Types",
+                        pos,
+                        format!("expected `{}`, found `{}`", a, b),
+                    ))
                 }
             }
         }
     }
 
-    pub fn unify_var(&mut self, tvar: i32, ty: Type) -> Substitution {
+    pub fn unify_var(
+        &mut self,
+        tvar: i32,
+        ty: Type,
+        pos: (Position, Position),
+    ) -> Result<Substitution, Error> {
         match ty.clone() {
             Type::Var(tvar2) => {
                 if tvar == tvar2 {
-                    Substitution::empty()
+                    Ok(Substitution::empty())
                 } else {
-                    Substitution(BTreeMap::from([(Type::Var(tvar), ty)]))
+                    Ok(Substitution(BTreeMap::from([(Type::Var(tvar), ty)])))
                 }
             }
             _ => {
                 if self.occurs(tvar, ty.clone()) {
-                    panic!("circular type")
+                    Err(type_error(
+                        "Infinite Type",
+                        pos,
+                        format!("the type `{}` would have to contain itself", ty),
+                    ))
                 } else {
                     let methods = self.methods.clone();
                     for (x, class) in methods {
@@ -118,7 +239,7 @@ impl TypeSystem {
                             }
                         }
                     }
-                    Substitution(BTreeMap::from([(Type::Var(tvar), ty)]))
+                    Ok(Substitution(BTreeMap::from([(Type::Var(tvar), ty)])))
                 }
             }
         }
@@ -134,8 +255,11 @@ impl TypeSystem {
                     | self.occurs(tvar, *r.clone())
             }
             Type::Array(arr, _) => self.occurs(tvar, *arr),
-            Type::Class(klass) => self.occurs(tvar, *klass),
-            Type::Object(obj) => obj
+            Type::Class(klass) | Type::Nullable(klass) => self.occurs(tvar, *klass),
+            Type::Tuple(elements) => elements
+                .into_iter()
+                .any(|element| self.occurs(tvar, element)),
+            Type::Object(_, obj) => obj
                 .iter()
                 .map(|(_, ty)| self.occurs(tvar, ty.clone()))
                 .collect::<Vec<bool>>()
@@ -0,0 +1,77 @@
+/*
+ * Copyright 2020 to 2021 BlazifyOrg
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *    http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+*/
+use bzxc_shared::{BindingId, Error, Position, Token};
+
+use crate::{type_error, TypeSystem};
+
+impl TypeSystem {
+    /*
+     * Reassigning `name` is rejected when `binding` is a `val` or a `const`
+     */
+    pub(crate) fn check_reassignable(
+        &self,
+        name: &Token,
+        binding: BindingId,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        let kind = if self.consts.contains_key(&binding) {
+            "const"
+        } else {
+            "val"
+        };
+        match self.resolver.val(binding) {
+            Some((start, end)) => Err(type_error(
+                "Immutable Variable",
+                pos,
+                format!(
+                    "`{}` is a `{}`, it can't be reassigned",
+                    name.value.into_string(),
+                    kind
+                ),
+            )
+            .with_label(start, end, "bound here, make it a `var` to reassign it")),
+            None => Ok(()),
+        }
+    }
+
+    /*
+     * With frozen vals the properties of an object bound with `val` can't be changed either
+     */
+    pub(crate) fn check_frozen(
+        &self,
+        name: &Token,
+        pos: (Position, Position),
+    ) -> Result<(), Error> {
+        if !self.freeze_vals {
+            return Ok(());
+        }
+
+        let binding = match self.resolver.resolve(&name.value.into_string()) {
+            Some(binding) => binding,
+            None => return Ok(()),
+        };
+
+        match self.resolver.val(binding) {
+            Some((start, end)) => Err(type_error(
+                "Immutable Variable",
+                pos,
+                format!(
+                    "`{}` is a `val`, its properties can't be changed",
+                    name.value.into_string()
+                ),
+            )
+            .with_label(start, end, "bound here, make it a `var` to change them")),
+            None => Ok(()),
+        }
+    }
+}